@@ -0,0 +1,20 @@
+#!/bin/sh
+# An example of the shell notifier, it appends the notifications to a file.
+#
+# notify:
+#   shell:
+#     - name: "shell alert service"
+#       cmd: "/bin/sh"
+#       args:
+#         - "resources/scripts/notify/notify.sh"
+#       env:
+#         - "EASEPROBE_NOTIFY_FILE=/tmp/easeprobe-notify.log"
+
+FILE=${EASEPROBE_NOTIFY_FILE:-/tmp/easeprobe-notify.log}
+
+if [ "$EASEPROBE_TYPE" = "SLA" ]; then
+    echo "[$EASEPROBE_TIME] $EASEPROBE_TITLE" >> "$FILE"
+    cat >> "$FILE"
+else
+    echo "[$EASEPROBE_TIME] $EASEPROBE_NAME ($EASEPROBE_KIND) $EASEPROBE_PRE_STATUS -> $EASEPROBE_STATUS - $EASEPROBE_MESSAGE" >> "$FILE"
+fi
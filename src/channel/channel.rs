@@ -5,20 +5,38 @@ use std::{collections::HashMap, time::Duration};
 use tokio::sync::{mpsc, Mutex, Notify, RwLock};
 
 use crate::{
-    global, DefaultNotifier, DefaultProber, Format, NotificationStrategySettings, Notifier,
+    global, metric, DefaultNotifier, DefaultProber, Format, NotificationStrategySettings, Notifier,
     ProbeBehavior, ProbeResult, Prober, Status, StatusChangeThresholdSettings,
 };
 
-use super::manager::is_dry_notify;
+use super::{
+    manager::{is_channel_muted, is_dry_notify, is_notifier_muted},
+    MaintenanceWindow,
+};
 
 const KIND: &str = "channel";
 
 type Notifiers = Arc<Mutex<HashMap<String, Arc<RwLock<dyn Notifier>>>>>;
 
+/// The suppressed notifications of the probers in the maintenance window.
+#[derive(Debug, Clone, Default)]
+struct Suppressed {
+    window: String,
+    count: usize,
+}
+
+/// The suppressed notifications by the kind and name of the probers.
+type Maintenance = Arc<Mutex<HashMap<(String, String), Suppressed>>>;
+
+/// The maintenance windows of the channel.
+type Windows = Arc<std::sync::RwLock<Vec<MaintenanceWindow>>>;
+
 pub struct Channel {
     name: String,
     probers: Mutex<HashMap<String, Arc<RwLock<dyn Prober>>>>,
     pub(crate) notifiers: Notifiers,
+    windows: Windows,
+    maintenance: Maintenance,
     stop_notify: Arc<Notify>,
     channel: mpsc::Sender<ProbeResult>,
 }
@@ -30,6 +48,8 @@ impl Channel {
             name: name.to_string(),
             probers: Mutex::new(HashMap::new()),
             notifiers: Arc::new(Mutex::new(HashMap::new())),
+            windows: Windows::default(),
+            maintenance: Arc::new(Mutex::new(HashMap::new())),
             stop_notify: Arc::new(Notify::new()),
             channel: channel_tx,
         };
@@ -37,6 +57,7 @@ impl Channel {
         res
     }
 
+    #[cfg(test)]
     pub async fn get_prober(&self, name: &str) -> Option<Arc<RwLock<dyn Prober>>> {
         let res = {
             let probers = self.probers.lock().await;
@@ -45,6 +66,10 @@ impl Channel {
         res
     }
 
+    pub async fn get_probers(&self) -> Vec<Arc<RwLock<dyn Prober>>> {
+        self.probers.lock().await.values().cloned().collect()
+    }
+
     pub async fn add_prober(&self, prober: Arc<RwLock<dyn Prober>>) {
         let prober_clone = Arc::clone(&prober);
 
@@ -61,17 +86,20 @@ impl Channel {
         probers.insert(p.name().to_string(), prober_clone);
     }
 
+    #[cfg(test)]
     pub async fn add_probers(&self, probers: Vec<Arc<RwLock<dyn Prober>>>) {
         for p in probers {
             self.add_prober(p).await
         }
     }
 
+    #[cfg(test)]
     pub async fn get_notifier(&self, name: &str) -> Option<Arc<RwLock<dyn Notifier>>> {
         let notifiers = self.notifiers.lock().await;
-        notifiers.get(name).map(|v| Arc::clone(v))
+        notifiers.get(name).map(Arc::clone)
     }
 
+    #[cfg(test)]
     pub async fn add_notifiers(&self, notifiers: Vec<Arc<RwLock<dyn Notifier>>>) {
         for n in notifiers {
             self.add_notifier(n).await;
@@ -95,6 +123,11 @@ impl Channel {
         notifiers.insert(n.name().to_string(), notifier_clone);
     }
 
+    /// Replaces the maintenance windows, the notifications of the probers in the open windows are suppressed.
+    pub fn set_maintenance(&self, windows: Vec<MaintenanceWindow>) {
+        *self.windows.write().unwrap() = windows;
+    }
+
     pub async fn stop(&self) {
         self.stop_notify.notify_waiters();
     }
@@ -114,6 +147,8 @@ impl Channel {
         let stop_notify = Arc::clone(&self.stop_notify);
         let channel_name = self.name.clone();
         let notifiers = Arc::clone(&self.notifiers);
+        let windows = Arc::clone(&self.windows);
+        let maintenance = Arc::clone(&self.maintenance);
 
         tokio::spawn(async move {
             loop {
@@ -124,7 +159,7 @@ impl Channel {
                     }
                     result = channel.recv() => {
                         if let Some(result) = result {
-                            Self::handle_result(&channel_name, result, &notifiers).await;
+                            Self::handle_result(&channel_name, result, &notifiers, &windows, &maintenance).await;
                         }
                     }
                 }
@@ -132,7 +167,107 @@ impl Channel {
         });
     }
 
-    async fn handle_result(channel_name: &String, mut result: ProbeResult, notifiers: &Notifiers) {
+    /// The name of the open maintenance window of the prober in the channel.
+    fn maintenance_window(
+        windows: &Windows,
+        kind: &str,
+        name: &str,
+        channel: &str,
+    ) -> Option<String> {
+        let now = std::time::SystemTime::now();
+        windows
+            .read()
+            .unwrap()
+            .iter()
+            .find(|w| w.matches(kind, name, channel) && w.is_active(now))
+            .map(|w| w.name.clone())
+    }
+
+    async fn handle_result(
+        channel_name: &String,
+        mut result: ProbeResult,
+        notifiers: &Notifiers,
+        windows: &Windows,
+        maintenance: &Maintenance,
+    ) {
+        metric::observe_probe(&result);
+
+        let need_notify = Self::need_notify(channel_name, &mut result);
+        let key = (result.kind.clone(), result.name.clone());
+        if let Some(window) =
+            Self::maintenance_window(windows, &result.kind, &result.name, channel_name)
+        {
+            let mut maintenance = maintenance.lock().await;
+            // the prober is tracked only if a notification is suppressed,
+            // e.g. it's not alerted again if it was down before the window
+            if need_notify {
+                log::info!(
+                    "[{} / {}]: {} ({}) - In the maintenance window [{}], the notification is suppressed.",
+                    KIND,
+                    channel_name,
+                    result.name,
+                    result.endpoint,
+                    window
+                );
+                let suppressed = maintenance.entry(key).or_default();
+                suppressed.count += 1;
+                suppressed.window = window;
+            } else if let Some(suppressed) = maintenance.get_mut(&key) {
+                suppressed.window = window;
+            }
+            return;
+        }
+
+        // the maintenance window is closed, alert once if the prober is still failing
+        let suppressed = maintenance.lock().await.remove(&key);
+        if let Some(suppressed) = suppressed {
+            if result.status != Status::Up && result.status != Status::Init {
+                log::info!(
+                    "[{} / {}]: {} ({}) - Still [{}] after the maintenance window [{}], sending notification...",
+                    KIND,
+                    channel_name,
+                    result.name,
+                    result.endpoint,
+                    result.status,
+                    suppressed.window
+                );
+                result.message = format!(
+                    "Still {} after the maintenance window {} ({} notifications suppressed) - {}",
+                    result.status, suppressed.window, suppressed.count, result.message
+                );
+                Self::notify(channel_name, result, notifiers).await;
+                return;
+            }
+        }
+
+        if !need_notify {
+            return;
+        }
+        if result.pre_status != result.status {
+            log::info!(
+                "[{} / {}]: {} ({}) - Status changed [{}] ==> [{}], sending notification...",
+                KIND,
+                channel_name,
+                result.name,
+                result.endpoint,
+                result.pre_status,
+                result.status
+            );
+        } else {
+            log::debug!(
+                "[{} / {}]: {} ({}) - Meet the notification condition, sending notification...",
+                KIND,
+                channel_name,
+                result.name,
+                result.endpoint
+            );
+        }
+
+        Self::notify(channel_name, result, notifiers).await;
+    }
+
+    /// Whether the result is notified by the status changes and the notification strategy.
+    fn need_notify(channel_name: &String, result: &mut ProbeResult) -> bool {
         // if it is the first time, and the status is UP, no need notify
         if result.pre_status == Status::Init && result.status == Status::Up {
             log::debug!(
@@ -144,7 +279,7 @@ impl Channel {
                 result.pre_status,
                 result.status
             );
-            return;
+            return false;
         }
 
         // if the status has no change for UP or Init, no need notify
@@ -160,7 +295,7 @@ impl Channel {
                 result.pre_status,
                 result.status
             );
-            return;
+            return false;
         }
 
         // if the status changed to UP, reset the notification strategy
@@ -178,33 +313,40 @@ impl Channel {
                 result.name,
                 result.endpoint
             );
-            return;
+            return false;
         }
 
-        if result.pre_status != result.status {
+        true
+    }
+
+    /// Sends the result to the notifiers which are not muted.
+    async fn notify(channel_name: &String, result: ProbeResult, notifiers: &Notifiers) {
+        if is_channel_muted(channel_name) {
             log::info!(
-                "[{} / {}]: {} ({}) - Status changed [{}] ==> [{}], sending notification...",
-                KIND,
-                channel_name,
-                result.name,
-                result.endpoint,
-                result.pre_status,
-                result.status
-            );
-        } else {
-            log::debug!(
-                "[{} / {}]: {} ({}) - Meet the notification condition, sending notification...",
+                "[{} / {}]: {} ({}) - The channel is muted, no notification.",
                 KIND,
                 channel_name,
                 result.name,
                 result.endpoint
             );
+            return;
         }
 
         let result = Arc::new(result);
         let notifiers = notifiers.lock().await;
         for notifier in notifiers.values() {
             let n = notifier.read().await;
+            if is_notifier_muted(n.name()) {
+                log::info!(
+                    "[{} / {}]: {} ({}) - The notifier [{}] is muted, no notification.",
+                    KIND,
+                    channel_name,
+                    result.name,
+                    result.endpoint,
+                    n.name()
+                );
+                continue;
+            }
             let t = Arc::clone(&result);
             if is_dry_notify() {
                 n.dry_notify(t);
@@ -220,6 +362,54 @@ impl Channel {
     }
 }
 
+#[allow(dead_code)]
+pub(crate) fn new_dummy_notify(
+    kind: &str,
+    name: &str,
+    channels: Vec<String>,
+) -> RwLock<impl Notifier> {
+    RwLock::new(DefaultNotifier {
+        kind: kind.to_string(),
+        name: name.to_string(),
+        format: Format::Text,
+        send_func: None,
+        channels,
+        dry: false,
+        timeout: Duration::default(),
+        retry: global::Retry::default(),
+    })
+}
+
+#[allow(dead_code)]
+pub(crate) fn new_dummy_prober(
+    kind: &str,
+    tag: &str,
+    name: &str,
+    channels: Vec<String>,
+) -> RwLock<impl Prober> {
+    RwLock::new(DefaultProber {
+        kind: kind.to_string(),
+        name: name.to_string(),
+        tag: tag.to_string(),
+        channels,
+        timeout: Duration::new(1, 0),
+        interval: Duration::new(5, 0),
+        result: ProbeResult::default(),
+        behavior: DummyProbeBehavior,
+        threshold: StatusChangeThresholdSettings::default(),
+        notification: NotificationStrategySettings::default(),
+    })
+}
+
+pub struct DummyProbeBehavior;
+
+#[async_trait]
+impl ProbeBehavior for DummyProbeBehavior {
+    async fn do_probe(&self) -> Result<(bool, String)> {
+        Ok((true, "Dummy probe succeeded".to_string()))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -297,52 +487,135 @@ mod tests {
             "http"
         );
     }
-}
-
-#[allow(dead_code)]
-pub(crate) fn new_dummy_notify(
-    kind: &str,
-    name: &str,
-    channels: Vec<String>,
-) -> RwLock<impl Notifier> {
-    RwLock::new(DefaultNotifier {
-        kind: kind.to_string(),
-        name: name.to_string(),
-        format: Format::Text,
-        send_func: None,
-        channels,
-        dry: false,
-        timeout: Duration::default(),
-        retry: global::Retry::default(),
-    })
-}
 
-#[allow(dead_code)]
-pub(crate) fn new_dummy_prober(
-    kind: &str,
-    tag: &str,
-    name: &str,
-    channels: Vec<String>,
-) -> RwLock<impl Prober> {
-    RwLock::new(DefaultProber {
-        kind: kind.to_string(),
-        name: name.to_string(),
-        tag: tag.to_string(),
-        channels,
-        timeout: Duration::new(1, 0),
-        interval: Duration::new(5, 0),
-        result: ProbeResult::default(),
-        behavior: DummyProbeBehavior,
-        threshold: StatusChangeThresholdSettings::default(),
-        notification: NotificationStrategySettings::default(),
-    })
-}
+    #[tokio::test]
+    async fn test_maintenance() {
+        let sent = Arc::new(std::sync::Mutex::new(vec![]));
+        let capture = Arc::clone(&sent);
+        let notifier: Arc<RwLock<dyn Notifier>> = Arc::new(RwLock::new(DefaultNotifier {
+            kind: "capture".to_string(),
+            name: "maintenance".to_string(),
+            format: Format::Text,
+            send_func: Some(Box::new(move |n: crate::Notification| {
+                capture.lock().unwrap().push(n);
+                Box::pin(async { Ok(()) })
+            })),
+            channels: vec![],
+            dry: false,
+            timeout: Duration::from_secs(1),
+            retry: global::Retry {
+                times: 1,
+                interval: Duration::ZERO,
+            },
+        }));
+        let notifiers: Notifiers = Arc::new(Mutex::new(HashMap::from([(
+            "maintenance".to_string(),
+            notifier,
+        )])));
+        let windows = Windows::default();
+        let maintenance = Maintenance::default();
+
+        let now = chrono::Utc::now();
+        let conf = crate::conf::Maintenance {
+            name: "deploy".to_string(),
+            start: (now - chrono::Duration::minutes(1)).to_rfc3339(),
+            end: (now + chrono::Duration::hours(1)).to_rfc3339(),
+            probers: vec!["^maintenance-".to_string()],
+            ..Default::default()
+        };
+        let window = MaintenanceWindow::new(&conf, chrono_tz::Tz::UTC).unwrap();
+        *windows.write().unwrap() = vec![window];
+
+        let channel = "maintenance".to_string();
+        let result = |pre_status, status| ProbeResult {
+            name: "maintenance-web".to_string(),
+            kind: "http".to_string(),
+            pre_status,
+            status,
+            message: "HTTP Status Code is 502".to_string(),
+            ..Default::default()
+        };
+        // only the results which would be notified are counted
+        for (pre, status) in [
+            (Status::Up, Status::Bad),
+            (Status::Bad, Status::Down),
+            (Status::Down, Status::Down),
+            (Status::Down, Status::Bad),
+        ] {
+            Channel::handle_result(
+                &channel,
+                result(pre, status),
+                &notifiers,
+                &windows,
+                &maintenance,
+            )
+            .await;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(sent.lock().unwrap().is_empty());
+
+        // the window is closed with the prober still failing
+        windows.write().unwrap().clear();
+        Channel::handle_result(
+            &channel,
+            result(Status::Bad, Status::Bad),
+            &notifiers,
+            &windows,
+            &maintenance,
+        )
+        .await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(maintenance.lock().await.is_empty());
+        {
+            let sent = sent.lock().unwrap();
+            assert_eq!(sent.len(), 1);
+            assert_eq!(
+                sent[0].result.as_ref().unwrap().message,
+                "Still bad after the maintenance window deploy (2 notifications suppressed) - HTTP Status Code is 502"
+            );
+        }
 
-pub struct DummyProbeBehavior;
+        // the prober was down and alerted before the window, it's not alerted again after it
+        let window = MaintenanceWindow::new(&conf, chrono_tz::Tz::UTC).unwrap();
+        *windows.write().unwrap() = vec![window];
+        for _ in 0..2 {
+            Channel::handle_result(
+                &channel,
+                result(Status::Down, Status::Down),
+                &notifiers,
+                &windows,
+                &maintenance,
+            )
+            .await;
+        }
+        assert!(maintenance.lock().await.is_empty());
+        windows.write().unwrap().clear();
+        Channel::handle_result(
+            &channel,
+            result(Status::Down, Status::Down),
+            &notifiers,
+            &windows,
+            &maintenance,
+        )
+        .await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(sent.lock().unwrap().len(), 1);
+    }
 
-#[async_trait]
-impl ProbeBehavior for DummyProbeBehavior {
-    async fn do_probe(&self) -> Result<(bool, String)> {
-        Ok((true, "Dummy probe succeeded".to_string()))
+    #[tokio::test]
+    async fn test_metrics() {
+        let ch = Channel::new("metric").await;
+        ch.send(ProbeResult {
+            name: "channel-metric".to_string(),
+            kind: "tcp".to_string(),
+            status: Status::Up,
+            ..Default::default()
+        })
+        .await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(metric::render().contains(
+            r#"easeprobe_probe_status{kind="tcp",name="channel-metric",endpoint="",status="up"} 1"#
+        ));
+        ch.stop().await;
     }
 }
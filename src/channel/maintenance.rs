@@ -0,0 +1,345 @@
+use std::time::SystemTime;
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, Timelike, Utc};
+use chrono_tz::Tz;
+use regex::Regex;
+
+use crate::{conf::Maintenance, local_to_utc};
+
+/// A field of the cron expression, the bit `n` is set if the value `n` matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CronField {
+    bits: u64,
+    /// Whether it's `*`, the day and the weekday are matched by either of them if neither is `*`
+    any: bool,
+}
+
+impl CronField {
+    /// Parses the values, the ranges `a-b`, the steps `*/n` or `a-b/n`, separated by commas.
+    fn parse(field: &str, min: u32, max: u32) -> Result<Self> {
+        let num = |s: &str| {
+            s.parse::<u32>()
+                .with_context(|| format!("invalid number `{}`", s))
+        };
+        let mut bits = 0u64;
+        for part in field.split(',') {
+            let (range, step) = match part.split_once('/') {
+                Some((range, step)) => match step.parse::<u32>() {
+                    Ok(step) if step > 0 => (range, step),
+                    _ => bail!("invalid step `{}`", part),
+                },
+                None => (part, 1),
+            };
+            let (lo, hi) = match range.split_once('-') {
+                _ if range == "*" => (min, max),
+                Some((lo, hi)) => (num(lo)?, num(hi)?),
+                // `n/step` means from `n` to the max
+                None if step > 1 => (num(range)?, max),
+                None => (num(range)?, num(range)?),
+            };
+            if lo < min || hi > max || lo > hi {
+                bail!("`{}` is out of the range {}-{}", part, min, max);
+            }
+            for v in (lo..=hi).step_by(step as usize) {
+                bits |= 1 << v;
+            }
+        }
+        Ok(Self {
+            bits,
+            any: field == "*",
+        })
+    }
+
+    fn matches(&self, v: u32) -> bool {
+        self.bits & (1 << v) != 0
+    }
+}
+
+/// The cron expression `min hour day month weekday`, the weekday is 0-7 from Sunday.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Cron {
+    minute: CronField,
+    hour: CronField,
+    day: CronField,
+    month: CronField,
+    weekday: CronField,
+}
+
+impl Cron {
+    fn parse(expr: &str) -> Result<Self> {
+        let fields: Vec<_> = expr.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            bail!("the cron `{}` must have 5 fields", expr);
+        };
+        let mut weekday = CronField::parse(weekday, 0, 7)?;
+        // both 0 and 7 are Sunday
+        if weekday.matches(7) {
+            weekday.bits |= 1;
+        }
+        Ok(Self {
+            minute: CronField::parse(minute, 0, 59)?,
+            hour: CronField::parse(hour, 0, 23)?,
+            day: CronField::parse(day, 1, 31)?,
+            month: CronField::parse(month, 1, 12)?,
+            weekday,
+        })
+    }
+
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        let day = self.day.matches(date.day());
+        let weekday = self.weekday.matches(date.weekday().num_days_from_sunday());
+        let matched = if self.day.any || self.weekday.any {
+            day && weekday
+        } else {
+            day || weekday
+        };
+        matched && self.month.matches(date.month())
+    }
+
+    /// The latest time matching the cron in `since..=now`, it walks back by the days
+    /// and takes the last matching hour and minute of the first matching day.
+    fn latest(&self, now: NaiveDateTime, since: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut date = now.date();
+        while date >= since.date() {
+            if self.matches_date(date) {
+                let last_hour = if date == now.date() { now.hour() } else { 23 };
+                for hour in (0..=last_hour).rev().filter(|h| self.hour.matches(*h)) {
+                    let last_minute = if date == now.date() && hour == now.hour() {
+                        now.minute()
+                    } else {
+                        59
+                    };
+                    if let Some(minute) = (0..=last_minute).rev().find(|m| self.minute.matches(*m))
+                    {
+                        let t = date.and_hms_opt(hour, minute, 0)?;
+                        return (t >= since).then_some(t);
+                    }
+                }
+            }
+            date = date.pred_opt()?;
+        }
+        None
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Period {
+    Once {
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    },
+    Recurring {
+        cron: Cron,
+        duration: Duration,
+        tz: Tz,
+    },
+}
+
+/// The compiled maintenance window.
+#[derive(Debug, Clone)]
+pub struct MaintenanceWindow {
+    pub name: String,
+    period: Period,
+    probers: Vec<Regex>,
+    kinds: Vec<String>,
+    channels: Vec<String>,
+}
+
+/// Parses the time in RFC 3339, or the local time of the time zone.
+fn parse_datetime(s: &str, tz: &Tz) -> Result<DateTime<Utc>> {
+    if let Ok(t) = DateTime::parse_from_rfc3339(s) {
+        return Ok(t.with_timezone(&Utc));
+    }
+    for fmt in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"] {
+        if let Ok(t) = NaiveDateTime::parse_from_str(s, fmt) {
+            return Ok(local_to_utc(tz, t));
+        }
+    }
+    bail!("invalid time `{}`, expect `YYYY-mm-dd HH:MM[:SS]`", s)
+}
+
+impl MaintenanceWindow {
+    /// Compiles the window, the times are in the time zone.
+    pub fn new(conf: &Maintenance, tz: Tz) -> Result<Self> {
+        let name = &conf.name;
+        if name.is_empty() {
+            bail!("the name of the maintenance window is required");
+        }
+        let period = match (conf.cron.is_empty(), conf.start.is_empty()) {
+            (false, true) => {
+                if conf.duration.is_zero() {
+                    bail!(
+                        "the duration of the maintenance window `{}` is required",
+                        name
+                    );
+                }
+                Period::Recurring {
+                    cron: Cron::parse(&conf.cron)
+                        .with_context(|| format!("maintenance window `{}`", name))?,
+                    duration: Duration::from_std(conf.duration)?,
+                    tz,
+                }
+            }
+            (true, false) => {
+                let start = parse_datetime(&conf.start, &tz)?;
+                let end = if conf.end.is_empty() {
+                    start + Duration::from_std(conf.duration)?
+                } else {
+                    parse_datetime(&conf.end, &tz)?
+                };
+                if end <= start {
+                    bail!("the maintenance window `{}` ends before it starts", name);
+                }
+                Period::Once { start, end }
+            }
+            _ => bail!(
+                "the maintenance window `{}` requires either the cron or the start",
+                name
+            ),
+        };
+        let probers = conf
+            .probers
+            .iter()
+            .map(|p| Regex::new(p).with_context(|| format!("maintenance window `{}`", name)))
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            name: name.clone(),
+            period,
+            probers,
+            kinds: conf.kinds.clone(),
+            channels: conf.channels.clone(),
+        })
+    }
+
+    /// Whether the prober in the channel is in the scope, the empty scopes match any.
+    pub fn matches(&self, kind: &str, name: &str, channel: &str) -> bool {
+        (self.probers.is_empty() || self.probers.iter().any(|re| re.is_match(name)))
+            && (self.kinds.is_empty() || self.kinds.iter().any(|k| k == kind))
+            && (self.channels.is_empty() || self.channels.iter().any(|c| c == channel))
+    }
+
+    /// Whether the window is open at the time.
+    pub fn is_active(&self, now: SystemTime) -> bool {
+        let now = DateTime::<Utc>::from(now);
+        match &self.period {
+            Period::Once { start, end } => *start <= now && now < *end,
+            Period::Recurring { cron, duration, tz } => {
+                let now = now.with_timezone(tz).naive_local();
+                // the latest window starting within the duration before now
+                cron.latest(now, now - *duration)
+                    .is_some_and(|start| start + *duration > now)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(f: impl FnOnce(&mut Maintenance)) -> Result<MaintenanceWindow> {
+        let mut conf = Maintenance {
+            name: "deploy".to_string(),
+            ..Default::default()
+        };
+        f(&mut conf);
+        MaintenanceWindow::new(&conf, "Asia/Shanghai".parse().unwrap())
+    }
+
+    fn at(s: &str) -> SystemTime {
+        DateTime::parse_from_rfc3339(s).unwrap().into()
+    }
+
+    #[test]
+    fn test_cron() {
+        let t = |s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap();
+        let matches = |cron: &Cron, s| cron.latest(t(s), t(s)).is_some();
+        let cron = Cron::parse("*/15 2-4 * * 1-5,7").unwrap();
+        // Monday
+        assert!(matches(&cron, "2024-01-01 02:45"));
+        assert!(!matches(&cron, "2024-01-01 02:50"));
+        assert!(!matches(&cron, "2024-01-01 05:00"));
+        // Saturday and Sunday
+        assert!(!matches(&cron, "2024-01-06 03:00"));
+        assert!(matches(&cron, "2024-01-07 03:00"));
+
+        // either the day or the weekday
+        let cron = Cron::parse("0 0 1 * 0").unwrap();
+        assert!(matches(&cron, "2024-02-01 00:00"));
+        assert!(matches(&cron, "2024-02-04 00:00"));
+        assert!(!matches(&cron, "2024-02-05 00:00"));
+
+        // the latest start before now
+        assert_eq!(
+            cron.latest(t("2024-02-06 12:00"), t("2024-01-01 00:00")),
+            Some(t("2024-02-04 00:00"))
+        );
+        assert_eq!(
+            cron.latest(t("2024-02-06 12:00"), t("2024-02-05 00:00")),
+            None
+        );
+
+        for expr in [
+            "* * * *",
+            "60 * * * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "a * * * *",
+        ] {
+            assert!(Cron::parse(expr).is_err(), "{}", expr);
+        }
+    }
+
+    #[test]
+    fn test_window() {
+        // 03:00-04:00 in Shanghai every day
+        let w = window(|c| {
+            c.cron = "0 3 * * *".to_string();
+            c.duration = std::time::Duration::from_secs(3600);
+        })
+        .unwrap();
+        assert!(!w.is_active(at("2024-01-01T18:59:59Z")));
+        assert!(w.is_active(at("2024-01-01T19:00:00Z")));
+        assert!(w.is_active(at("2024-01-01T19:59:59Z")));
+        assert!(!w.is_active(at("2024-01-01T20:00:00Z")));
+
+        // the first 30 days of the year
+        let w = window(|c| {
+            c.cron = "0 0 1 1 *".to_string();
+            c.duration = std::time::Duration::from_secs(30 * 24 * 3600);
+        })
+        .unwrap();
+        assert!(w.is_active(at("2024-01-20T00:00:00+08:00")));
+        assert!(!w.is_active(at("2024-01-31T00:00:00+08:00")));
+        assert!(!w.is_active(at("2023-12-31T23:59:59+08:00")));
+
+        let w = window(|c| {
+            c.start = "2024-01-01 03:00".to_string();
+            c.end = "2024-01-01T03:30:00+08:00".to_string();
+            c.probers = vec!["^web-".to_string()];
+            c.kinds = vec!["http".to_string()];
+        })
+        .unwrap();
+        assert!(!w.is_active(at("2024-01-01T19:29:00Z")));
+        assert!(w.is_active(at("2023-12-31T19:29:00Z")));
+        assert!(!w.is_active(at("2023-12-31T19:30:00Z")));
+        assert!(w.matches("http", "web-1", "any"));
+        assert!(!w.matches("tcp", "web-1", "any"));
+        assert!(!w.matches("http", "db", "any"));
+
+        assert!(window(|_| {}).is_err());
+        assert!(window(|c| c.cron = "0 3 * * *".to_string()).is_err());
+        assert!(window(|c| {
+            c.start = "2024-01-01 03:00".to_string();
+            c.end = "2024-01-01 02:00".to_string();
+        })
+        .is_err());
+        assert!(window(|c| {
+            c.start = "2024-01-01 03:00".to_string();
+            c.probers = vec!["(".to_string()];
+            c.duration = std::time::Duration::from_secs(60);
+        })
+        .is_err());
+    }
+}
@@ -1,16 +1,17 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc, LazyLock,
     },
+    time::SystemTime,
 };
 
 use tokio::sync::{Mutex, RwLock};
 
 use crate::{Notifier, Prober};
 
-use super::Channel;
+use super::{Channel, MaintenanceWindow};
 
 static CHANNELS: LazyLock<Mutex<HashMap<String, Arc<Channel>>>> =
     LazyLock::new(|| Mutex::new(HashMap::new()));
@@ -25,6 +26,139 @@ pub fn is_dry_notify() -> bool {
     DRY_NOTIFY.load(Ordering::SeqCst)
 }
 
+/// The paused probers by the kind and name.
+static PAUSED_PROBERS: LazyLock<std::sync::Mutex<HashSet<(String, String)>>> =
+    LazyLock::new(Default::default);
+
+/// The muted notifiers and channels by the name, the value is the end of the mute.
+static MUTED_NOTIFIERS: LazyLock<std::sync::Mutex<HashMap<String, SystemTime>>> =
+    LazyLock::new(Default::default);
+static MUTED_CHANNELS: LazyLock<std::sync::Mutex<HashMap<String, SystemTime>>> =
+    LazyLock::new(Default::default);
+
+pub fn pause_prober(kind: &str, name: &str) {
+    log::info!("[{} / {}] - the prober is paused", kind, name);
+    PAUSED_PROBERS
+        .lock()
+        .unwrap()
+        .insert((kind.to_string(), name.to_string()));
+}
+
+pub fn resume_prober(kind: &str, name: &str) {
+    log::info!("[{} / {}] - the prober is resumed", kind, name);
+    PAUSED_PROBERS
+        .lock()
+        .unwrap()
+        .remove(&(kind.to_string(), name.to_string()));
+}
+
+pub fn is_prober_paused(kind: &str, name: &str) -> bool {
+    PAUSED_PROBERS
+        .lock()
+        .unwrap()
+        .contains(&(kind.to_string(), name.to_string()))
+}
+
+/// Mutes until the time, or unmutes if it's `None`.
+fn mute(
+    muted: &std::sync::Mutex<HashMap<String, SystemTime>>,
+    name: &str,
+    until: Option<SystemTime>,
+) {
+    let mut muted = muted.lock().unwrap();
+    match until {
+        Some(until) => muted.insert(name.to_string(), until),
+        None => muted.remove(name),
+    };
+}
+
+/// Whether it's muted now, the expired mute is removed.
+fn is_muted(muted: &std::sync::Mutex<HashMap<String, SystemTime>>, name: &str) -> bool {
+    let mut muted = muted.lock().unwrap();
+    match muted.get(name) {
+        Some(until) if *until > SystemTime::now() => true,
+        Some(_) => {
+            muted.remove(name);
+            false
+        }
+        None => false,
+    }
+}
+
+/// The mutes which are not expired, sorted by the name.
+fn mutes(muted: &std::sync::Mutex<HashMap<String, SystemTime>>) -> Vec<(String, SystemTime)> {
+    let now = SystemTime::now();
+    let mut mutes: Vec<_> = muted
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(_, until)| **until > now)
+        .map(|(name, until)| (name.clone(), *until))
+        .collect();
+    mutes.sort();
+    mutes
+}
+
+/// Mutes the notifier until the time, or unmutes it if it's `None`.
+pub fn mute_notifier(name: &str, until: Option<SystemTime>) {
+    match until {
+        Some(until) => log::info!(
+            "[notifier / {}] - muted until {}",
+            name,
+            crate::global::format_time(until)
+        ),
+        None => log::info!("[notifier / {}] - unmuted", name),
+    }
+    mute(&MUTED_NOTIFIERS, name, until);
+}
+
+pub fn is_notifier_muted(name: &str) -> bool {
+    is_muted(&MUTED_NOTIFIERS, name)
+}
+
+pub fn muted_notifiers() -> Vec<(String, SystemTime)> {
+    mutes(&MUTED_NOTIFIERS)
+}
+
+/// Mutes all the notifiers of the channel until the time, or unmutes it if it's `None`.
+pub fn mute_channel(name: &str, until: Option<SystemTime>) {
+    match until {
+        Some(until) => log::info!(
+            "[channel / {}] - muted until {}",
+            name,
+            crate::global::format_time(until)
+        ),
+        None => log::info!("[channel / {}] - unmuted", name),
+    }
+    mute(&MUTED_CHANNELS, name, until);
+}
+
+pub fn is_channel_muted(name: &str) -> bool {
+    is_muted(&MUTED_CHANNELS, name)
+}
+
+pub fn muted_channels() -> Vec<(String, SystemTime)> {
+    mutes(&MUTED_CHANNELS)
+}
+
+/// The maintenance windows of the configuration.
+static MAINTENANCE: LazyLock<std::sync::Mutex<Vec<MaintenanceWindow>>> =
+    LazyLock::new(Default::default);
+
+/// Sets the maintenance windows of all the channels, including the channels created later.
+pub async fn set_maintenance(windows: Vec<MaintenanceWindow>) {
+    for w in &windows {
+        log::info!(
+            "[maintenance / {}] - the maintenance window is scheduled",
+            w.name
+        );
+    }
+    *MAINTENANCE.lock().unwrap() = windows.clone();
+    for ch in CHANNELS.lock().await.values() {
+        ch.set_maintenance(windows.clone());
+    }
+}
+
 pub async fn get_channel(name: &str) -> Option<Arc<Channel>> {
     let channels = CHANNELS.lock().await;
     channels.get(name).cloned()
@@ -34,6 +168,7 @@ pub async fn set_channel(name: &str) {
     let mut channels = CHANNELS.lock().await;
     if !channels.contains_key(name) {
         let ch = Channel::new(name).await;
+        ch.set_maintenance(MAINTENANCE.lock().unwrap().clone());
         channels.insert(name.to_string(), Arc::new(ch));
     }
 }
@@ -255,8 +390,28 @@ mod tests {
 
         let chs = get_all_channels().await;
         assert_eq!(3, chs.len());
-        assert!(chs.get("test").is_some());
-        assert!(chs.get("X").is_some());
-        assert!(chs.get("Y").is_some());
+        assert!(chs.contains_key("test"));
+        assert!(chs.contains_key("X"));
+        assert!(chs.contains_key("Y"));
+    }
+
+    #[test]
+    fn test_pause_mute() {
+        pause_prober("http", "paused-web");
+        assert!(is_prober_paused("http", "paused-web"));
+        assert!(!is_prober_paused("tcp", "paused-web"));
+        resume_prober("http", "paused-web");
+        assert!(!is_prober_paused("http", "paused-web"));
+
+        let hour = SystemTime::now() + std::time::Duration::from_secs(3600);
+        mute_notifier("muted-slack", Some(hour));
+        assert!(is_notifier_muted("muted-slack"));
+        assert!(muted_notifiers().contains(&("muted-slack".to_string(), hour)));
+        mute_notifier("muted-slack", None);
+        assert!(!is_notifier_muted("muted-slack"));
+
+        mute_channel("muted-channel", Some(SystemTime::now()));
+        assert!(!is_channel_muted("muted-channel"));
+        assert!(!muted_channels().iter().any(|(n, _)| n == "muted-channel"));
     }
 }
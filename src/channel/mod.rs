@@ -1,3 +1,6 @@
+#[allow(clippy::module_inception)]
 mod channel;
 pub use channel::*;
+mod maintenance;
+pub use maintenance::*;
 pub mod manager;
@@ -1,4 +1,4 @@
-use std::{fs, sync::Arc, time::Duration};
+use std::{fs, sync::Arc};
 
 use anyhow::Result;
 use clap::Parser;
@@ -7,14 +7,17 @@ mod probe;
 pub use probe::*;
 mod notify;
 pub use notify::*;
+mod sla;
+pub use sla::*;
 use tokio::sync::RwLock;
 
 use crate::{
-    channel::manager,
+    channel::{manager, MaintenanceWindow},
     conf::{self, Conf},
-    get_env_or_default,
+    get_env_or_default, logger,
     notify::Notifier,
-    probe::Prober,
+    probe::{DataStore, Prober, Snapshot},
+    web,
 };
 
 #[derive(Parser, Debug)]
@@ -28,14 +31,39 @@ struct Args {
     #[arg(short = 'f', long, default_value_t = get_env_or_default("PROBE_CONFIG", "config.yaml"))]
     yaml_file: String,
 
+    /// Log level, it overrides the `settings.log.level` of the configuration
+    #[arg(short = 'l', long)]
+    log_level: Option<String>,
+
     /// Show JSON schema
     #[arg(short = 'j', long, default_value_t = false)]
     json_schema: bool,
 }
 
-pub async fn start() -> Result<()> {
-    logforth::stdout().apply();
+/// Waits for the Ctrl-C, or the SIGTERM on unix.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut term) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = term.recv() => {}
+                }
+                return;
+            }
+            Err(e) => log::warn!("Failed to listen to the SIGTERM: {}", e),
+        }
+    }
+    if let Err(e) = tokio::signal::ctrl_c().await {
+        // keep running rather than exiting at once
+        log::error!("Failed to listen to the Ctrl-C: {}", e);
+        std::future::pending::<()>().await;
+    }
+}
 
+pub async fn start() -> Result<()> {
     let args = Args::parse();
     if args.json_schema {
         let schema = conf::json_schema().expect("failed to show JSON schema: ");
@@ -45,28 +73,118 @@ pub async fn start() -> Result<()> {
 
     let f = fs::read(args.yaml_file)?;
     let c: Conf = serde_yaml::from_slice(&f)?;
-    println!("{:?}", c);
-
+    logger::init(&c.settings.log, args.log_level.as_deref())?;
+
+    manager::set_dry_notify(args.dry_notify);
+    let tz = parse_timezone(&c.settings.timezone)?;
+    let windows = c
+        .maintenance
+        .iter()
+        .map(|m| MaintenanceWindow::new(m, tz))
+        .collect::<Result<_>>()?;
+    manager::set_maintenance(windows).await;
     manager::set_channel("test").await;
 
     let mut probers: Vec<Arc<RwLock<dyn Prober>>> = vec![];
     for ele in c.http {
         probers.push(Arc::new(RwLock::new(ele)));
     }
+    for ele in c.client {
+        probers.push(Arc::new(RwLock::new(ele)));
+    }
+    for ele in c.dns {
+        probers.push(Arc::new(RwLock::new(ele)));
+    }
+    for ele in c.grpc {
+        probers.push(Arc::new(RwLock::new(ele)));
+    }
+    for ele in c.websocket {
+        probers.push(Arc::new(RwLock::new(ele)));
+    }
     config_probers(&mut probers, &c.settings).await;
 
     let mut notifiers: Vec<Arc<RwLock<dyn Notifier>>> = vec![];
     for ele in c.notify.log {
         notifiers.push(Arc::new(RwLock::new(ele)));
     }
+    for ele in c.notify.slack {
+        notifiers.push(Arc::new(RwLock::new(ele)));
+    }
+    for ele in c.notify.discord {
+        notifiers.push(Arc::new(RwLock::new(ele)));
+    }
+    for ele in c.notify.telegram {
+        notifiers.push(Arc::new(RwLock::new(ele)));
+    }
+    for ele in c.notify.email {
+        notifiers.push(Arc::new(RwLock::new(ele)));
+    }
+    for ele in c.notify.webhook {
+        notifiers.push(Arc::new(RwLock::new(ele)));
+    }
+    for ele in c.notify.teams {
+        notifiers.push(Arc::new(RwLock::new(ele)));
+    }
+    for ele in c.notify.lark {
+        notifiers.push(Arc::new(RwLock::new(ele)));
+    }
+    for ele in c.notify.dingtalk {
+        notifiers.push(Arc::new(RwLock::new(ele)));
+    }
+    for ele in c.notify.wecom {
+        notifiers.push(Arc::new(RwLock::new(ele)));
+    }
+    for ele in c.notify.pagerduty {
+        notifiers.push(Arc::new(RwLock::new(ele)));
+    }
+    for ele in c.notify.opsgenie {
+        notifiers.push(Arc::new(RwLock::new(ele)));
+    }
+    for ele in c.notify.shell {
+        notifiers.push(Arc::new(RwLock::new(ele)));
+    }
     config_notifiers(&mut notifiers, &c.settings).await;
 
     manager::set_notifiers(notifiers.clone()).await;
     manager::set_probers(probers.clone()).await;
 
-    run_probers(probers);
-
-    tokio::time::sleep(Duration::new(30, 1000)).await;
+    let store = DataStore::new(&c.settings.sla.data_file, c.settings.sla.backups);
+    if let Some(store) = &store {
+        if let Err(e) = store.backup() {
+            log::warn!("Failed to backup the data file {:?}: {}", store.path(), e);
+        }
+        store.restore(&probers).await;
+        tokio::spawn(save_data(store.clone(), probers.clone()));
+    }
+    Snapshot::publish_all(&probers).await;
+
+    run_probers(probers.clone());
+
+    let sla = SlaScheduler::new(
+        &c.settings.sla,
+        &c.settings.timezone,
+        SystemClock,
+        probers.clone(),
+        sla_notifiers(&c.settings.sla.channels).await,
+    )?;
+    tokio::spawn(sla.run());
+
+    let http = c.settings.http.clone();
+    let web_probers = probers.clone();
+    tokio::spawn(async move {
+        if let Err(e) = web::serve(&http, web_probers).await {
+            log::error!("The http server is stopped: {:#}", e);
+        }
+    });
+
+    shutdown_signal().await;
+    log::info!("Shutting down...");
+    manager::all_done().await;
+    if let Some(store) = &store {
+        if let Err(e) = store.save_probers(&probers).await {
+            log::error!("Failed to save the data file {:?}: {}", store.path(), e);
+        }
+    }
 
     Ok(())
 }
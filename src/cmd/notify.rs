@@ -17,7 +17,7 @@ pub async fn config_notifiers(notifiers: &mut Vec<Arc<RwLock<dyn Notifier>>>, gs
             );
             continue;
         }
-        valid_notifiers.push(Arc::clone(&ele));
+        valid_notifiers.push(Arc::clone(ele));
     }
 
     *notifiers = valid_notifiers;
@@ -2,7 +2,17 @@ use std::{sync::Arc, time::Duration};
 
 use tokio::sync::RwLock;
 
-use crate::{conf, manager::get_channel, Prober, Status};
+use crate::{
+    conf,
+    manager::{get_channel, is_prober_paused},
+    DataStore, ProbeResult, Prober, Snapshot, Status,
+};
+
+/// The probers are never probed more frequently than it.
+const MIN_PROBE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The interval of saving the results to the data file.
+const SAVE_INTERVAL: Duration = Duration::from_secs(60);
 
 pub async fn config_probers(probers: &mut Vec<Arc<RwLock<dyn Prober>>>, gs: &conf::Settings) {
     let mut valid_probers = Vec::new();
@@ -27,25 +37,50 @@ pub async fn config_probers(probers: &mut Vec<Arc<RwLock<dyn Prober>>>, gs: &con
     *probers = valid_probers;
 }
 
+/// Probes and sends the result to the channels of the prober.
+pub async fn probe_and_send(prober: &Arc<RwLock<dyn Prober>>) -> ProbeResult {
+    let (res, channels) = {
+        let mut p = prober.write().await;
+        (p.probe().await, p.channels())
+    };
+    Snapshot::publish(prober, &res, channels.clone());
+
+    for ch in channels {
+        if let Some(ch) = get_channel(&ch).await {
+            ch.send(res.clone()).await;
+        }
+    }
+    res
+}
+
 pub fn run_probers(probers: Vec<Arc<RwLock<dyn Prober>>>) {
     for prober in probers {
         let p = Arc::clone(&prober);
         tokio::spawn(async move {
             loop {
-                let (res, channels) = {
-                    let mut p = p.write().await;
-
-                    (p.probe().await, p.channels())
+                let (paused, interval) = {
+                    let p = p.read().await;
+                    (is_prober_paused(p.kind(), p.name()), *p.interval())
                 };
-
-                for ch in channels {
-                    if let Some(ch) = get_channel(&ch).await {
-                        ch.send(res.clone()).await;
-                    }
+                if !paused {
+                    probe_and_send(&p).await;
                 }
 
-                tokio::time::sleep(Duration::new(1, 1000)).await;
+                tokio::time::sleep(interval.max(MIN_PROBE_INTERVAL)).await;
             }
         });
     }
 }
+
+/// Saves the results of the probers to the data file periodically.
+pub async fn save_data(store: DataStore, probers: Vec<Arc<RwLock<dyn Prober>>>) {
+    let mut interval = tokio::time::interval(SAVE_INTERVAL);
+    // the first tick completes immediately
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        if let Err(e) = store.save_probers(&probers).await {
+            log::error!("Failed to save the data file {:?}: {}", store.path(), e);
+        }
+    }
+}
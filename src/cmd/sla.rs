@@ -0,0 +1,327 @@
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use chrono::{
+    DateTime, Datelike, Duration, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeZone,
+    Timelike, Utc, Weekday,
+};
+use chrono_tz::Tz;
+use tokio::sync::RwLock;
+
+use crate::{
+    conf::{SLAReport, Schedule},
+    manager, Notifier, Prober,
+};
+
+/// The clock of the scheduler, it's replaced in the tests.
+#[async_trait]
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+    async fn sleep_until(&self, time: DateTime<Utc>);
+}
+
+/// The wall clock.
+pub struct SystemClock;
+
+#[async_trait]
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    async fn sleep_until(&self, time: DateTime<Utc>) {
+        // re-checks the wall clock in case it's adjusted while sleeping
+        while let Ok(d) = (time - self.now()).to_std() {
+            if d.is_zero() {
+                break;
+            }
+            tokio::time::sleep(d.min(std::time::Duration::from_secs(60))).await;
+        }
+    }
+}
+
+/// Parses the time of the day in `hour:min` or `hour:min:sec`.
+fn parse_time(time: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(time, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(time, "%H:%M"))
+        .or_else(|_| bail!("invalid SLA report time `{}`, must be hour:min[:sec]", time))
+}
+
+/// Parses the IANA time zone, e.g. `Asia/Shanghai`, the empty one is UTC.
+pub(crate) fn parse_timezone(tz: &str) -> Result<Tz> {
+    if tz.is_empty() {
+        return Ok(Tz::UTC);
+    }
+    tz.parse().or_else(|_| bail!("invalid time zone `{}`", tz))
+}
+
+/// The first instant of the local time, the skipped time of the DST gap
+/// is moved forward by an hour.
+pub(crate) fn local_to_utc(tz: &Tz, time: NaiveDateTime) -> DateTime<Utc> {
+    match tz.from_local_datetime(&time) {
+        LocalResult::Single(t) | LocalResult::Ambiguous(t, _) => t.with_timezone(&Utc),
+        LocalResult::None => local_to_utc(tz, time + Duration::hours(1)),
+    }
+}
+
+fn last_day_of_month(date: NaiveDate) -> NaiveDate {
+    let (year, month) = if date.month() == 12 {
+        (date.year() + 1, 1)
+    } else {
+        (date.year(), date.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1).unwrap() - Duration::days(1)
+}
+
+/// Returns the next time of the report after `now`, or `None` if it's not scheduled.
+pub fn next_time(
+    schedule: &Schedule,
+    time: NaiveTime,
+    tz: &Tz,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let local = now.with_timezone(tz).naive_local();
+    let is_day = |date: NaiveDate| match schedule {
+        Schedule::Weekly => date.weekday() == Weekday::Sun,
+        Schedule::Monthly => date == last_day_of_month(date),
+        _ => true,
+    };
+    match schedule {
+        Schedule::None => None,
+        Schedule::Minutely => {
+            let minute = local.with_second(0)?.with_nanosecond(0)?;
+            Some(local_to_utc(tz, minute + Duration::minutes(1)))
+        }
+        Schedule::Hourly => {
+            let hour = local.with_minute(0)?.with_second(0)?.with_nanosecond(0)?;
+            (1..=3)
+                .map(|h| local_to_utc(tz, hour + Duration::hours(h)))
+                .find(|t| *t > now)
+        }
+        Schedule::Daily | Schedule::Weekly | Schedule::Monthly => {
+            // the last day of the month is at most 31 days later
+            (0..=32)
+                .map(|d| local.date() + Duration::days(d))
+                .filter(|date| is_day(*date))
+                .map(|date| local_to_utc(tz, date.and_time(time)))
+                .find(|t| *t > now)
+        }
+    }
+}
+
+/// Sends the SLA report of the probers to the notifiers on schedule.
+pub struct SlaScheduler<C: Clock> {
+    schedule: Schedule,
+    time: NaiveTime,
+    timezone: Tz,
+    clock: C,
+    probers: Vec<Arc<RwLock<dyn Prober>>>,
+    notifiers: Vec<Arc<RwLock<dyn Notifier>>>,
+}
+
+impl<C: Clock> SlaScheduler<C> {
+    pub fn new(
+        report: &SLAReport,
+        timezone: &str,
+        clock: C,
+        probers: Vec<Arc<RwLock<dyn Prober>>>,
+        notifiers: Vec<Arc<RwLock<dyn Notifier>>>,
+    ) -> Result<Self> {
+        Ok(Self {
+            schedule: report.schedule.clone(),
+            time: parse_time(&report.time)?,
+            timezone: parse_timezone(timezone)?,
+            clock,
+            probers,
+            notifiers,
+        })
+    }
+
+    /// Waits for the next scheduled time and sends the report,
+    /// returns the time of the report or `None` if it's not scheduled.
+    pub async fn tick(&self) -> Option<DateTime<Utc>> {
+        let next = next_time(&self.schedule, self.time, &self.timezone, self.clock.now())?;
+        log::info!(
+            "The next {:?} SLA report is at {}",
+            self.schedule,
+            next.with_timezone(&self.timezone)
+        );
+        self.clock.sleep_until(next).await;
+        log::info!(
+            "Sending the {:?} SLA report to {} notifiers",
+            self.schedule,
+            self.notifiers.len()
+        );
+        for n in &self.notifiers {
+            n.read().await.notify_stat(self.probers.clone()).await;
+        }
+        Some(next)
+    }
+
+    pub async fn run(self) {
+        if self.schedule == Schedule::None {
+            log::info!("The SLA report is not scheduled");
+            return;
+        }
+        while self.tick().await.is_some() {}
+    }
+}
+
+/// The notifiers of the SLA report, all the notifiers if the channels are empty.
+pub async fn sla_notifiers(channels: &[String]) -> Vec<Arc<RwLock<dyn Notifier>>> {
+    let channels = if channels.is_empty() {
+        manager::get_all_channels().await.into_keys().collect()
+    } else {
+        channels.to_vec()
+    };
+    manager::get_notifiers(channels)
+        .await
+        .into_values()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use crate::{
+        global::NotifierSetting,
+        new_dummy_prober,
+        notify::{testing::CaptureServer, WebhookConfig},
+    };
+
+    use super::*;
+
+    /// The clock jumps to the time on sleeping.
+    struct ManualClock(Mutex<DateTime<Utc>>);
+
+    #[async_trait]
+    impl Clock for ManualClock {
+        fn now(&self) -> DateTime<Utc> {
+            *self.0.lock().unwrap()
+        }
+
+        async fn sleep_until(&self, time: DateTime<Utc>) {
+            *self.0.lock().unwrap() = time;
+        }
+    }
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    fn next(schedule: Schedule, time: &str, tz: &str, now: &str) -> String {
+        next_time(
+            &schedule,
+            parse_time(time).unwrap(),
+            &parse_timezone(tz).unwrap(),
+            utc(now),
+        )
+        .unwrap()
+        .to_rfc3339()
+    }
+
+    #[test]
+    fn test_next_time() {
+        let now = "2024-02-27T10:30:15Z";
+        assert_eq!(
+            next(Schedule::Minutely, "00:00", "", now),
+            "2024-02-27T10:31:00+00:00"
+        );
+        assert_eq!(
+            next(Schedule::Hourly, "00:00", "", now),
+            "2024-02-27T11:00:00+00:00"
+        );
+        // India is UTC+05:30
+        assert_eq!(
+            next(Schedule::Hourly, "00:00", "Asia/Kolkata", now),
+            "2024-02-27T11:30:00+00:00"
+        );
+        assert_eq!(
+            next(Schedule::Daily, "23:59", "", now),
+            "2024-02-27T23:59:00+00:00"
+        );
+        assert_eq!(
+            next(Schedule::Daily, "10:30:15", "", now),
+            "2024-02-28T10:30:15+00:00"
+        );
+        // it's 18:30 in Shanghai
+        assert_eq!(
+            next(Schedule::Daily, "08:00", "Asia/Shanghai", now),
+            "2024-02-28T00:00:00+00:00"
+        );
+        // 2024-02-27 is Tuesday
+        assert_eq!(
+            next(Schedule::Weekly, "00:00", "", now),
+            "2024-03-03T00:00:00+00:00"
+        );
+        assert_eq!(
+            next(Schedule::Monthly, "00:00", "", now),
+            "2024-02-29T00:00:00+00:00"
+        );
+        assert_eq!(
+            next(Schedule::Monthly, "00:00", "", "2024-12-31T00:00:00Z"),
+            "2025-01-31T00:00:00+00:00"
+        );
+        // 02:30 is skipped by the DST in New York on 2024-03-10
+        assert_eq!(
+            next(
+                Schedule::Daily,
+                "02:30",
+                "America/New_York",
+                "2024-03-10T00:00:00Z"
+            ),
+            "2024-03-10T07:30:00+00:00"
+        );
+        assert!(next_time(&Schedule::None, NaiveTime::MIN, &Tz::UTC, utc(now)).is_none());
+    }
+
+    #[test]
+    fn test_parse() {
+        assert!(parse_time("23:59").is_ok());
+        assert!(parse_time("24:00").is_err());
+        assert!(parse_timezone("Mars/Olympus").is_err());
+        assert_eq!(parse_timezone("").unwrap(), Tz::UTC);
+    }
+
+    #[tokio::test]
+    async fn test_scheduler() {
+        let server = CaptureServer::start(200, "").await;
+        let mut webhook: WebhookConfig =
+            serde_yaml::from_str(&format!("name: sla\nurl: {}", server.url)).unwrap();
+        webhook.config(&NotifierSetting::default()).unwrap();
+        let report: SLAReport = serde_yaml::from_str("schedule: daily\ntime: '08:00'").unwrap();
+        let clock = ManualClock(Mutex::new(utc("2024-02-27T10:30:15Z")));
+        let prober: Arc<RwLock<dyn Prober>> =
+            Arc::new(new_dummy_prober("http", "", "dummy", vec![]));
+        let scheduler = SlaScheduler::new(
+            &report,
+            "Asia/Shanghai",
+            clock,
+            vec![prober],
+            vec![Arc::new(RwLock::new(webhook))],
+        )
+        .unwrap();
+
+        assert_eq!(scheduler.tick().await, Some(utc("2024-02-28T00:00:00Z")));
+        assert_eq!(scheduler.tick().await, Some(utc("2024-02-29T00:00:00Z")));
+        let reqs = server.requests().await;
+        assert_eq!(reqs.len(), 2);
+        assert!(reqs[0].body.contains("Overall SLA Report"));
+
+        let report: SLAReport = serde_yaml::from_str("schedule: none").unwrap();
+        let clock = ManualClock(Mutex::new(utc("2024-02-27T10:30:15Z")));
+        let scheduler = SlaScheduler::new(&report, "", clock, vec![], vec![]).unwrap();
+        assert_eq!(scheduler.tick().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_run_with_system_clock() {
+        // the scheduler keeps waiting for the next report with the wall clock
+        let report: SLAReport = serde_yaml::from_str("schedule: daily").unwrap();
+        let scheduler = SlaScheduler::new(&report, "UTC", SystemClock, vec![], vec![]).unwrap();
+        let run = tokio::time::timeout(std::time::Duration::from_millis(200), scheduler.run());
+        assert!(run.await.is_err());
+    }
+}
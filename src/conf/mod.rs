@@ -14,9 +14,10 @@ pub fn json_schema() -> Result<String> {
     // Ok(serde_json::to_string_pretty(&schema)?)
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum Schedule {
+    #[default]
     None,
     Minutely,
     Hourly,
@@ -25,19 +26,19 @@ pub enum Schedule {
     Monthly,
 }
 
-impl Default for Schedule {
-    fn default() -> Self {
-        Self::None
-    }
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct SLAReport {
-    schedule: Schedule,
-    time: String,
-    data_file: String,
-    backups: i32,
-    channels: Vec<String>,
+#[serde(default)]
+pub struct SLAReport {
+    pub schedule: Schedule,
+    /// The time of the daily, weekly and monthly report in `hour:min[:sec]`
+    pub time: String,
+    /// The data file of the SLA, `-` disables the persistence
+    #[serde(rename = "data")]
+    pub data_file: String,
+    /// The max number of the data file backups, all of them are kept if it's negative
+    pub backups: i32,
+    /// The channels to send the report, all the channels if it's empty
+    pub channels: Vec<String>,
 }
 
 impl Default for SLAReport {
@@ -52,26 +53,89 @@ impl Default for SLAReport {
     }
 }
 
+/// The log file and its rotation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Log {
+    /// The log file, the stdout if it's empty
+    pub file: String,
+    /// The level of the program log: panic, fatal, error, warn, info, debug or trace
+    pub level: String,
+    /// Rotates the file by itself, otherwise the file is reopened after it's
+    /// moved by the others, e.g. logrotate
+    pub self_rotate: bool,
+    /// The max size of the file in MB, it's not rotated by the size if it's zero
+    pub size: u64,
+    /// The max age of the backups in days, they are kept forever if it's zero
+    pub age: u64,
+    /// The max number of the backups, all of them are kept if it's zero
+    pub backups: usize,
+    /// Compresses the backups by gzip
+    pub compress: bool,
+}
+
+impl Default for Log {
+    fn default() -> Self {
+        Self {
+            file: Default::default(),
+            level: "info".to_string(),
+            self_rotate: true,
+            size: 10,
+            age: 7,
+            backups: 5,
+            compress: true,
+        }
+    }
+}
+
 // HTTP Server settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct HTTPServer {
-    ip: String,
-    port: String,
-    refresh: Duration,
-    // #[serde(default)]
-    // access_log: Log,
+#[serde(default)]
+pub struct HTTPServer {
+    pub ip: String,
+    pub port: u16,
+    /// The auto-refresh interval of the dashboard, the minimum probe interval if it's zero
+    #[serde(with = "humantime_serde")]
+    pub refresh: Duration,
+    /// The bearer token of the control API, the control API is disabled if it's empty
+    pub token: String,
+    /// The access log
+    pub log: Log,
 }
 
 impl Default for HTTPServer {
     fn default() -> Self {
         Self {
-            ip: Default::default(),
-            port: "8181".to_string(),
+            ip: "0.0.0.0".to_string(),
+            port: 8181,
             refresh: Default::default(),
+            token: Default::default(),
+            log: Default::default(),
         }
     }
 }
 
+/// The maintenance window, the notifications of the matched probers are suppressed in it.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Maintenance {
+    pub name: String,
+    /// The start of the one-off window in `YYYY-mm-dd HH:MM[:SS]` of the time zone, or RFC 3339
+    pub start: String,
+    /// The end of the one-off window
+    pub end: String,
+    /// The start of the recurring window in the cron format `min hour day month weekday`
+    pub cron: String,
+    /// The length of the recurring window
+    #[serde(with = "humantime_serde")]
+    pub duration: Duration,
+    /// The regexes of the prober names, a prober must match all the non-empty
+    /// scopes of the probers, kinds and channels
+    pub probers: Vec<String>,
+    pub kinds: Vec<String>,
+    pub channels: Vec<String>,
+}
+
 // Global Settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Settings {
@@ -81,20 +145,20 @@ pub struct Settings {
     icon: String,
     #[serde(default)]
     pid: String,
-    // #[serde(default)]
-    // log: Log,
+    #[serde(default)]
+    pub log: Log,
     #[serde(default = "default_time_format")]
     timeformat: String,
     #[serde(default = "default_time_zone")]
-    timezone: String,
+    pub timezone: String,
     #[serde(default)]
     pub probe: ProbeSettings,
     #[serde(default)]
     pub notify: NotifierSetting,
     #[serde(default)]
-    sla: SLAReport,
+    pub sla: SLAReport,
     #[serde(default)]
-    http: HTTPServer,
+    pub http: HTTPServer,
 }
 
 fn default_name() -> String {
@@ -115,6 +179,16 @@ pub struct Conf {
     version: String,
     #[serde(default)]
     pub http: Vec<probe::HttpProber>,
+    #[serde(default)]
+    pub client: Vec<probe::ClientProber>,
+    #[serde(default)]
+    pub dns: Vec<probe::DnsProber>,
+    #[serde(default)]
+    pub grpc: Vec<probe::GrpcProber>,
+    #[serde(default)]
+    pub websocket: Vec<probe::WebSocketProber>,
     pub notify: notify::Config,
     pub settings: Settings,
+    #[serde(default)]
+    pub maintenance: Vec<Maintenance>,
 }
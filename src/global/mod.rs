@@ -1,12 +1,19 @@
-use std::time::{Duration, SystemTime};
+use std::{
+    future::Future,
+    time::{Duration, SystemTime},
+};
 mod probe;
 use anyhow::{bail, Result};
 use chrono::{DateTime, Local, Utc};
+use hmac::{Hmac, KeyInit, Mac};
 pub use probe::*;
 mod notify;
 pub use notify::*;
+mod tls;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+pub use tls::*;
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct Retry {
@@ -35,7 +42,7 @@ pub fn format_time(time: SystemTime) -> String {
 }
 
 pub fn footer_string() -> String {
-    return "EaseProbe v1.0.0 @ localhost".to_string();
+    "EaseProbe v1.0.0 @ localhost".to_string()
 }
 
 /// Normalizes a value based on the provided logic:
@@ -57,13 +64,14 @@ where
     local
 }
 
-pub async fn do_retry<F>(kind: &str, name: &str, tag: &str, r: &Retry, func: F) -> Result<()>
+pub async fn do_retry<F, Fut>(kind: &str, name: &str, tag: &str, r: &Retry, func: F) -> Result<()>
 where
-    F: Fn() -> Result<()>,
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<()>>,
 {
     let mut last_error = anyhow::anyhow!("No error occurred");
     for i in 0..r.times {
-        let res = func();
+        let res = func().await;
         if res.is_ok() {
             return Ok(());
         }
@@ -94,6 +102,13 @@ where
     )
 }
 
+/// Computes the HMAC-SHA256 of the message.
+pub fn hmac_sha256(key: &[u8], msg: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(msg);
+    mac.finalize().into_bytes().to_vec()
+}
+
 pub fn get_env_or_default(key: &str, default: &str) -> String {
     std::env::var(key).unwrap_or_else(|_| default.to_string())
 }
@@ -32,15 +32,14 @@ impl NotifierSetting {
     }
 
     pub fn normalize_retry(&self, retry: &Retry) -> Retry {
-        let mut res = Retry::default();
-        res.interval = normalize(
-            self.retry.interval,
-            retry.interval,
-            Duration::from_secs(0),
-            DEFAULT_RETRY_INTERVAL,
-        );
-
-        res.times = normalize(self.retry.times, retry.times, 0, DEFAULT_RETRY_TIMES);
-        res
+        Retry {
+            interval: normalize(
+                self.retry.interval,
+                retry.interval,
+                Duration::from_secs(0),
+                DEFAULT_RETRY_INTERVAL,
+            ),
+            times: normalize(self.retry.times, retry.times, 0, DEFAULT_RETRY_TIMES),
+        }
     }
 }
@@ -1,4 +1,4 @@
-use std::{str::FromStr, time::Duration};
+use std::{fmt::Display, str::FromStr, time::Duration};
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -75,29 +75,25 @@ impl Default for NotificationStrategySettings {
 }
 
 #[derive(
-    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
 )]
 pub enum IntervalStrategy {
     Unknown,
+    #[default]
     Regular,
     Increment,
     Exponential,
 }
 
-impl Default for IntervalStrategy {
-    fn default() -> Self {
-        Self::Regular
-    }
-}
-
-impl ToString for IntervalStrategy {
-    fn to_string(&self) -> String {
-        match self {
-            IntervalStrategy::Unknown => "unknown".to_string(),
-            IntervalStrategy::Regular => "regular".to_string(),
-            IntervalStrategy::Increment => "increment".to_string(),
-            IntervalStrategy::Exponential => "exponent".to_string(),
-        }
+impl Display for IntervalStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            IntervalStrategy::Unknown => "unknown",
+            IntervalStrategy::Regular => "regular",
+            IntervalStrategy::Increment => "increment",
+            IntervalStrategy::Exponential => "exponent",
+        };
+        write!(f, "{}", s)
     }
 }
 
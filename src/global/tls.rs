@@ -0,0 +1,135 @@
+use std::{path::Path, sync::Arc};
+
+use anyhow::{bail, Context, Result};
+use rustls::{
+    client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+    crypto::{ring, CryptoProvider},
+    pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer, ServerName, UnixTime},
+    ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The TLS/mTLS settings shared by the probers and notifiers.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct TLSConfig {
+    #[serde(default)]
+    #[schemars(description = "The CA certificate file path")]
+    pub ca: String,
+    #[serde(default)]
+    #[schemars(description = "The client certificate file path")]
+    pub cert: String,
+    #[serde(default)]
+    #[schemars(description = "The client private key file path")]
+    pub key: String,
+    #[serde(default)]
+    #[schemars(description = "Skip the server certificate verification")]
+    pub insecure: bool,
+}
+
+impl TLSConfig {
+    /// Returns true if any TLS setting is present.
+    pub fn is_enabled(&self) -> bool {
+        !self.ca.is_empty() || !self.cert.is_empty() || !self.key.is_empty() || self.insecure
+    }
+
+    /// Checks the certificate files exist and `cert`/`key` are set together.
+    pub fn check(&self) -> Result<()> {
+        if self.cert.is_empty() != self.key.is_empty() {
+            bail!("the TLS cert and key must be configured together");
+        }
+        for f in [&self.ca, &self.cert, &self.key] {
+            if !f.is_empty() && !Path::new(f).is_file() {
+                bail!("the TLS file {} does not exist", f);
+            }
+        }
+        Ok(())
+    }
+
+    /// Builds the rustls client config. The system's web PKI roots are used if no `ca` is set.
+    pub fn rustls_config(&self) -> Result<Arc<ClientConfig>> {
+        let provider = Arc::new(ring::default_provider());
+        let builder = ClientConfig::builder_with_provider(Arc::clone(&provider))
+            .with_safe_default_protocol_versions()?;
+
+        let builder = if self.insecure {
+            builder
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(NoVerifier(provider)))
+        } else {
+            let mut roots = RootCertStore::empty();
+            if self.ca.is_empty() {
+                roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+            } else {
+                for cert in CertificateDer::pem_file_iter(&self.ca)
+                    .with_context(|| format!("failed to read CA file {}", self.ca))?
+                {
+                    roots.add(cert?)?;
+                }
+            }
+            builder.with_root_certificates(roots)
+        };
+
+        let config = if self.cert.is_empty() {
+            builder.with_no_client_auth()
+        } else {
+            let certs = CertificateDer::pem_file_iter(&self.cert)
+                .with_context(|| format!("failed to read cert file {}", self.cert))?
+                .collect::<Result<Vec<_>, _>>()?;
+            let key = PrivateKeyDer::from_pem_file(&self.key)
+                .with_context(|| format!("failed to read key file {}", self.key))?;
+            builder.with_client_auth_cert(certs, key)?
+        };
+
+        Ok(Arc::new(config))
+    }
+}
+
+/// Accepts any server certificate, used by the `insecure` option.
+#[derive(Debug)]
+struct NoVerifier(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for NoVerifier {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
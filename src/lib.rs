@@ -4,6 +4,8 @@ mod cmd;
 pub use cmd::*;
 mod global;
 use global::*;
+mod logger;
+mod metric;
 mod notify;
 use notify::*;
 mod probe;
@@ -11,3 +13,4 @@ use probe::*;
 mod report;
 use report::*;
 mod conf;
+mod web;
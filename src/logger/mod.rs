@@ -0,0 +1,117 @@
+//! The program log and the access log of the http server.
+
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use log::LevelFilter;
+use logforth::{
+    append::{Append, Stdout},
+    layout::TextLayout,
+    Diagnostic, Layout,
+};
+
+use crate::conf::Log;
+
+mod rotate;
+pub use rotate::*;
+
+/// Parses the level, `panic` and `fatal` are the errors.
+pub fn parse_level(level: &str) -> Result<LevelFilter> {
+    Ok(match level.to_lowercase().as_str() {
+        "" | "info" => LevelFilter::Info,
+        "panic" | "fatal" | "error" => LevelFilter::Error,
+        "warn" | "warning" => LevelFilter::Warn,
+        "debug" => LevelFilter::Debug,
+        "trace" => LevelFilter::Trace,
+        _ => bail!("invalid log level `{}`", level),
+    })
+}
+
+/// The writer of the log lines, it's the stdout if the file is not set.
+#[derive(Debug)]
+pub enum LogWriter {
+    Stdout,
+    File(RotateFile),
+}
+
+impl LogWriter {
+    pub fn new(conf: &Log) -> Result<Self> {
+        if conf.file.is_empty() {
+            Ok(Self::Stdout)
+        } else {
+            Ok(Self::File(RotateFile::new(conf)?))
+        }
+    }
+
+    pub fn write_line(&self, line: &[u8]) -> Result<()> {
+        use std::io::Write;
+
+        match self {
+            Self::Stdout => {
+                let mut out = std::io::stdout().lock();
+                out.write_all(line)?;
+                out.write_all(b"\n")?;
+                Ok(())
+            }
+            Self::File(f) => f.write_line(line),
+        }
+    }
+}
+
+/// The appender of the program log file.
+#[derive(Debug)]
+struct FileAppend {
+    file: Arc<RotateFile>,
+    layout: TextLayout,
+}
+
+impl Append for FileAppend {
+    fn append(&self, record: &log::Record, diagnostics: &[Diagnostic]) -> anyhow::Result<()> {
+        let line = self.layout.format(record, diagnostics)?;
+        self.file.write_line(&line)
+    }
+}
+
+/// Sets up the program log, the level of the command line overrides the configuration.
+pub fn init(conf: &Log, level: Option<&str>) -> Result<()> {
+    let level = parse_level(level.unwrap_or(&conf.level))?;
+    let builder = logforth::builder().max_level(level);
+    if conf.file.is_empty() {
+        builder
+            .dispatch(|d| d.filter(level).append(Stdout::default()))
+            .try_apply()?;
+        log::info!(
+            "The program log is written to the stdout at the {} level",
+            level
+        );
+    } else {
+        let file = Arc::new(RotateFile::new(conf)?);
+        let append = FileAppend {
+            file: Arc::clone(&file),
+            layout: TextLayout::default().no_color(),
+        };
+        builder
+            .dispatch(|d| d.filter(level).append(append))
+            .try_apply()?;
+        log::info!(
+            "The program log is written to {:?} at the {} level",
+            file.path(),
+            level
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_level() {
+        assert_eq!(parse_level("").unwrap(), LevelFilter::Info);
+        assert_eq!(parse_level("DEBUG").unwrap(), LevelFilter::Debug);
+        assert_eq!(parse_level("fatal").unwrap(), LevelFilter::Error);
+        assert_eq!(parse_level("warning").unwrap(), LevelFilter::Warn);
+        assert!(parse_level("verbose").is_err());
+    }
+}
@@ -0,0 +1,321 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    thread::JoinHandle,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDateTime};
+use flate2::{write::GzEncoder, Compression};
+
+use crate::conf::Log;
+
+const MB: u64 = 1024 * 1024;
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+/// The time in the backup names, e.g. `easeprobe-2024-01-02T03-04-05.000.log`
+const BACKUP_TIME_FORMAT: &str = "%Y-%m-%dT%H-%M-%S%.3f";
+
+#[derive(Debug)]
+struct Opened {
+    file: File,
+    size: u64,
+}
+
+/// Removes and compresses the backups of the log file.
+#[derive(Debug, Clone)]
+struct Cleaner {
+    path: PathBuf,
+    max_age: Duration,
+    backups: usize,
+    compress: bool,
+    /// The cleanups run one by one
+    running: Arc<Mutex<()>>,
+}
+
+/// The log file, it's rotated before it exceeds the max size.
+#[derive(Debug)]
+pub struct RotateFile {
+    path: PathBuf,
+    self_rotate: bool,
+    max_size: u64,
+    opened: Mutex<Opened>,
+    cleaner: Cleaner,
+    /// The cleanups in the background after the rotations
+    cleanups: Mutex<Vec<JoinHandle<()>>>,
+}
+
+fn open(path: &Path) -> Result<Opened> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("failed to open the log file {:?}", path))?;
+    let size = file.metadata()?.len();
+    Ok(Opened { file, size })
+}
+
+/// Compresses the file to `<file>.gz` and removes it.
+fn gzip(path: &Path) -> Result<()> {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".gz");
+    let compress = || -> Result<()> {
+        let mut gz = GzEncoder::new(File::create(&name)?, Compression::default());
+        io::copy(&mut File::open(path)?, &mut gz)?;
+        gz.finish()?;
+        Ok(())
+    };
+    if let Err(e) = compress() {
+        let _ = fs::remove_file(&name);
+        return Err(e.context(format!("failed to compress {:?}", path)));
+    }
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+/// The stem and the extension with the dot, e.g. `easeprobe` and `.log`.
+fn stem_ext(path: &Path) -> (String, String) {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let ext = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    (stem, ext)
+}
+
+impl Cleaner {
+    /// The backups sorted from the newest.
+    fn backups(&self) -> Result<Vec<PathBuf>> {
+        let (stem, ext) = stem_ext(&self.path);
+        let dir = match self.path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let mut backups = vec![];
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let Some(name) = path.file_name().map(|n| n.to_string_lossy().to_string()) else {
+                continue;
+            };
+            let name = name.strip_suffix(".gz").unwrap_or(&name);
+            let time = name
+                .strip_prefix(&format!("{}-", stem))
+                .and_then(|n| n.strip_suffix(&ext));
+            if time.is_some_and(|t| NaiveDateTime::parse_from_str(t, BACKUP_TIME_FORMAT).is_ok()) {
+                backups.push(path);
+            }
+        }
+        backups.sort();
+        backups.reverse();
+        Ok(backups)
+    }
+
+    /// Removes the extra and the expired backups, and compresses the others.
+    fn cleanup(&self) -> Result<()> {
+        let _running = self.running.lock().unwrap();
+        let now = SystemTime::now();
+        for (i, backup) in self.backups()?.into_iter().enumerate() {
+            let expired = !self.max_age.is_zero()
+                && fs::metadata(&backup)?
+                    .modified()?
+                    .checked_add(self.max_age)
+                    .is_some_and(|t| t < now);
+            if (self.backups > 0 && i >= self.backups) || expired {
+                fs::remove_file(&backup)?;
+            } else if self.compress && backup.extension().is_none_or(|e| e != "gz") {
+                gzip(&backup)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl RotateFile {
+    pub fn new(conf: &Log) -> Result<Self> {
+        let path = PathBuf::from(&conf.file);
+        let opened = open(&path)?;
+        Ok(Self {
+            cleaner: Cleaner {
+                path: path.clone(),
+                max_age: DAY * conf.age as u32,
+                backups: conf.backups,
+                compress: conf.compress,
+                running: Default::default(),
+            },
+            path,
+            self_rotate: conf.self_rotate,
+            max_size: conf.size * MB,
+            opened: Mutex::new(opened),
+            cleanups: Default::default(),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Writes the line with the line break, the file is rotated if it's full,
+    /// or reopened if it's moved by the others.
+    pub fn write_line(&self, line: &[u8]) -> Result<()> {
+        let mut opened = self.opened.lock().unwrap();
+        let mut buf = Vec::with_capacity(line.len() + 1);
+        buf.extend_from_slice(line);
+        buf.push(b'\n');
+
+        if self.self_rotate {
+            let full = self.max_size > 0 && opened.size + buf.len() as u64 > self.max_size;
+            if full && opened.size > 0 {
+                self.rotate(&mut opened)?;
+            }
+        } else if self.is_moved(&opened) {
+            *opened = open(&self.path)?;
+        }
+        opened.file.write_all(&buf)?;
+        opened.size += buf.len() as u64;
+        Ok(())
+    }
+
+    /// Whether the path is not the opened file any more.
+    fn is_moved(&self, opened: &Opened) -> bool {
+        let Ok(current) = fs::metadata(&self.path) else {
+            return true;
+        };
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            if let Ok(file) = opened.file.metadata() {
+                return file.ino() != current.ino() || file.dev() != current.dev();
+            }
+        }
+        current.len() < opened.size
+    }
+
+    /// The name of the backup, it's moved forward if the name is taken.
+    fn backup_path(&self, time: SystemTime) -> PathBuf {
+        let (stem, ext) = stem_ext(&self.path);
+        let mut time = DateTime::<Local>::from(time);
+        loop {
+            let name = format!("{}-{}{}", stem, time.format(BACKUP_TIME_FORMAT), ext);
+            let path = self.path.with_file_name(name);
+            let mut gz = path.as_os_str().to_owned();
+            gz.push(".gz");
+            if !path.exists() && !Path::new(&gz).exists() {
+                return path;
+            }
+            time += chrono::Duration::milliseconds(1);
+        }
+    }
+
+    /// Renames the file to the backup and reopens it, the backups are cleaned up in the background.
+    fn rotate(&self, opened: &mut Opened) -> Result<()> {
+        let backup = self.backup_path(SystemTime::now());
+        fs::rename(&self.path, &backup)
+            .with_context(|| format!("failed to rotate the log file {:?}", self.path))?;
+        *opened = open(&self.path)?;
+
+        let cleaner = self.cleaner.clone();
+        let cleanup = std::thread::spawn(move || {
+            // the program log may be written to this file, so it can't be logged
+            if let Err(e) = cleaner.cleanup() {
+                eprintln!(
+                    "Failed to clean up the backups of {:?}: {:#}",
+                    cleaner.path, e
+                );
+            }
+        });
+        let mut cleanups = self.cleanups.lock().unwrap();
+        cleanups.retain(|c| !c.is_finished());
+        cleanups.push(cleanup);
+        Ok(())
+    }
+
+    /// Waits for the cleanups in the background.
+    #[cfg(test)]
+    fn wait_cleanups(&self) {
+        for c in self.cleanups.lock().unwrap().drain(..) {
+            c.join().unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("easeprobe-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn gunzip(path: &Path) -> Vec<u8> {
+        use std::io::Read;
+
+        let mut data = vec![];
+        flate2::read::GzDecoder::new(File::open(path).unwrap())
+            .read_to_end(&mut data)
+            .unwrap();
+        data
+    }
+
+    #[test]
+    fn test_rotate() {
+        let dir = temp_dir("rotate");
+        let conf = Log {
+            file: dir.join("easeprobe.log").to_string_lossy().to_string(),
+            backups: 2,
+            ..Default::default()
+        };
+        let mut f = RotateFile::new(&conf).unwrap();
+        f.max_size = 22;
+        // a file of the other program
+        fs::write(dir.join("easeprobe-other.log"), "").unwrap();
+
+        for i in 0..4 {
+            f.write_line(format!("line {} - 0", i).as_bytes()).unwrap();
+            f.write_line(format!("line {} - 1", i).as_bytes()).unwrap();
+        }
+        f.wait_cleanups();
+        assert_eq!(
+            fs::read_to_string(f.path()).unwrap(),
+            "line 3 - 0\nline 3 - 1\n"
+        );
+        let backups = f.cleaner.backups().unwrap();
+        assert_eq!(backups.len(), 2);
+        assert!(backups.iter().all(|b| b.extension().unwrap() == "gz"));
+        assert_eq!(gunzip(&backups[0]), b"line 2 - 0\nline 2 - 1\n");
+        assert_eq!(gunzip(&backups[1]), b"line 1 - 0\nline 1 - 1\n");
+        assert!(dir.join("easeprobe-other.log").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_reopen() {
+        let dir = temp_dir("reopen");
+        let conf = Log {
+            file: dir.join("access.log").to_string_lossy().to_string(),
+            self_rotate: false,
+            ..Default::default()
+        };
+        let f = RotateFile::new(&conf).unwrap();
+        f.write_line(b"before").unwrap();
+        // rotated by logrotate
+        fs::rename(f.path(), dir.join("access.log.1")).unwrap();
+        f.write_line(b"after").unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.join("access.log.1")).unwrap(),
+            "before\n"
+        );
+        assert_eq!(fs::read_to_string(f.path()).unwrap(), "after\n");
+        assert!(f.cleaner.backups().unwrap().is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
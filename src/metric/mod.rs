@@ -0,0 +1,239 @@
+//! The Prometheus metrics of the probers and the notifiers.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+    sync::{LazyLock, Mutex},
+    time::Duration,
+};
+
+use crate::{ProbeResult, Status};
+
+const NAMESPACE: &str = "easeprobe";
+
+/// The statuses in the order of the exposition.
+const STATUSES: [Status; 5] = [
+    Status::Init,
+    Status::Up,
+    Status::Down,
+    Status::Unknown,
+    Status::Bad,
+];
+
+/// The latest result of a prober.
+#[derive(Debug, Clone)]
+struct ProbeSample {
+    endpoint: String,
+    status: Status,
+    round_trip_time: Duration,
+    total: i64,
+    counts: HashMap<Status, i64>,
+    uptime: Duration,
+    downtime: Duration,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct SendCount {
+    success: u64,
+    failure: u64,
+}
+
+/// The samples by the kind and name of the probers.
+static PROBES: LazyLock<Mutex<BTreeMap<(String, String), ProbeSample>>> =
+    LazyLock::new(|| Mutex::new(BTreeMap::new()));
+
+/// The kind, name of the notifier and the type of the message.
+type SendKey = (String, String, String);
+
+/// The send counts of the notifiers.
+static SENDS: LazyLock<Mutex<BTreeMap<SendKey, SendCount>>> =
+    LazyLock::new(|| Mutex::new(BTreeMap::new()));
+
+/// Records the probe result, the values are the snapshot of the result,
+/// so it's fine to record the same result more than once.
+pub fn observe_probe(r: &ProbeResult) {
+    let sample = ProbeSample {
+        endpoint: r.endpoint.clone(),
+        status: r.status,
+        round_trip_time: r.round_trip_time,
+        total: r.stat.total,
+        counts: r.stat.status.clone(),
+        uptime: r.stat.uptime,
+        downtime: r.stat.downtime,
+    };
+    PROBES
+        .lock()
+        .unwrap()
+        .insert((r.kind.clone(), r.name.clone()), sample);
+}
+
+/// Counts the notification sent by the notifier, the `tag` is the type of the message.
+pub fn observe_send(kind: &str, name: &str, tag: &str, success: bool) {
+    let mut sends = SENDS.lock().unwrap();
+    let count = sends
+        .entry((kind.to_string(), name.to_string(), tag.to_string()))
+        .or_default();
+    if success {
+        count.success += 1;
+    } else {
+        count.failure += 1;
+    }
+}
+
+/// Escapes the label value of the text exposition format.
+fn escape_label(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn labels(pairs: &[(&str, &str)]) -> String {
+    let pairs: Vec<_> = pairs
+        .iter()
+        .map(|(k, v)| format!("{}=\"{}\"", k, escape_label(v)))
+        .collect();
+    format!("{{{}}}", pairs.join(","))
+}
+
+/// Writes the metric family, the samples are the labels and the value.
+fn family(out: &mut String, name: &str, kind: &str, help: &str, samples: &[(String, String)]) {
+    let _ = writeln!(out, "# HELP {}_{} {}", NAMESPACE, name, help);
+    let _ = writeln!(out, "# TYPE {}_{} {}", NAMESPACE, name, kind);
+    for (labels, value) in samples {
+        let _ = writeln!(out, "{}_{}{} {}", NAMESPACE, name, labels, value);
+    }
+}
+
+/// Renders the metrics in the Prometheus text exposition format.
+pub fn render() -> String {
+    let probes = PROBES.lock().unwrap().clone();
+    let sends = SENDS.lock().unwrap().clone();
+
+    let (mut status, mut rtt, mut total, mut counts, mut uptime, mut downtime) =
+        (vec![], vec![], vec![], vec![], vec![], vec![]);
+    for ((kind, name), s) in &probes {
+        let base = [
+            ("kind", kind.as_str()),
+            ("name", name.as_str()),
+            ("endpoint", s.endpoint.as_str()),
+        ];
+        for st in STATUSES {
+            let l = labels(&[&base[..], &[("status", st.to_string())]].concat());
+            status.push((l.clone(), u8::from(s.status == st).to_string()));
+            let n = s.counts.get(&st).copied().unwrap_or_default();
+            counts.push((l, n.to_string()));
+        }
+        let l = labels(&base);
+        rtt.push((l.clone(), s.round_trip_time.as_secs_f64().to_string()));
+        total.push((l.clone(), s.total.to_string()));
+        uptime.push((l.clone(), s.uptime.as_secs_f64().to_string()));
+        downtime.push((l, s.downtime.as_secs_f64().to_string()));
+    }
+
+    let mut sent = vec![];
+    for ((kind, name, tag), c) in &sends {
+        for (result, n) in [("success", c.success), ("failure", c.failure)] {
+            let l = labels(&[
+                ("kind", kind),
+                ("name", name),
+                ("type", tag),
+                ("result", result),
+            ]);
+            sent.push((l, n.to_string()));
+        }
+    }
+
+    let mut out = String::new();
+    let families = [
+        (
+            "probe_status",
+            "gauge",
+            "The current status of the prober, 1 for the status",
+            &status,
+        ),
+        (
+            "probe_round_trip_seconds",
+            "gauge",
+            "The round trip time of the latest probe",
+            &rtt,
+        ),
+        (
+            "probe_total",
+            "counter",
+            "The total number of the probes",
+            &total,
+        ),
+        (
+            "probe_status_total",
+            "counter",
+            "The number of the probes of each status",
+            &counts,
+        ),
+        (
+            "probe_uptime_seconds",
+            "counter",
+            "The total uptime of the prober",
+            &uptime,
+        ),
+        (
+            "probe_downtime_seconds",
+            "counter",
+            "The total downtime of the prober",
+            &downtime,
+        ),
+        (
+            "notification_sent_total",
+            "counter",
+            "The number of the notifications sent",
+            &sent,
+        ),
+    ];
+    for (name, kind, help, samples) in families {
+        family(&mut out, name, kind, help, samples);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let mut r = ProbeResult {
+            name: "metric \"web\"".to_string(),
+            kind: "http".to_string(),
+            endpoint: "https://example.com".to_string(),
+            status: Status::Down,
+            round_trip_time: Duration::from_millis(250),
+            ..Default::default()
+        };
+        r.stat.total = 10;
+        r.stat.status.insert(Status::Up, 9);
+        r.stat.status.insert(Status::Down, 1);
+        r.stat.uptime = Duration::from_secs(540);
+        r.stat.downtime = Duration::from_secs(60);
+        observe_probe(&r);
+        observe_send("slack", "metric-test", "Notification", true);
+        observe_send("slack", "metric-test", "Notification", false);
+        observe_send("slack", "metric-test", "Notification", true);
+
+        let out = render();
+        let l = r#"kind="http",name="metric \"web\"",endpoint="https://example.com""#;
+        for line in [
+            "# TYPE easeprobe_probe_status gauge".to_string(),
+            format!("easeprobe_probe_status{{{},status=\"down\"}} 1", l),
+            format!("easeprobe_probe_status{{{},status=\"up\"}} 0", l),
+            format!("easeprobe_probe_round_trip_seconds{{{}}} 0.25", l),
+            format!("easeprobe_probe_total{{{}}} 10", l),
+            format!("easeprobe_probe_status_total{{{},status=\"up\"}} 9", l),
+            format!("easeprobe_probe_status_total{{{},status=\"bad\"}} 0", l),
+            format!("easeprobe_probe_uptime_seconds{{{}}} 540", l),
+            format!("easeprobe_probe_downtime_seconds{{{}}} 60", l),
+            r#"easeprobe_notification_sent_total{kind="slack",name="metric-test",type="Notification",result="success"} 2"#.to_string(),
+            r#"easeprobe_notification_sent_total{kind="slack",name="metric-test",type="Notification",result="failure"} 1"#.to_string(),
+        ] {
+            assert!(out.lines().any(|l| l == line), "{} in\n{}", line, out);
+        }
+    }
+}
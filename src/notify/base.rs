@@ -1,16 +1,37 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{atomic::AtomicUsize, Arc},
+    time::Duration,
+};
 
 use anyhow::{bail, Result};
 use async_trait::async_trait;
+use futures_util::future::BoxFuture;
 use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
 
 use super::Notifier;
 use crate::{
-    global, report, Format, NotifierSetting, ProbeResult, Prober, Retry, DEFAULT_CHANNEL_NAME,
-    FORMAT_FUNCS,
+    format_funcs, global, report, Format, NotifierSetting, ProbeResult, Prober, Retry, Status,
+    DEFAULT_CHANNEL_NAME,
 };
-pub type SendFunc = Box<dyn Fn(&str, &str) -> Result<()> + Send + Sync>;
+
+/// The notification to send, the body is formatted in the notifier's format.
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub title: String,
+    pub body: String,
+    /// The probe result of the notification, `None` for the SLA report
+    pub result: Option<Arc<ProbeResult>>,
+    /// The number of the parts delivered by the previous attempts, it's shared by the attempts,
+    /// so the notifiers sending the notification in parts resume from the undelivered part
+    pub delivered: Arc<AtomicUsize>,
+}
+
+/// The send function of the notifiers, it's called for every attempt
+/// and each attempt is limited by the notifier's timeout.
+pub type SendFunc = Box<dyn Fn(Notification) -> BoxFuture<'static, Result<()>> + Send + Sync>;
 
 #[derive(Serialize, Deserialize)]
 pub struct DefaultNotifier {
@@ -46,12 +67,11 @@ impl std::fmt::Debug for DefaultNotifier {
 }
 
 impl DefaultNotifier {
-    async fn send_with_retry(&self, title: &str, msg: &str, tag: &str) {
-        let func = || -> Result<()> {
+    pub(crate) async fn send_with_retry(&self, notification: Notification, tag: &str) {
+        let title = &notification.title;
+        let func = || async {
             log::debug!("[{} / {} / {}] - {}", self.kind, self.name, tag, title);
-            if let Some(send_func) = &self.send_func {
-                send_func(title, msg)
-            } else {
+            let Some(send_func) = &self.send_func else {
                 log::error!(
                     "[{} / {} / {}] - {} SendFunc is none",
                     self.kind,
@@ -60,11 +80,109 @@ impl DefaultNotifier {
                     title
                 );
                 bail!("SendFunc is none")
+            };
+            send_func(notification.clone()).await
+        };
+        self.retry_send(&notification.body, tag, func).await;
+    }
+
+    /// Sends with the given function under the retry policy, and logs the result.
+    /// Every attempt is limited by the timeout.
+    pub(crate) async fn retry_send<F, Fut>(&self, msg: &str, tag: &str, func: F)
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<()>>,
+    {
+        let attempt = || async {
+            match tokio::time::timeout(self.timeout, func()).await {
+                Ok(res) => res,
+                Err(_) => bail!("timed out after {:?}", self.timeout),
             }
         };
-        let err = global::do_retry(&self.kind, &self.name, tag, &self.retry, func).await;
-        report::log_send(&self.kind, &self.name, tag, &msg, err);
+        let err = global::do_retry(&self.kind, &self.name, tag, &self.retry, attempt).await;
+        report::log_send(&self.kind, &self.name, tag, msg, err);
+    }
+}
+
+/// The action of the incident management notifiers for a probe result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AlertAction {
+    /// Opens (or updates) the incident of the prober
+    Trigger,
+    /// Resolves the incident of the prober
+    Resolve,
+    /// Nothing to do, e.g. the first successful probe
+    Ignore,
+}
+
+impl AlertAction {
+    pub(crate) fn of(result: &ProbeResult) -> Self {
+        match result.status {
+            Status::Init => AlertAction::Ignore,
+            Status::Up => match result.pre_status {
+                Status::Init | Status::Up => AlertAction::Ignore,
+                _ => AlertAction::Resolve,
+            },
+            _ => AlertAction::Trigger,
+        }
+    }
+}
+
+/// Merges the user's `status: value` map into the defaults,
+/// the statuses are the lowercase names and the values must be one of `allowed`.
+pub(crate) fn status_map(
+    defaults: &[(Status, &str)],
+    custom: &HashMap<String, String>,
+    allowed: &[&str],
+) -> Result<HashMap<Status, String>> {
+    let mut map: HashMap<Status, String> =
+        defaults.iter().map(|(s, v)| (*s, v.to_string())).collect();
+    for (k, v) in custom {
+        let status = Status::from_string(k);
+        if status.to_string() != k.to_lowercase() {
+            bail!("unknown status `{}`", k);
+        }
+        if !allowed.contains(&v.as_str()) {
+            bail!(
+                "invalid value `{}` of the status `{}`, must be one of {:?}",
+                v,
+                k,
+                allowed
+            );
+        }
+        map.insert(status, v.clone());
     }
+    Ok(map)
+}
+
+/// Checks the error code field of the JSON response, a non-zero code is an error.
+pub(crate) fn check_error_code(kind: &str, body: &str, field: &str) -> Result<()> {
+    let resp: serde_json::Value = serde_json::from_str(body)?;
+    match resp[field].as_i64() {
+        Some(0) | None => Ok(()),
+        Some(code) => bail!(
+            "Error response from {} - code [{}] - msg [{}]",
+            kind,
+            code,
+            body
+        ),
+    }
+}
+
+/// Sends the HTTP request and returns the response body, any non-2xx status is an error.
+pub(crate) async fn send_http(kind: &str, request: reqwest::RequestBuilder) -> Result<String> {
+    let response = request.send().await?;
+    let status = response.status();
+    let body = response.text().await?;
+    if !status.is_success() {
+        bail!(
+            "Error response from {} - code [{}] - msg [{}]",
+            kind,
+            status.as_u16(),
+            body
+        );
+    }
+    Ok(body)
 }
 
 #[async_trait]
@@ -115,10 +233,13 @@ impl Notifier for DefaultNotifier {
             self.dry_notify(result);
             return;
         }
-        let title = result.title();
-        let msg = (FORMAT_FUNCS.get(&self.format).unwrap().result_fn)(result);
-
-        self.send_with_retry(&title, &msg, "Notification").await;
+        let notification = Notification {
+            title: result.title(),
+            body: (format_funcs(self.format).result_fn)(result.clone()),
+            result: Some(result),
+            delivered: Default::default(),
+        };
+        self.send_with_retry(notification, "Notification").await;
     }
 
     async fn notify_stat(&self, probers: Vec<Arc<RwLock<dyn Prober>>>) {
@@ -126,10 +247,13 @@ impl Notifier for DefaultNotifier {
             self.dry_notify_stat(probers);
             return;
         }
-        let title = "Overall SLA Report";
-        let msg = (FORMAT_FUNCS.get(&self.format).unwrap().stat_fn)(probers);
-
-        self.send_with_retry(title, &msg, "SLA").await;
+        let notification = Notification {
+            title: "Overall SLA Report".to_string(),
+            body: (format_funcs(self.format).stat_fn)(probers),
+            result: None,
+            delivered: Default::default(),
+        };
+        self.send_with_retry(notification, "SLA").await;
     }
 
     fn dry_notify(&self, res: Arc<ProbeResult>) {
@@ -137,7 +261,7 @@ impl Notifier for DefaultNotifier {
             "[{} / {} / dry_notify] - {}",
             self.kind,
             self.name,
-            (FORMAT_FUNCS.get(&self.format).unwrap().result_fn)(res)
+            (format_funcs(self.format).result_fn)(res)
         );
     }
 
@@ -146,13 +270,50 @@ impl Notifier for DefaultNotifier {
             "[{} / {} / dry_notify_stat] - {}",
             self.kind,
             self.name,
-            (FORMAT_FUNCS.get(&self.format).unwrap().stat_fn)(probers)
+            (format_funcs(self.format).stat_fn)(probers)
         );
     }
 }
 
 #[cfg(test)]
 mod tests {
-    #[test]
-    fn test_base() {}
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_send_timeout() {
+        let mut n: DefaultNotifier = serde_yaml::from_str(
+            "name: slow\ntimeout: { secs: 0, nanos: 10000000 }\nretry:\n  times: 3\n  interval: { secs: 0, nanos: 1000 }",
+        )
+        .unwrap();
+        n.config(&NotifierSetting::default()).unwrap();
+
+        // the first two attempts hang, the third one is sent
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let sent: Arc<std::sync::Mutex<Vec<Notification>>> = Arc::default();
+        let (a, s) = (attempts.clone(), sent.clone());
+        n.send_func = Some(Box::new(move |notification: Notification| {
+            let (a, s) = (a.clone(), s.clone());
+            Box::pin(async move {
+                if a.fetch_add(1, Ordering::SeqCst) < 2 {
+                    tokio::time::sleep(Duration::from_secs(60)).await;
+                }
+                s.lock().unwrap().push(notification);
+                Ok(())
+            })
+        }));
+
+        let result = ProbeResult {
+            name: "Web".to_string(),
+            ..Default::default()
+        };
+        n.notify(Arc::new(result)).await;
+
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].title, "Web Failure");
+        assert_eq!(sent[0].result.as_ref().unwrap().name, "Web");
+    }
 }
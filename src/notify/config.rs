@@ -1,8 +1,36 @@
 use serde::{Deserialize, Serialize};
 
-use super::LogConfig;
+use super::{
+    DingTalkConfig, DiscordConfig, EmailConfig, LarkConfig, LogConfig, OpsgenieConfig,
+    PagerDutyConfig, ShellConfig, SlackConfig, TeamsConfig, TelegramConfig, WeComConfig,
+    WebhookConfig,
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
     pub log: Vec<LogConfig>,
+    #[serde(default)]
+    pub slack: Vec<SlackConfig>,
+    #[serde(default)]
+    pub discord: Vec<DiscordConfig>,
+    #[serde(default)]
+    pub telegram: Vec<TelegramConfig>,
+    #[serde(default)]
+    pub email: Vec<EmailConfig>,
+    #[serde(default)]
+    pub webhook: Vec<WebhookConfig>,
+    #[serde(default)]
+    pub teams: Vec<TeamsConfig>,
+    #[serde(default)]
+    pub lark: Vec<LarkConfig>,
+    #[serde(default)]
+    pub dingtalk: Vec<DingTalkConfig>,
+    #[serde(default)]
+    pub wecom: Vec<WeComConfig>,
+    #[serde(default)]
+    pub pagerduty: Vec<PagerDutyConfig>,
+    #[serde(default)]
+    pub opsgenie: Vec<OpsgenieConfig>,
+    #[serde(default)]
+    pub shell: Vec<ShellConfig>,
 }
@@ -0,0 +1,168 @@
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use reqwest::{Client, Url};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::RwLock;
+
+use crate::{
+    global::{self, NotifierSetting},
+    probe::{ProbeResult, Prober},
+    Format,
+};
+
+use super::{check_error_code, send_http, DefaultNotifier, Notification, Notifier};
+
+/// The DingTalk notifier sends the markdown messages to a group robot webhook.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DingTalkConfig {
+    #[serde(flatten)]
+    default: DefaultNotifier,
+    webhook: String,
+    /// The robot's signing secret, the requests are signed if set
+    #[serde(default)]
+    secret: String,
+}
+
+/// Appends the `timestamp` and `sign` to the webhook URL,
+/// the sign is the base64 HMAC-SHA256 of `{timestamp}\n{secret}`.
+fn sign_url(webhook: &Url, secret: &str, timestamp: u128) -> Url {
+    let sign = global::hmac_sha256(
+        secret.as_bytes(),
+        format!("{}\n{}", timestamp, secret).as_bytes(),
+    );
+    let mut url = webhook.clone();
+    url.query_pairs_mut()
+        .append_pair("timestamp", &timestamp.to_string())
+        .append_pair("sign", &STANDARD.encode(sign));
+    url
+}
+
+#[async_trait]
+impl Notifier for DingTalkConfig {
+    fn kind(&self) -> &str {
+        self.default.kind()
+    }
+
+    fn name(&self) -> &str {
+        self.default.name()
+    }
+
+    fn channels(&self) -> Vec<String> {
+        self.default.channels()
+    }
+
+    fn config(&mut self, g_conf: &NotifierSetting) -> Result<()> {
+        self.default.kind = "dingtalk".to_string();
+        self.default.format = Format::Markdown;
+        self.default.config(g_conf)?;
+
+        let webhook: Url = self
+            .webhook
+            .parse()
+            .with_context(|| format!("invalid webhook {}", self.webhook))?;
+        if webhook.scheme() != "http" && webhook.scheme() != "https" {
+            bail!("[{} / {}] invalid webhook", self.kind(), self.name());
+        }
+
+        let client = Client::builder().timeout(self.default.timeout).build()?;
+        let secret = self.secret.clone();
+        self.default.send_func = Some(Box::new(move |notification: Notification| {
+            let url = if secret.is_empty() {
+                webhook.clone()
+            } else {
+                // the timestamp in milliseconds, it must be within one hour of the server
+                let timestamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_millis();
+                sign_url(&webhook, &secret, timestamp)
+            };
+            let request = client.post(url).json(&json!({
+                "msgtype": "markdown",
+                "markdown": { "title": notification.title, "text": notification.body },
+            }));
+            Box::pin(async move {
+                let body = send_http("DingTalk", request).await?;
+                check_error_code("DingTalk", &body, "errcode")
+            })
+        }));
+
+        Ok(())
+    }
+
+    async fn notify(&self, result: Arc<ProbeResult>) {
+        self.default.notify(result).await
+    }
+
+    async fn notify_stat(&self, probers: Vec<Arc<RwLock<dyn Prober>>>) {
+        self.default.notify_stat(probers).await
+    }
+
+    fn dry_notify(&self, res: Arc<ProbeResult>) {
+        self.default.dry_notify(res)
+    }
+
+    fn dry_notify_stat(&self, probers: Vec<Arc<RwLock<dyn Prober>>>) {
+        self.default.dry_notify_stat(probers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::notify::testing::CaptureServer;
+
+    use super::*;
+
+    #[test]
+    fn test_sign_url() {
+        let webhook: Url = "https://oapi.dingtalk.com/robot/send?access_token=abc"
+            .parse()
+            .unwrap();
+        let url = sign_url(&webhook, "SEC000", 1700000000000);
+
+        let sign = STANDARD.encode(global::hmac_sha256(b"SEC000", b"1700000000000\nSEC000"));
+        let pairs: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+        assert_eq!(
+            pairs,
+            vec![
+                ("access_token".to_string(), "abc".to_string()),
+                ("timestamp".to_string(), "1700000000000".to_string()),
+                ("sign".to_string(), sign),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dingtalk() {
+        let server = CaptureServer::start(200, r#"{"errcode":0,"errmsg":"ok"}"#).await;
+        let mut d: DingTalkConfig = serde_yaml::from_str(&format!(
+            "name: dingtalk\nwebhook: {}/robot/send?access_token=abc\nsecret: SEC000",
+            server.url
+        ))
+        .unwrap();
+        d.config(&NotifierSetting::default()).unwrap();
+
+        let result = ProbeResult {
+            name: "Web".to_string(),
+            ..Default::default()
+        };
+        d.notify(Arc::new(result)).await;
+
+        let reqs = server.requests().await;
+        assert_eq!(reqs.len(), 1);
+        assert!(reqs[0]
+            .path
+            .starts_with("/robot/send?access_token=abc&timestamp="));
+        assert!(reqs[0].path.contains("&sign="));
+        let body: serde_json::Value = serde_json::from_str(&reqs[0].body).unwrap();
+        assert_eq!(body["msgtype"], "markdown");
+        assert_eq!(body["markdown"]["title"], "Web Failure");
+    }
+}
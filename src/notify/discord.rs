@@ -0,0 +1,268 @@
+use std::sync::{atomic::Ordering, Arc};
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use reqwest::{header::CONTENT_TYPE, Client};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::RwLock;
+
+use crate::{
+    global::NotifierSetting,
+    probe::{ProbeResult, Prober},
+    Format,
+};
+
+use super::{send_http, DefaultNotifier, Notification, Notifier};
+
+/// The max number of embeds in a Discord message.
+const EMBEDS_MAX: usize = 10;
+/// The max number of chars of all embeds in a Discord message.
+const EMBEDS_CHARS_MAX: usize = 6000;
+
+/// The Discord notifier posts the embed messages to a webhook.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DiscordConfig {
+    #[serde(flatten)]
+    default: DefaultNotifier,
+    webhook: String,
+    /// Overrides the default username of the webhook
+    #[serde(default)]
+    username: String,
+    /// Overrides the default avatar of the webhook
+    #[serde(default)]
+    avatar: String,
+}
+
+/// The number of chars Discord counts for the embed limit.
+fn embed_chars(embed: &Value) -> usize {
+    let len = |v: &Value| v.as_str().map_or(0, |s| s.chars().count());
+    let fields = embed["fields"].as_array().map_or(0, |fields| {
+        fields
+            .iter()
+            .map(|f| len(&f["name"]) + len(&f["value"]))
+            .sum()
+    });
+    len(&embed["title"]) + len(&embed["description"]) + len(&embed["footer"]["text"]) + fields
+}
+
+/// Splits the embeds of the message into the messages within Discord's limits.
+fn split_message(msg: &str, username: &str, avatar: &str) -> Result<Vec<String>> {
+    let message: Value = serde_json::from_str(msg)?;
+    let Some(embeds) = message["embeds"].as_array() else {
+        bail!("no embeds in the Discord message");
+    };
+
+    let mut batches: Vec<Vec<&Value>> = vec![];
+    let mut chars = 0;
+    for embed in embeds {
+        let n = embed_chars(embed);
+        match batches.last_mut() {
+            Some(b) if b.len() < EMBEDS_MAX && chars + n <= EMBEDS_CHARS_MAX => {
+                b.push(embed);
+                chars += n;
+            }
+            _ => {
+                batches.push(vec![embed]);
+                chars = n;
+            }
+        }
+    }
+
+    Ok(batches
+        .into_iter()
+        .map(|embeds| {
+            let mut m = json!({ "embeds": embeds });
+            if !username.is_empty() {
+                m["username"] = json!(username);
+            }
+            if !avatar.is_empty() {
+                m["avatar_url"] = json!(avatar);
+            }
+            m.to_string()
+        })
+        .collect())
+}
+
+#[async_trait]
+impl Notifier for DiscordConfig {
+    fn kind(&self) -> &str {
+        self.default.kind()
+    }
+
+    fn name(&self) -> &str {
+        self.default.name()
+    }
+
+    fn channels(&self) -> Vec<String> {
+        self.default.channels()
+    }
+
+    fn config(&mut self, g_conf: &NotifierSetting) -> Result<()> {
+        self.default.kind = "discord".to_string();
+        self.default.format = Format::Discord;
+        self.default.config(g_conf)?;
+
+        if self.webhook.is_empty() {
+            bail!("[{} / {}] webhook is required", self.kind(), self.name());
+        }
+
+        let client = Client::builder().timeout(self.default.timeout).build()?;
+        let webhook = self.webhook.clone();
+        let username = self.username.clone();
+        let avatar = self.avatar.clone();
+        self.default.send_func = Some(Box::new(move |notification: Notification| {
+            let client = client.clone();
+            let webhook = webhook.clone();
+            let messages = split_message(&notification.body, &username, &avatar);
+            let delivered = notification.delivered;
+            Box::pin(async move {
+                // the messages delivered by the previous attempts are not sent again
+                for (i, m) in messages?
+                    .into_iter()
+                    .enumerate()
+                    .skip(delivered.load(Ordering::SeqCst))
+                {
+                    let request = client
+                        .post(&webhook)
+                        .header(CONTENT_TYPE, "application/json")
+                        .body(m);
+                    send_http("Discord", request).await?;
+                    delivered.store(i + 1, Ordering::SeqCst);
+                }
+                Ok(())
+            })
+        }));
+
+        Ok(())
+    }
+
+    async fn notify(&self, result: Arc<ProbeResult>) {
+        self.default.notify(result).await
+    }
+
+    async fn notify_stat(&self, probers: Vec<Arc<RwLock<dyn Prober>>>) {
+        self.default.notify_stat(probers).await
+    }
+
+    fn dry_notify(&self, res: Arc<ProbeResult>) {
+        self.default.dry_notify(res)
+    }
+
+    fn dry_notify_stat(&self, probers: Vec<Arc<RwLock<dyn Prober>>>) {
+        self.default.dry_notify_stat(probers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{channel::new_dummy_prober, notify::testing::CaptureServer, Status};
+
+    use super::*;
+
+    fn discord(webhook: &str) -> DiscordConfig {
+        let mut d: DiscordConfig = serde_yaml::from_str(&format!(
+            "name: discord\nwebhook: {}\nusername: EaseProbe",
+            webhook
+        ))
+        .unwrap();
+        d.config(&NotifierSetting::default()).unwrap();
+        d
+    }
+
+    #[tokio::test]
+    async fn test_discord() {
+        let server = CaptureServer::start(204, "").await;
+        let d = discord(&server.url);
+
+        let result = ProbeResult {
+            name: "Web".to_string(),
+            endpoint: "https://example.com".to_string(),
+            status: Status::Up,
+            ..Default::default()
+        };
+        d.notify(Arc::new(result)).await;
+
+        let reqs = server.requests().await;
+        assert_eq!(reqs.len(), 1);
+        let body: Value = serde_json::from_str(&reqs[0].body).unwrap();
+        assert_eq!(body["username"], "EaseProbe");
+        let embed = &body["embeds"][0];
+        assert_eq!(embed["color"], 0x2ECC71);
+        assert_eq!(embed["fields"][0]["value"], "https://example.com");
+        assert_eq!(embed["fields"][1]["value"], "⏱ 0ms");
+        // the empty message is replaced, Discord rejects the empty value
+        assert_eq!(embed["fields"][3]["value"], "-");
+    }
+
+    #[tokio::test]
+    async fn test_discord_sla() {
+        let server = CaptureServer::start(204, "").await;
+        let d = discord(&server.url);
+
+        let mut probers: Vec<Arc<RwLock<dyn Prober>>> = vec![];
+        for i in 0..30 {
+            let p = Arc::new(new_dummy_prober("http", "", "dummy", vec![]));
+            p.write().await.result().name = format!("dummy-{}", i);
+            probers.push(p);
+        }
+        d.notify_stat(probers).await;
+
+        let reqs = server.requests().await;
+        assert_eq!(reqs.len(), 1);
+        let body: Value = serde_json::from_str(&reqs[0].body).unwrap();
+        let embeds = body["embeds"].as_array().unwrap();
+        assert_eq!(embeds.len(), 2);
+        assert_eq!(embeds[0]["title"], "Overall SLA Report (1/2)");
+        assert_eq!(embeds[0]["fields"].as_array().unwrap().len(), 25);
+        assert_eq!(embeds[1]["fields"].as_array().unwrap().len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_discord_retry() {
+        // the second message fails once
+        let server = CaptureServer::start_with(&[(204, ""), (500, "error"), (204, "")]).await;
+        let mut d: DiscordConfig = serde_yaml::from_str(&format!(
+            "name: discord\nwebhook: {}\nretry:\n  times: 3\n  interval: {{ secs: 0, nanos: 1000 }}",
+            server.url
+        ))
+        .unwrap();
+        d.config(&NotifierSetting::default()).unwrap();
+
+        let embeds: Vec<_> = (0..12).map(|i| json!({ "title": i.to_string() })).collect();
+        let notification = Notification {
+            title: "SLA".to_string(),
+            body: json!({ "embeds": embeds }).to_string(),
+            result: None,
+            delivered: Default::default(),
+        };
+        d.default.send_with_retry(notification, "SLA").await;
+
+        let reqs = server.requests().await;
+        assert_eq!(reqs.len(), 3);
+        let first = |i: usize| {
+            let body: Value = serde_json::from_str(&reqs[i].body).unwrap();
+            body["embeds"][0]["title"].as_str().unwrap().to_string()
+        };
+        assert_eq!([first(0), first(1), first(2)], ["0", "10", "10"]);
+    }
+
+    #[test]
+    fn test_split_message() {
+        let field = json!({ "name": "n", "value": "v".repeat(1000) });
+        let embed = json!({ "title": "t", "fields": [field, field] });
+        let embeds: Vec<_> = (0..12).map(|_| embed.clone()).collect();
+        let msg = json!({ "embeds": embeds }).to_string();
+
+        // 2002 chars per embed, 2 embeds fit in 6000 chars
+        let messages = split_message(&msg, "", "").unwrap();
+        assert_eq!(messages.len(), 6);
+        assert!(!messages[0].contains("username"));
+
+        let embeds: Vec<_> = (0..12).map(|_| json!({ "title": "t" })).collect();
+        let msg = json!({ "embeds": embeds }).to_string();
+        assert_eq!(split_message(&msg, "", "").unwrap().len(), 2);
+
+        assert!(split_message("{}", "", "").is_err());
+    }
+}
@@ -0,0 +1,342 @@
+use std::{
+    fs,
+    sync::{Arc, LazyLock},
+};
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use lettre::{
+    message::{Mailbox, MultiPart},
+    transport::smtp::{
+        authentication::Credentials,
+        client::{Certificate, Identity, Tls, TlsParameters},
+    },
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::{
+    global::NotifierSetting,
+    probe::{ProbeResult, Prober},
+    report::escape_html,
+    Format, TLSConfig,
+};
+
+use super::{DefaultNotifier, Notification, Notifier};
+
+/// The style of the HTML email, mostly for the SLA report table.
+const HTML_STYLE: &str =
+    "body { font-family: -apple-system, Helvetica, Arial, sans-serif; white-space: pre-line; } \
+table { border-collapse: collapse; margin: 8px 0; white-space: normal; } \
+th, td { border: 1px solid #ddd; padding: 6px 12px; text-align: left; } \
+th { background-color: #f2f2f2; }";
+
+/// The email notifier sends the multipart text and HTML emails via SMTP.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EmailConfig {
+    #[serde(flatten)]
+    default: DefaultNotifier,
+    /// The SMTP server `host:port`, implicit TLS on 465, STARTTLS on 587
+    server: String,
+    #[serde(default)]
+    username: String,
+    #[serde(default)]
+    password: String,
+    /// The recipients separated by `;`
+    to: String,
+    /// The sender, default is the `username`
+    #[serde(default)]
+    from: String,
+    #[serde(flatten)]
+    tls: TLSConfig,
+}
+
+static HTML_TAG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<(/?)([a-zA-Z]+)[^>]*>").unwrap());
+
+/// Converts the HTML of the notification into the plain text alternative.
+fn html_to_text(html: &str) -> String {
+    let text = HTML_TAG.replace_all(html, |c: &regex::Captures| {
+        match (c[2].to_lowercase().as_str(), !c[1].is_empty()) {
+            ("br", _) => "\n",
+            ("td" | "th", true) => "\t",
+            _ => "",
+        }
+    });
+    text.replace("\t\n", "\n")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&")
+}
+
+fn html_document(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n{}\n</body>\n</html>\n",
+        escape_html(title),
+        HTML_STYLE,
+        body
+    )
+}
+
+impl EmailConfig {
+    fn tls_parameters(&self, host: &str) -> Result<TlsParameters> {
+        let mut builder = TlsParameters::builder(host.to_string())
+            .dangerous_accept_invalid_certs(self.tls.insecure);
+        if !self.tls.ca.is_empty() {
+            let ca = fs::read(&self.tls.ca)
+                .with_context(|| format!("failed to read CA file {}", self.tls.ca))?;
+            builder = builder.add_root_certificate(Certificate::from_pem(&ca)?);
+        }
+        if !self.tls.cert.is_empty() {
+            let cert = fs::read(&self.tls.cert)
+                .with_context(|| format!("failed to read cert file {}", self.tls.cert))?;
+            let key = fs::read(&self.tls.key)
+                .with_context(|| format!("failed to read key file {}", self.tls.key))?;
+            builder = builder.identify_with(Identity::from_pem(&cert, &key)?);
+        }
+        Ok(builder.build_rustls()?)
+    }
+
+    fn transport(&self) -> Result<AsyncSmtpTransport<Tokio1Executor>> {
+        let Some((host, port)) = self.server.rsplit_once(':') else {
+            bail!("server must be `host:port` - {}", self.server);
+        };
+        let port: u16 = port
+            .parse()
+            .with_context(|| format!("invalid port of server {}", self.server))?;
+
+        self.tls.check()?;
+        let params = self.tls_parameters(host)?;
+        let tls = match port {
+            465 => Tls::Wrapper(params),
+            587 => Tls::Required(params),
+            _ => Tls::Opportunistic(params),
+        };
+
+        let mut builder = AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host)
+            .port(port)
+            .tls(tls)
+            .timeout(Some(self.default.timeout));
+        if !self.username.is_empty() {
+            builder = builder.credentials(Credentials::new(
+                self.username.clone(),
+                self.password.clone(),
+            ));
+        }
+        Ok(builder.build())
+    }
+}
+
+#[async_trait]
+impl Notifier for EmailConfig {
+    fn kind(&self) -> &str {
+        self.default.kind()
+    }
+
+    fn name(&self) -> &str {
+        self.default.name()
+    }
+
+    fn channels(&self) -> Vec<String> {
+        self.default.channels()
+    }
+
+    fn config(&mut self, g_conf: &NotifierSetting) -> Result<()> {
+        self.default.kind = "email".to_string();
+        self.default.format = Format::HTML;
+        self.default.config(g_conf)?;
+
+        let from = if self.from.is_empty() {
+            &self.username
+        } else {
+            &self.from
+        };
+        let from: Mailbox = from
+            .parse()
+            .with_context(|| format!("invalid sender `{}`", from))?;
+        let to = self
+            .to
+            .split(';')
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(|t| {
+                t.parse::<Mailbox>()
+                    .with_context(|| format!("invalid recipient `{}`", t))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if to.is_empty() {
+            bail!("[{} / {}] no recipient", self.kind(), self.name());
+        }
+
+        let transport = self.transport().map_err(|e| {
+            log::error!("[{} / {}] {}", self.kind(), self.name(), e);
+            e
+        })?;
+        self.default.send_func = Some(Box::new(move |notification: Notification| {
+            let transport = transport.clone();
+            let message = to
+                .iter()
+                .fold(Message::builder().from(from.clone()), |b, to| {
+                    b.to(to.clone())
+                })
+                .subject(notification.title.as_str())
+                .multipart(MultiPart::alternative_plain_html(
+                    html_to_text(&notification.body),
+                    html_document(&notification.title, &notification.body),
+                ));
+            Box::pin(async move {
+                transport.send(message?).await?;
+                Ok(())
+            })
+        }));
+
+        Ok(())
+    }
+
+    async fn notify(&self, result: Arc<ProbeResult>) {
+        self.default.notify(result).await
+    }
+
+    async fn notify_stat(&self, probers: Vec<Arc<RwLock<dyn Prober>>>) {
+        self.default.notify_stat(probers).await
+    }
+
+    fn dry_notify(&self, res: Arc<ProbeResult>) {
+        self.default.dry_notify(res)
+    }
+
+    fn dry_notify_stat(&self, probers: Vec<Arc<RwLock<dyn Prober>>>) {
+        self.default.dry_notify_stat(probers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+        net::TcpListener,
+        sync::Mutex,
+    };
+
+    use crate::{channel::new_dummy_prober, Status};
+
+    use super::*;
+
+    /// A local SMTP sink, which records the commands and the mail data.
+    async fn smtp_sink() -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let received = Arc::new(Mutex::new(vec![]));
+
+        let recv = Arc::clone(&received);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let (r, mut w) = stream.into_split();
+                let mut lines = BufReader::new(r).lines();
+                w.write_all(b"220 sink ESMTP\r\n").await.unwrap();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    let cmd = line.to_uppercase();
+                    let reply: &[u8] = if cmd.starts_with("EHLO") {
+                        b"250-sink\r\n250 AUTH PLAIN LOGIN\r\n"
+                    } else if cmd.starts_with("AUTH") {
+                        b"235 OK\r\n"
+                    } else if cmd.starts_with("DATA") {
+                        w.write_all(b"354 go ahead\r\n").await.unwrap();
+                        let mut data = String::new();
+                        while let Ok(Some(l)) = lines.next_line().await {
+                            if l == "." {
+                                break;
+                            }
+                            data.push_str(&l);
+                            data.push('\n');
+                        }
+                        recv.lock().await.push(data);
+                        b"250 queued\r\n"
+                    } else if cmd.starts_with("QUIT") {
+                        w.write_all(b"221 bye\r\n").await.unwrap();
+                        break;
+                    } else {
+                        recv.lock().await.push(line);
+                        b"250 OK\r\n"
+                    };
+                    w.write_all(reply).await.unwrap();
+                }
+            }
+        });
+
+        (addr, received)
+    }
+
+    fn email(server: &str) -> EmailConfig {
+        let mut e: EmailConfig = serde_yaml::from_str(&format!(
+            "name: mail\nserver: {}\nusername: probe@example.com\npassword: secret\nto: 'ops@example.com; dev@example.com'",
+            server
+        ))
+        .unwrap();
+        e.config(&NotifierSetting::default()).unwrap();
+        e
+    }
+
+    #[tokio::test]
+    async fn test_email() {
+        let (server, received) = smtp_sink().await;
+        let e = email(&server);
+
+        let result = ProbeResult {
+            name: "Web & API".to_string(),
+            endpoint: "https://example.com".to_string(),
+            status: Status::Down,
+            ..Default::default()
+        };
+        e.notify(Arc::new(result)).await;
+
+        let received = received.lock().await;
+        assert!(received.contains(&"MAIL FROM:<probe@example.com>".to_string()));
+        assert!(received.contains(&"RCPT TO:<ops@example.com>".to_string()));
+        assert!(received.contains(&"RCPT TO:<dev@example.com>".to_string()));
+
+        let data = received.last().unwrap();
+        assert!(data.contains("Subject: Web & API Failure"));
+        assert!(data.contains("multipart/alternative"));
+        assert!(data.contains("Content-Type: text/plain"));
+        assert!(data.contains("Content-Type: text/html"));
+        assert!(data.contains("<b>Web &amp; API Failure</b>"));
+    }
+
+    #[tokio::test]
+    async fn test_email_sla() {
+        let (server, received) = smtp_sink().await;
+        let e = email(&server);
+
+        let prober: Arc<RwLock<dyn Prober>> =
+            Arc::new(new_dummy_prober("http", "", "dummy", vec![]));
+        prober.write().await.result().name = "dummy".to_string();
+        e.notify_stat(vec![prober]).await;
+
+        let received = received.lock().await;
+        let data = received.last().unwrap();
+        assert!(data.contains("Subject: Overall SLA Report"));
+        assert!(data.contains("<table>"));
+        assert!(data.contains("<td>dummy</td>"));
+    }
+
+    #[test]
+    fn test_html_to_text() {
+        let html = "<b>Report</b>\n<table>\n<tr><th>Name</th><th>SLA</th></tr>\n<tr><td>a &amp; b</td><td>100%</td></tr>\n</table>";
+        assert_eq!(html_to_text(html), "Report\n\nName\tSLA\na & b\t100%\n");
+    }
+
+    #[test]
+    fn test_email_config() {
+        let conf = |yaml: &str| {
+            let mut e: EmailConfig = serde_yaml::from_str(yaml).unwrap();
+            e.config(&NotifierSetting::default())
+        };
+        assert!(conf("name: m\nserver: smtp.example.com\nusername: a@b.c\nto: x@y.z").is_err());
+        assert!(conf("name: m\nserver: smtp.example.com:465\nusername: a@b.c\nto: ';'").is_err());
+        assert!(conf("name: m\nserver: smtp.example.com:465\nfrom: bad\nto: x@y.z").is_err());
+        assert!(conf("name: m\nserver: smtp.example.com:587\nusername: a@b.c\nto: x@y.z").is_ok());
+    }
+}
@@ -0,0 +1,137 @@
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use reqwest::{header::CONTENT_TYPE, Client};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::{
+    global::NotifierSetting,
+    probe::{ProbeResult, Prober},
+    Format,
+};
+
+use super::{check_error_code, send_http, DefaultNotifier, Notification, Notifier};
+
+/// The Lark (Feishu) notifier sends the interactive cards to a custom bot webhook.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LarkConfig {
+    #[serde(flatten)]
+    default: DefaultNotifier,
+    webhook: String,
+}
+
+#[async_trait]
+impl Notifier for LarkConfig {
+    fn kind(&self) -> &str {
+        self.default.kind()
+    }
+
+    fn name(&self) -> &str {
+        self.default.name()
+    }
+
+    fn channels(&self) -> Vec<String> {
+        self.default.channels()
+    }
+
+    fn config(&mut self, g_conf: &NotifierSetting) -> Result<()> {
+        self.default.kind = "lark".to_string();
+        self.default.format = Format::Lark;
+        self.default.config(g_conf)?;
+
+        if self.webhook.is_empty() {
+            bail!("[{} / {}] webhook is required", self.kind(), self.name());
+        }
+
+        let client = Client::builder().timeout(self.default.timeout).build()?;
+        let webhook = self.webhook.clone();
+        self.default.send_func = Some(Box::new(move |notification: Notification| {
+            let request = client
+                .post(&webhook)
+                .header(CONTENT_TYPE, "application/json")
+                .body(notification.body);
+            Box::pin(async move {
+                let body = send_http("Lark", request).await?;
+                // the legacy bots reply the `StatusCode`
+                check_error_code("Lark", &body, "code")?;
+                check_error_code("Lark", &body, "StatusCode")
+            })
+        }));
+
+        Ok(())
+    }
+
+    async fn notify(&self, result: Arc<ProbeResult>) {
+        self.default.notify(result).await
+    }
+
+    async fn notify_stat(&self, probers: Vec<Arc<RwLock<dyn Prober>>>) {
+        self.default.notify_stat(probers).await
+    }
+
+    fn dry_notify(&self, res: Arc<ProbeResult>) {
+        self.default.dry_notify(res)
+    }
+
+    fn dry_notify_stat(&self, probers: Vec<Arc<RwLock<dyn Prober>>>) {
+        self.default.dry_notify_stat(probers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{channel::new_dummy_prober, notify::testing::CaptureServer, Status};
+
+    use super::*;
+
+    async fn lark(reply: &str) -> (CaptureServer, LarkConfig) {
+        let server = CaptureServer::start(200, reply).await;
+        let mut l: LarkConfig = serde_yaml::from_str(&format!(
+            "name: lark\nwebhook: {}\nretry:\n  times: 2\n  interval: {{ secs: 0, nanos: 1000 }}",
+            server.url
+        ))
+        .unwrap();
+        l.config(&NotifierSetting::default()).unwrap();
+        (server, l)
+    }
+
+    #[tokio::test]
+    async fn test_lark() {
+        let (server, l) = lark(r#"{"code":0,"msg":"success"}"#).await;
+        let result = ProbeResult {
+            name: "Web".to_string(),
+            status: Status::Down,
+            ..Default::default()
+        };
+        l.notify(Arc::new(result)).await;
+
+        let prober: Arc<RwLock<dyn Prober>> =
+            Arc::new(new_dummy_prober("http", "", "dummy", vec![]));
+        l.notify_stat(vec![prober]).await;
+
+        let reqs = server.requests().await;
+        assert_eq!(reqs.len(), 2);
+        let body: serde_json::Value = serde_json::from_str(&reqs[0].body).unwrap();
+        assert_eq!(body["msg_type"], "interactive");
+        assert_eq!(body["card"]["header"]["template"], "red");
+        assert_eq!(body["card"]["header"]["title"]["content"], "❌ Web Failure");
+        let body: serde_json::Value = serde_json::from_str(&reqs[1].body).unwrap();
+        assert_eq!(
+            body["card"]["header"]["title"]["content"],
+            "Overall SLA Report"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_lark_error() {
+        let (server, l) = lark(r#"{"code":19021,"msg":"sign match fail"}"#).await;
+        l.notify(Arc::new(ProbeResult::default())).await;
+        assert_eq!(server.requests().await.len(), 2);
+
+        let (server, l) = lark(r#"{"StatusCode":1}"#).await;
+        l.notify(Arc::new(ProbeResult::default())).await;
+        assert_eq!(server.requests().await.len(), 2);
+    }
+}
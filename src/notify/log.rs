@@ -1,30 +1,41 @@
-use std::{
-    fs::OpenOptions,
-    io::Write,
-    sync::{Arc, Mutex},
-};
+use std::{fs::OpenOptions, sync::Arc};
 
-use anyhow::{bail, Result};
+use anyhow::Result;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use tokio::sync::RwLock;
+use tokio::{
+    io::AsyncWriteExt,
+    sync::{Mutex, RwLock},
+};
 
 use crate::{
+    format_funcs,
     global::NotifierSetting,
     probe::{ProbeResult, Prober},
 };
 
-use super::{DefaultNotifier, Notifier};
+use super::{
+    syslog::{Severity, Syslog},
+    DefaultNotifier, Notification, Notifier,
+};
+
+/// The keyword of the `file` to send to the syslog.
+const SYSLOG: &str = "syslog";
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LogConfig {
     #[serde(flatten)]
     default: DefaultNotifier,
+    /// The log file, or `syslog` to send to the syslog
     file: String,
+    /// The remote syslog server, the local syslog is used if empty
     #[serde(default)]
     host: String,
+    /// The network of the remote syslog server, `udp` or `tcp`
     #[serde(default)]
     network: String,
+    #[serde(skip)]
+    syslog: Option<Syslog>,
 }
 
 #[async_trait]
@@ -48,35 +59,67 @@ impl Notifier for LogConfig {
 
         self.default.config(g_conf)?;
 
-        if !self.file.is_empty() {
-            let log_target = Arc::new(Mutex::new(
-                OpenOptions::new()
-                    .append(true)
-                    .create(true)
-                    .open(&self.file)
-                    .map_err(|e| anyhow::anyhow!("Failed to open log file {}: {}", self.file, e))?,
-            ));
-            let send_fn = move |title: &str, msg: &str| {
-                let mut file = log_target.lock().unwrap();
-                writeln!(file, "Notification: {}", title)?;
-                for line in msg.lines() {
-                    writeln!(file, "{}", line)?;
-                }
-                file.flush()?;
-                Ok(())
-            };
-            self.default.send_func = Some(Box::new(send_fn));
+        if self.file == SYSLOG {
+            self.syslog = Some(
+                Syslog::new(&self.host, &self.network, self.default.timeout).inspect_err(|e| {
+                    log::error!("[{} / {}] {}", self.kind(), self.name(), e);
+                })?,
+            );
+        } else if !self.file.is_empty() {
+            let file = OpenOptions::new()
+                .append(true)
+                .create(true)
+                .open(&self.file)
+                .map_err(|e| anyhow::anyhow!("Failed to open log file {}: {}", self.file, e))?;
+            let log_target = Arc::new(Mutex::new(tokio::fs::File::from_std(file)));
+            self.default.send_func = Some(Box::new(move |notification: Notification| {
+                let log_target = log_target.clone();
+                Box::pin(async move {
+                    let mut record = format!("Notification: {}\n", notification.title);
+                    for line in notification.body.lines() {
+                        record.push_str(line);
+                        record.push('\n');
+                    }
+                    let mut file = log_target.lock().await;
+                    file.write_all(record.as_bytes()).await?;
+                    file.flush().await?;
+                    Ok(())
+                })
+            }));
         }
 
         Ok(())
     }
 
     async fn notify(&self, result: Arc<ProbeResult>) {
-        self.default.notify(result).await
+        let Some(syslog) = &self.syslog else {
+            return self.default.notify(result).await;
+        };
+        if self.default.dry {
+            self.dry_notify(result);
+            return;
+        }
+        let severity = Severity::of(result.status, result.pre_status);
+        let msg = (format_funcs(self.default.format).result_fn)(result);
+        self.default
+            .retry_send(&msg, "Notification", || {
+                syslog.send(severity, "Notification", &msg)
+            })
+            .await;
     }
 
     async fn notify_stat(&self, probers: Vec<Arc<RwLock<dyn Prober>>>) {
-        self.default.notify_stat(probers).await
+        let Some(syslog) = &self.syslog else {
+            return self.default.notify_stat(probers).await;
+        };
+        if self.default.dry {
+            self.dry_notify_stat(probers);
+            return;
+        }
+        let msg = (format_funcs(self.default.format).stat_fn)(probers);
+        self.default
+            .retry_send(&msg, "SLA", || syslog.send(Severity::Info, "SLA", &msg))
+            .await;
     }
 
     fn dry_notify(&self, res: Arc<ProbeResult>) {
@@ -87,3 +130,85 @@ impl Notifier for LogConfig {
         self.default.dry_notify_stat(probers)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tokio::{
+        io::AsyncReadExt,
+        net::{TcpListener, UdpSocket},
+    };
+
+    use crate::Status;
+
+    use super::*;
+
+    fn syslog(host: &str, network: &str) -> LogConfig {
+        let mut l: LogConfig = serde_yaml::from_str(&format!(
+            "name: syslog\nfile: syslog\nhost: {}\nnetwork: {}",
+            host, network
+        ))
+        .unwrap();
+        l.config(&NotifierSetting::default()).unwrap();
+        l
+    }
+
+    fn result() -> Arc<ProbeResult> {
+        Arc::new(ProbeResult {
+            name: "Web".to_string(),
+            status: Status::Down,
+            pre_status: Status::Up,
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_syslog_udp() {
+        let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let l = syslog(&server.local_addr().unwrap().to_string(), "udp");
+        l.notify(result()).await;
+
+        let mut buf = [0u8; 4096];
+        let n = server.recv(&mut buf).await.unwrap();
+        let record = String::from_utf8_lossy(&buf[..n]);
+        assert!(record.starts_with("<27>1 "));
+        assert!(record.contains(" easeprobe "));
+        assert!(record.contains(" Notification - [Web Failure] ❌"));
+    }
+
+    #[tokio::test]
+    async fn test_syslog_tcp() {
+        let server = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let l = syslog(&server.local_addr().unwrap().to_string(), "tcp");
+        l.notify(result()).await;
+        l.notify_stat(vec![]).await;
+
+        let (mut stream, _) = server.accept().await.unwrap();
+        let mut records = Vec::new();
+        let mut buf = Vec::new();
+        while records.len() < 2 {
+            let mut chunk = [0u8; 4096];
+            let n = stream.read(&mut chunk).await.unwrap();
+            buf.extend_from_slice(&chunk[..n]);
+            // the octet counting frames: `LEN SP MSG`
+            while let Some(sp) = buf.iter().position(|b| *b == b' ') {
+                let len: usize = std::str::from_utf8(&buf[..sp]).unwrap().parse().unwrap();
+                if buf.len() < sp + 1 + len {
+                    break;
+                }
+                records.push(String::from_utf8(buf[sp + 1..sp + 1 + len].to_vec()).unwrap());
+                buf.drain(..sp + 1 + len);
+            }
+        }
+        assert!(records[0].starts_with("<27>1 "));
+        assert!(records[1].starts_with("<30>1 "));
+        assert!(records[1].contains(" SLA - [Overall SLA Report]"));
+    }
+
+    #[test]
+    fn test_syslog_config() {
+        let mut l: LogConfig =
+            serde_yaml::from_str("name: syslog\nfile: syslog\nhost: 127.0.0.1\nnetwork: sctp")
+                .unwrap();
+        assert!(l.config(&NotifierSetting::default()).is_err());
+    }
+}
@@ -7,8 +7,35 @@ mod base;
 pub use base::*;
 mod config;
 pub use config::*;
+mod dingtalk;
+pub use dingtalk::*;
+mod discord;
+pub use discord::*;
+mod email;
+pub use email::*;
+mod lark;
+pub use lark::*;
 mod log;
 pub use log::*;
+mod opsgenie;
+pub use opsgenie::*;
+mod pagerduty;
+pub use pagerduty::*;
+mod shell;
+pub use shell::*;
+mod slack;
+pub use slack::*;
+mod syslog;
+mod teams;
+pub use teams::*;
+mod telegram;
+pub use telegram::*;
+mod webhook;
+pub use webhook::*;
+mod wecom;
+pub use wecom::*;
+#[cfg(test)]
+pub(crate) mod testing;
 
 use crate::{NotifierSetting, ProbeResult, Prober};
 
@@ -0,0 +1,262 @@
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use reqwest::{header::AUTHORIZATION, Client, Url};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::RwLock;
+
+use crate::{
+    global::NotifierSetting,
+    probe::{ProbeResult, Prober},
+    report::truncate,
+    Status,
+};
+
+use super::{send_http, status_map, AlertAction, DefaultNotifier, Notification, Notifier};
+
+const PRIORITIES: [&str; 5] = ["P1", "P2", "P3", "P4", "P5"];
+
+const DEFAULT_PRIORITY: [(Status, &str); 5] = [
+    (Status::Init, "P5"),
+    (Status::Up, "P5"),
+    (Status::Down, "P1"),
+    (Status::Unknown, "P3"),
+    (Status::Bad, "P2"),
+];
+
+/// The limit of the alert message
+const MAX_MESSAGE: usize = 130;
+
+fn default_api() -> String {
+    "https://api.opsgenie.com".to_string()
+}
+
+/// The Opsgenie notifier creates the alert of the failed prober
+/// and closes it on recovery, the alerts are identified by the alias.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OpsgenieConfig {
+    #[serde(flatten)]
+    default: DefaultNotifier,
+    /// The API key of the integration
+    key: String,
+    /// The API endpoint, e.g. `https://api.eu.opsgenie.com` for the EU instance
+    #[serde(default = "default_api")]
+    api: String,
+    /// The priority of the statuses, e.g. `down: P1`
+    #[serde(default)]
+    priority: HashMap<String, String>,
+    #[serde(skip)]
+    priorities: HashMap<Status, String>,
+}
+
+/// The alert of a prober is identified by its kind and name.
+fn alias(result: &ProbeResult) -> String {
+    format!("{}/{}", result.kind, result.name)
+}
+
+/// Returns the URL and the payload of the alert request, it closes the alert on recovery.
+fn alert(
+    api: &Url,
+    priorities: &HashMap<Status, String>,
+    result: &ProbeResult,
+) -> (Url, serde_json::Value) {
+    let mut url = api.clone();
+    // the api is checked to be a base URL in config
+    let mut path = url.path_segments_mut().unwrap();
+    path.pop_if_empty();
+    if AlertAction::of(result) == AlertAction::Resolve {
+        path.extend(["v2", "alerts", &alias(result), "close"]);
+        drop(path);
+        url.query_pairs_mut().append_pair("identifierType", "alias");
+        let payload = json!({
+            "source": "EaseProbe",
+            "note": result.title(),
+        });
+        return (url, payload);
+    }
+    path.extend(["v2", "alerts"]);
+    drop(path);
+    let payload = json!({
+        "message": truncate(&result.title(), MAX_MESSAGE),
+        "alias": alias(result),
+        "description": result.message,
+        "priority": priorities[&result.status],
+        "entity": result.endpoint,
+        "source": "EaseProbe",
+        "tags": [result.kind],
+        "details": {
+            "status": result.status.to_string(),
+            "pre_status": result.pre_status.to_string(),
+            "rtt": result.round_trip_time.as_millis().to_string(),
+        },
+    });
+    (url, payload)
+}
+
+#[async_trait]
+impl Notifier for OpsgenieConfig {
+    fn kind(&self) -> &str {
+        self.default.kind()
+    }
+
+    fn name(&self) -> &str {
+        self.default.name()
+    }
+
+    fn channels(&self) -> Vec<String> {
+        self.default.channels()
+    }
+
+    fn config(&mut self, g_conf: &NotifierSetting) -> Result<()> {
+        self.default.kind = "opsgenie".to_string();
+        self.default.config(g_conf)?;
+
+        if self.key.is_empty() {
+            bail!("[{} / {}] key is required", self.kind(), self.name());
+        }
+        let api: Url = self.api.parse().inspect_err(|e| {
+            log::error!("[{} / {}] invalid api - {}", self.kind(), self.name(), e);
+        })?;
+        if api.cannot_be_a_base() {
+            bail!(
+                "[{} / {}] invalid api {}",
+                self.kind(),
+                self.name(),
+                self.api
+            );
+        }
+        self.priorities =
+            status_map(&DEFAULT_PRIORITY, &self.priority, &PRIORITIES).map_err(|e| {
+                log::error!("[{} / {}] {}", self.kind(), self.name(), e);
+                e
+            })?;
+
+        let client = Client::builder().timeout(self.default.timeout).build()?;
+        let authorization = format!("GenieKey {}", self.key);
+        let priorities = self.priorities.clone();
+        self.default.send_func = Some(Box::new(move |notification: Notification| {
+            let request = notification.result.map(|r| {
+                let (url, payload) = alert(&api, &priorities, &r);
+                client
+                    .post(url)
+                    .header(AUTHORIZATION, &authorization)
+                    .json(&payload)
+            });
+            Box::pin(async move {
+                let Some(request) = request else {
+                    bail!("the Opsgenie alert requires the probe result");
+                };
+                send_http("Opsgenie", request).await.map(|_| ())
+            })
+        }));
+
+        Ok(())
+    }
+
+    async fn notify(&self, result: Arc<ProbeResult>) {
+        if self.default.dry {
+            self.dry_notify(result);
+            return;
+        }
+        let action = AlertAction::of(&result);
+        if action == AlertAction::Ignore {
+            log::debug!(
+                "[{} / {}] - {} - no alert to update",
+                self.kind(),
+                self.name(),
+                result.title()
+            );
+            return;
+        }
+        self.default.notify(result).await
+    }
+
+    /// The SLA report is not an alert, it's not sent to Opsgenie.
+    async fn notify_stat(&self, _probers: Vec<Arc<RwLock<dyn Prober>>>) {
+        log::debug!(
+            "[{} / {}] - the SLA report is not sent",
+            self.kind(),
+            self.name()
+        );
+    }
+
+    fn dry_notify(&self, res: Arc<ProbeResult>) {
+        if let Ok(api) = self.api.parse() {
+            let (url, payload) = alert(&api, &self.priorities, &res);
+            log::info!(
+                "[{} / {} / dry_notify] - {} {}",
+                self.kind(),
+                self.name(),
+                url,
+                payload
+            );
+        }
+    }
+
+    fn dry_notify_stat(&self, _probers: Vec<Arc<RwLock<dyn Prober>>>) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::notify::testing::CaptureServer;
+
+    use super::*;
+
+    fn result(status: Status, pre_status: Status) -> Arc<ProbeResult> {
+        Arc::new(ProbeResult {
+            name: "Web API".to_string(),
+            kind: "http".to_string(),
+            endpoint: "https://example.com".to_string(),
+            status,
+            pre_status,
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_opsgenie() {
+        let server = CaptureServer::start(202, r#"{"result":"Request will be processed"}"#).await;
+        let mut o: OpsgenieConfig = serde_yaml::from_str(&format!(
+            "name: og\nkey: K3Y\napi: {}\npriority:\n  unknown: P4",
+            server.url
+        ))
+        .unwrap();
+        o.config(&NotifierSetting::default()).unwrap();
+
+        o.notify(result(Status::Up, Status::Init)).await;
+        o.notify(result(Status::Down, Status::Up)).await;
+        o.notify(result(Status::Unknown, Status::Up)).await;
+        o.notify(result(Status::Up, Status::Down)).await;
+
+        let reqs = server.requests().await;
+        assert_eq!(reqs.len(), 3);
+        assert_eq!(reqs[0].path, "/v2/alerts");
+        assert_eq!(reqs[0].headers["authorization"], "GenieKey K3Y");
+        let alert: serde_json::Value = serde_json::from_str(&reqs[0].body).unwrap();
+        assert_eq!(alert["alias"], "http/Web API");
+        assert_eq!(alert["priority"], "P1");
+        assert_eq!(alert["message"], "Web API Failure");
+        let alert: serde_json::Value = serde_json::from_str(&reqs[1].body).unwrap();
+        assert_eq!(alert["priority"], "P4");
+
+        assert_eq!(
+            reqs[2].path,
+            "/v2/alerts/http%2FWeb%20API/close?identifierType=alias"
+        );
+        let close: serde_json::Value = serde_json::from_str(&reqs[2].body).unwrap();
+        assert_eq!(close["source"], "EaseProbe");
+    }
+
+    #[test]
+    fn test_opsgenie_config() {
+        let mut o: OpsgenieConfig =
+            serde_yaml::from_str("name: og\nkey: x\npriority:\n  down: P0").unwrap();
+        assert!(o.config(&NotifierSetting::default()).is_err());
+
+        let mut o: OpsgenieConfig =
+            serde_yaml::from_str("name: og\nkey: x\napi: 'no url'").unwrap();
+        assert!(o.config(&NotifierSetting::default()).is_err());
+    }
+}
@@ -0,0 +1,248 @@
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::RwLock;
+
+use crate::{
+    global::NotifierSetting,
+    probe::{ProbeResult, Prober},
+    Status,
+};
+
+use super::{send_http, status_map, AlertAction, DefaultNotifier, Notification, Notifier};
+
+const SEVERITIES: [&str; 4] = ["critical", "error", "warning", "info"];
+
+const DEFAULT_SEVERITY: [(Status, &str); 5] = [
+    (Status::Init, "info"),
+    (Status::Up, "info"),
+    (Status::Down, "critical"),
+    (Status::Unknown, "warning"),
+    (Status::Bad, "error"),
+];
+
+fn default_api() -> String {
+    "https://events.pagerduty.com/v2/enqueue".to_string()
+}
+
+/// The PagerDuty notifier triggers the incident of the failed prober
+/// and resolves it on recovery with the Events API v2.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PagerDutyConfig {
+    #[serde(flatten)]
+    default: DefaultNotifier,
+    /// The integration key of the service
+    routing_key: String,
+    #[serde(default = "default_api")]
+    api: String,
+    /// The severity of the statuses, e.g. `down: critical`
+    #[serde(default)]
+    severity: HashMap<String, String>,
+    #[serde(skip)]
+    severities: HashMap<Status, String>,
+}
+
+/// The incident of a prober is deduplicated by its kind and name.
+fn dedup_key(result: &ProbeResult) -> String {
+    format!("{}/{}", result.kind, result.name)
+}
+
+/// The event of the probe result, it resolves the incident on recovery.
+fn event(
+    routing_key: &str,
+    severities: &HashMap<Status, String>,
+    result: &ProbeResult,
+) -> serde_json::Value {
+    if AlertAction::of(result) == AlertAction::Resolve {
+        return json!({
+            "routing_key": routing_key,
+            "event_action": "resolve",
+            "dedup_key": dedup_key(result),
+        });
+    }
+    let source = if result.endpoint.is_empty() {
+        &result.name
+    } else {
+        &result.endpoint
+    };
+    json!({
+        "routing_key": routing_key,
+        "event_action": "trigger",
+        "dedup_key": dedup_key(result),
+        "payload": {
+            "summary": result.title(),
+            "source": source,
+            "severity": severities[&result.status],
+            "timestamp": chrono::DateTime::<chrono::Utc>::from(result.start_time).to_rfc3339(),
+            "component": result.name,
+            "group": result.kind,
+            "custom_details": {
+                "status": result.status.to_string(),
+                "pre_status": result.pre_status.to_string(),
+                "message": result.message,
+                "rtt": result.round_trip_time.as_millis() as u64,
+            },
+        },
+        "client": "EaseProbe",
+    })
+}
+
+#[async_trait]
+impl Notifier for PagerDutyConfig {
+    fn kind(&self) -> &str {
+        self.default.kind()
+    }
+
+    fn name(&self) -> &str {
+        self.default.name()
+    }
+
+    fn channels(&self) -> Vec<String> {
+        self.default.channels()
+    }
+
+    fn config(&mut self, g_conf: &NotifierSetting) -> Result<()> {
+        self.default.kind = "pagerduty".to_string();
+        self.default.config(g_conf)?;
+
+        if self.routing_key.is_empty() {
+            bail!(
+                "[{} / {}] routing_key is required",
+                self.kind(),
+                self.name()
+            );
+        }
+        self.severities =
+            status_map(&DEFAULT_SEVERITY, &self.severity, &SEVERITIES).map_err(|e| {
+                log::error!("[{} / {}] {}", self.kind(), self.name(), e);
+                e
+            })?;
+        let client = Client::builder().timeout(self.default.timeout).build()?;
+        let api = self.api.clone();
+        let routing_key = self.routing_key.clone();
+        let severities = self.severities.clone();
+        self.default.send_func = Some(Box::new(move |notification: Notification| {
+            let request = notification.result.map(|r| {
+                client
+                    .post(&api)
+                    .json(&event(&routing_key, &severities, &r))
+            });
+            Box::pin(async move {
+                let Some(request) = request else {
+                    bail!("the PagerDuty event requires the probe result");
+                };
+                send_http("PagerDuty", request).await.map(|_| ())
+            })
+        }));
+
+        Ok(())
+    }
+
+    async fn notify(&self, result: Arc<ProbeResult>) {
+        if self.default.dry {
+            self.dry_notify(result);
+            return;
+        }
+        let action = AlertAction::of(&result);
+        if action == AlertAction::Ignore {
+            log::debug!(
+                "[{} / {}] - {} - no incident to update",
+                self.kind(),
+                self.name(),
+                result.title()
+            );
+            return;
+        }
+        self.default.notify(result).await
+    }
+
+    /// The SLA report is not an incident, it's not sent to PagerDuty.
+    async fn notify_stat(&self, _probers: Vec<Arc<RwLock<dyn Prober>>>) {
+        log::debug!(
+            "[{} / {}] - the SLA report is not sent",
+            self.kind(),
+            self.name()
+        );
+    }
+
+    fn dry_notify(&self, res: Arc<ProbeResult>) {
+        log::info!(
+            "[{} / {} / dry_notify] - {}",
+            self.kind(),
+            self.name(),
+            event(&self.routing_key, &self.severities, &res)
+        );
+    }
+
+    fn dry_notify_stat(&self, _probers: Vec<Arc<RwLock<dyn Prober>>>) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::notify::testing::CaptureServer;
+
+    use super::*;
+
+    fn result(status: Status, pre_status: Status) -> Arc<ProbeResult> {
+        Arc::new(ProbeResult {
+            name: "Web".to_string(),
+            kind: "http".to_string(),
+            endpoint: "https://example.com".to_string(),
+            status,
+            pre_status,
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_pagerduty() {
+        let server = CaptureServer::start(202, r#"{"status":"success"}"#).await;
+        let mut p: PagerDutyConfig = serde_yaml::from_str(&format!(
+            "name: pd\nrouting_key: R0UT1NG\napi: {}/v2/enqueue\nseverity:\n  down: error",
+            server.url
+        ))
+        .unwrap();
+        p.config(&NotifierSetting::default()).unwrap();
+
+        p.notify(result(Status::Up, Status::Init)).await;
+        p.notify(result(Status::Down, Status::Up)).await;
+        p.notify(result(Status::Unknown, Status::Down)).await;
+        p.notify(result(Status::Up, Status::Unknown)).await;
+        p.notify_stat(vec![]).await;
+
+        let reqs = server.requests().await;
+        assert_eq!(reqs.len(), 3);
+        assert_eq!(reqs[0].path, "/v2/enqueue");
+        let events: Vec<serde_json::Value> = reqs
+            .iter()
+            .map(|r| serde_json::from_str(&r.body).unwrap())
+            .collect();
+        assert_eq!(events[0]["routing_key"], "R0UT1NG");
+        assert_eq!(events[0]["event_action"], "trigger");
+        assert_eq!(events[0]["dedup_key"], "http/Web");
+        assert_eq!(events[0]["payload"]["severity"], "error");
+        assert_eq!(events[0]["payload"]["summary"], "Web Failure");
+        assert_eq!(events[0]["payload"]["source"], "https://example.com");
+        assert_eq!(events[1]["payload"]["severity"], "warning");
+        assert_eq!(events[2]["event_action"], "resolve");
+        assert_eq!(events[2]["dedup_key"], "http/Web");
+    }
+
+    #[test]
+    fn test_pagerduty_config() {
+        let mut p: PagerDutyConfig =
+            serde_yaml::from_str("name: pd\nrouting_key: x\nseverity:\n  down: fatal").unwrap();
+        assert!(p.config(&NotifierSetting::default()).is_err());
+
+        let mut p: PagerDutyConfig =
+            serde_yaml::from_str("name: pd\nrouting_key: x\nseverity:\n  broken: info").unwrap();
+        assert!(p.config(&NotifierSetting::default()).is_err());
+
+        let mut p: PagerDutyConfig = serde_yaml::from_str("name: pd\nrouting_key: ''").unwrap();
+        assert!(p.config(&NotifierSetting::default()).is_err());
+    }
+}
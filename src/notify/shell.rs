@@ -0,0 +1,251 @@
+use std::{process::Stdio, sync::Arc};
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::{io::AsyncWriteExt, process::Command, sync::RwLock};
+
+use crate::{
+    format_funcs,
+    global::NotifierSetting,
+    probe::{ProbeResult, Prober},
+    Format,
+};
+
+use super::{DefaultNotifier, Notifier};
+
+/// The shell notifier runs the command with the probe result in the environment
+/// variables `EASEPROBE_*`, and the formatted text in the stdin.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShellConfig {
+    #[serde(flatten)]
+    default: DefaultNotifier,
+    cmd: String,
+    #[serde(default)]
+    args: Vec<String>,
+    /// The extra environment variables in `KEY=value`
+    #[serde(default)]
+    env: Vec<String>,
+    #[serde(skip)]
+    envs: Vec<(String, String)>,
+}
+
+fn result_env(r: &ProbeResult) -> Vec<(&'static str, String)> {
+    let time = chrono::DateTime::<chrono::Utc>::from(r.start_time);
+    vec![
+        ("EASEPROBE_TYPE", "Status".to_string()),
+        ("EASEPROBE_TITLE", r.title()),
+        ("EASEPROBE_NAME", r.name.clone()),
+        ("EASEPROBE_KIND", r.kind.clone()),
+        ("EASEPROBE_ENDPOINT", r.endpoint.clone()),
+        ("EASEPROBE_STATUS", r.status.to_string().to_owned()),
+        ("EASEPROBE_PRE_STATUS", r.pre_status.to_string().to_owned()),
+        ("EASEPROBE_MESSAGE", r.message.clone()),
+        ("EASEPROBE_RTT", r.round_trip_time.as_millis().to_string()),
+        ("EASEPROBE_TIME", time.to_rfc3339()),
+        ("EASEPROBE_TIMESTAMP", time.timestamp().to_string()),
+    ]
+}
+
+fn sla_env(title: &str) -> Vec<(&'static str, String)> {
+    let time = chrono::Utc::now();
+    vec![
+        ("EASEPROBE_TYPE", "SLA".to_string()),
+        ("EASEPROBE_TITLE", title.to_string()),
+        ("EASEPROBE_TIME", time.to_rfc3339()),
+        ("EASEPROBE_TIMESTAMP", time.timestamp().to_string()),
+    ]
+}
+
+impl ShellConfig {
+    /// Runs the command, a non-zero exit code is a failure.
+    async fn run(&self, env: &[(&str, String)], input: &str) -> Result<()> {
+        let mut child = Command::new(&self.cmd)
+            .args(&self.args)
+            .envs(self.envs.iter().map(|(k, v)| (k, v)))
+            .envs(env.iter().map(|(k, v)| (k, v)))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("failed to run {}", self.cmd))?;
+
+        let mut stdin = child.stdin.take().context("failed to open stdin")?;
+        let input = input.to_string();
+        // the command may not read the stdin, so it's written in the background
+        tokio::spawn(async move {
+            let _ = stdin.write_all(input.as_bytes()).await;
+        });
+
+        // the child is killed on drop if the attempt is timed out
+        let output = child.wait_with_output().await?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if !output.status.success() {
+            bail!(
+                "{} - {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        log::debug!(
+            "[{} / {}] - output: {}",
+            self.kind(),
+            self.name(),
+            stdout.trim()
+        );
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Notifier for ShellConfig {
+    fn kind(&self) -> &str {
+        self.default.kind()
+    }
+
+    fn name(&self) -> &str {
+        self.default.name()
+    }
+
+    fn channels(&self) -> Vec<String> {
+        self.default.channels()
+    }
+
+    fn config(&mut self, g_conf: &NotifierSetting) -> Result<()> {
+        self.default.kind = "shell".to_string();
+        self.default.format = Format::Shell;
+        self.default.config(g_conf)?;
+
+        if self.cmd.is_empty() {
+            bail!("[{} / {}] cmd is required", self.kind(), self.name());
+        }
+        self.envs.clear();
+        for e in &self.env {
+            let Some((k, v)) = e.split_once('=') else {
+                bail!(
+                    "[{} / {}] invalid env `{}`, must be KEY=value",
+                    self.kind(),
+                    self.name(),
+                    e
+                );
+            };
+            self.envs.push((k.to_string(), v.to_string()));
+        }
+
+        Ok(())
+    }
+
+    async fn notify(&self, result: Arc<ProbeResult>) {
+        if self.default.dry {
+            self.dry_notify(result);
+            return;
+        }
+        let env = result_env(&result);
+        let text = (format_funcs(Format::Shell).result_fn)(result.clone());
+        self.default
+            .retry_send(&text, "Notification", || self.run(&env, &text))
+            .await;
+    }
+
+    async fn notify_stat(&self, probers: Vec<Arc<RwLock<dyn Prober>>>) {
+        if self.default.dry {
+            self.dry_notify_stat(probers);
+            return;
+        }
+        let env = sla_env("Overall SLA Report");
+        let text = (format_funcs(Format::Shell).stat_fn)(probers);
+        self.default
+            .retry_send(&text, "SLA", || self.run(&env, &text))
+            .await;
+    }
+
+    fn dry_notify(&self, res: Arc<ProbeResult>) {
+        self.default.dry_notify(res)
+    }
+
+    fn dry_notify_stat(&self, probers: Vec<Arc<RwLock<dyn Prober>>>) {
+        self.default.dry_notify_stat(probers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{channel::new_dummy_prober, Status};
+
+    use super::*;
+
+    fn out_file(name: &str) -> std::path::PathBuf {
+        let out = std::env::temp_dir().join(format!("easeprobe-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_file(&out);
+        out
+    }
+
+    fn shell(script: &str, out: &std::path::Path) -> ShellConfig {
+        let mut s: ShellConfig = serde_yaml::from_str(&format!(
+            "name: shell\ncmd: /bin/sh\nargs: ['-c', '{}']\nenv: ['OUT={}']\nretry:\n  times: 2\n  interval: {{ secs: 0, nanos: 1000 }}",
+            script,
+            out.display()
+        ))
+        .unwrap();
+        s.config(&NotifierSetting::default()).unwrap();
+        s
+    }
+
+    #[tokio::test]
+    async fn test_shell() {
+        let out = out_file("test_shell");
+        let s = shell(
+            r#"echo "$EASEPROBE_NAME|$EASEPROBE_KIND|$EASEPROBE_STATUS|$EASEPROBE_RTT" >> $OUT; cat >> $OUT"#,
+            &out,
+        );
+
+        let result = ProbeResult {
+            name: "Web".to_string(),
+            kind: "http".to_string(),
+            status: Status::Down,
+            round_trip_time: std::time::Duration::from_millis(42),
+            message: "HTTP Status Code is 500".to_string(),
+            ..Default::default()
+        };
+        s.notify(Arc::new(result)).await;
+        let content = std::fs::read_to_string(&out).unwrap();
+        assert!(content.starts_with("Web|http|down|42\n[Web Failure] ❌"));
+        assert!(content.contains("HTTP Status Code is 500"));
+
+        std::fs::remove_file(&out).unwrap();
+        let prober: Arc<RwLock<dyn Prober>> =
+            Arc::new(new_dummy_prober("http", "", "dummy", vec![]));
+        prober.write().await.result().name = "a,b".to_string();
+        s.notify_stat(vec![prober]).await;
+        let content = std::fs::read_to_string(&out).unwrap();
+        let lines: Vec<_> = content.lines().collect();
+        assert_eq!(lines[0], "|||");
+        assert!(lines[1].starts_with("name,kind,endpoint,status,sla,total,"));
+        assert!(lines[2].starts_with("\"a,b\",,,unknown,100.00,0,0,0,0,0,0,0,"));
+        assert_eq!(lines[3], "TOTAL,,,0 up / 0 down / 1 other,100.00,,,,,,,,");
+        std::fs::remove_file(&out).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_shell_failure() {
+        let out = out_file("test_shell_failure");
+        let s = shell("echo run >> $OUT; exit 1", &out);
+        s.notify(Arc::new(ProbeResult::default())).await;
+        assert_eq!(std::fs::read_to_string(&out).unwrap(), "run\nrun\n");
+        std::fs::remove_file(&out).unwrap();
+
+        let err = s.run(&[], "").await.unwrap_err();
+        assert!(err.to_string().contains("exit status: 1"));
+    }
+
+    #[test]
+    fn test_shell_config() {
+        let mut s: ShellConfig =
+            serde_yaml::from_str("name: shell\ncmd: /bin/sh\nenv: ['NO_VALUE']").unwrap();
+        assert!(s.config(&NotifierSetting::default()).is_err());
+
+        let mut s: ShellConfig = serde_yaml::from_str("name: shell\ncmd: ''").unwrap();
+        assert!(s.config(&NotifierSetting::default()).is_err());
+    }
+}
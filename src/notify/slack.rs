@@ -0,0 +1,238 @@
+use std::sync::{atomic::Ordering, Arc};
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use reqwest::{header::CONTENT_TYPE, Client};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::RwLock;
+
+use crate::{
+    global::NotifierSetting,
+    probe::{ProbeResult, Prober},
+    Format,
+};
+
+use super::{send_http, DefaultNotifier, Notification, Notifier};
+
+/// The max number of blocks in a Slack message.
+const BLOCKS_MAX: usize = 50;
+
+/// Splits the blocks of the message into the messages within Slack's limit,
+/// every message keeps the notification text.
+fn split_message(msg: &str) -> Result<Vec<String>> {
+    let message: Value = serde_json::from_str(msg)?;
+    let Some(blocks) = message["blocks"].as_array() else {
+        bail!("no blocks in the Slack message");
+    };
+    Ok(blocks
+        .chunks(BLOCKS_MAX)
+        .map(|blocks| json!({ "text": message["text"], "blocks": blocks }).to_string())
+        .collect())
+}
+
+/// The Slack notifier posts the Block Kit messages to an incoming webhook.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SlackConfig {
+    #[serde(flatten)]
+    default: DefaultNotifier,
+    webhook: String,
+}
+
+#[async_trait]
+impl Notifier for SlackConfig {
+    fn kind(&self) -> &str {
+        self.default.kind()
+    }
+
+    fn name(&self) -> &str {
+        self.default.name()
+    }
+
+    fn channels(&self) -> Vec<String> {
+        self.default.channels()
+    }
+
+    fn config(&mut self, g_conf: &NotifierSetting) -> Result<()> {
+        self.default.kind = "slack".to_string();
+        self.default.format = Format::Slack;
+        self.default.config(g_conf)?;
+
+        if self.webhook.is_empty() {
+            bail!("[{} / {}] webhook is required", self.kind(), self.name());
+        }
+
+        let client = Client::builder().timeout(self.default.timeout).build()?;
+        let webhook = self.webhook.clone();
+        self.default.send_func = Some(Box::new(move |notification: Notification| {
+            let client = client.clone();
+            let webhook = webhook.clone();
+            let messages = split_message(&notification.body);
+            let delivered = notification.delivered;
+            Box::pin(async move {
+                // the messages delivered by the previous attempts are not sent again
+                for (i, m) in messages?
+                    .into_iter()
+                    .enumerate()
+                    .skip(delivered.load(Ordering::SeqCst))
+                {
+                    let request = client
+                        .post(&webhook)
+                        .header(CONTENT_TYPE, "application/json")
+                        .body(m);
+                    send_http("Slack", request).await?;
+                    delivered.store(i + 1, Ordering::SeqCst);
+                }
+                Ok(())
+            })
+        }));
+
+        Ok(())
+    }
+
+    async fn notify(&self, result: Arc<ProbeResult>) {
+        self.default.notify(result).await
+    }
+
+    async fn notify_stat(&self, probers: Vec<Arc<RwLock<dyn Prober>>>) {
+        self.default.notify_stat(probers).await
+    }
+
+    fn dry_notify(&self, res: Arc<ProbeResult>) {
+        self.default.dry_notify(res)
+    }
+
+    fn dry_notify_stat(&self, probers: Vec<Arc<RwLock<dyn Prober>>>) {
+        self.default.dry_notify_stat(probers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{channel::new_dummy_prober, notify::testing::CaptureServer, Status};
+
+    use super::*;
+
+    fn slack(webhook: &str) -> SlackConfig {
+        let mut s: SlackConfig = serde_yaml::from_str(&format!(
+            "name: slack\nwebhook: {}\nretry:\n  times: 2\n  interval: {{ secs: 0, nanos: 1000 }}",
+            webhook
+        ))
+        .unwrap();
+        s.config(&NotifierSetting::default()).unwrap();
+        s
+    }
+
+    #[tokio::test]
+    async fn test_slack() {
+        let server = CaptureServer::start(200, "ok").await;
+        let s = slack(&format!("{}/services/T0/B0/X0", server.url));
+
+        let result = ProbeResult {
+            name: "Web <prod>".to_string(),
+            endpoint: format!("https://example.com/{}", "a".repeat(3000)),
+            status: Status::Down,
+            message: "HTTP Status Code is 500".to_string(),
+            ..Default::default()
+        };
+        s.notify(Arc::new(result)).await;
+
+        let reqs = server.requests().await;
+        assert_eq!(reqs.len(), 1);
+        assert_eq!(reqs[0].method, "POST");
+        assert_eq!(reqs[0].path, "/services/T0/B0/X0");
+        assert_eq!(reqs[0].headers["content-type"], "application/json");
+
+        let body: serde_json::Value = serde_json::from_str(&reqs[0].body).unwrap();
+        assert_eq!(body["text"], "❌ Web <prod> Failure");
+        assert_eq!(body["blocks"][0]["type"], "header");
+        let fields = body["blocks"][1]["fields"].to_string();
+        assert!(fields.contains("https://example.com"));
+        assert!(fields.contains("⏱ 0ms"));
+        let endpoint = body["blocks"][1]["fields"][0]["text"].as_str().unwrap();
+        assert_eq!(endpoint.chars().count(), 2000);
+        assert!(body["blocks"][2]["elements"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains(" at "));
+    }
+
+    #[tokio::test]
+    async fn test_slack_sla() {
+        let server = CaptureServer::start(200, "ok").await;
+        let s = slack(&server.url);
+
+        let prober: Arc<RwLock<dyn Prober>> =
+            Arc::new(new_dummy_prober("http", "", "dummy", vec![]));
+        prober.write().await.result().name = "dummy".to_string();
+        s.notify_stat(vec![prober]).await;
+
+        let reqs = server.requests().await;
+        let body: serde_json::Value = serde_json::from_str(&reqs[0].body).unwrap();
+        assert_eq!(body["text"], "Overall SLA Report");
+        assert_eq!(
+            body["blocks"][1]["fields"][0]["text"],
+            "*dummy* ⛔️\n\nSLA: 100.00%"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_slack_sla_split() {
+        let server = CaptureServer::start(200, "ok").await;
+        let s = slack(&server.url);
+
+        let mut probers: Vec<Arc<RwLock<dyn Prober>>> = vec![];
+        for i in 0..1000 {
+            let name = format!("web-{:04}", i);
+            let prober = new_dummy_prober("http", "", &name, vec![]);
+            prober.write().await.result().name = name;
+            probers.push(Arc::new(prober));
+        }
+        s.notify_stat(probers).await;
+
+        // 1 header, 100 sections of 10 probers and 1 footer in 3 messages
+        let reqs = server.requests().await;
+        assert_eq!(reqs.len(), 3);
+        let mut fields = vec![];
+        let mut blocks = 0;
+        for req in &reqs {
+            let body: serde_json::Value = serde_json::from_str(&req.body).unwrap();
+            assert_eq!(body["text"], "Overall SLA Report");
+            let bs = body["blocks"].as_array().unwrap();
+            assert!(bs.len() <= BLOCKS_MAX);
+            blocks += bs.len();
+            for b in bs.iter().filter(|b| b["type"] == "section") {
+                for f in b["fields"].as_array().unwrap() {
+                    fields.push(f["text"].as_str().unwrap().to_string());
+                }
+            }
+        }
+        assert_eq!(blocks, 102);
+        assert_eq!(fields.len(), 1000);
+        assert!(fields[999].starts_with("*web-0999*"));
+    }
+
+    #[test]
+    fn test_split_message() {
+        let msg = json!({ "text": "t", "blocks": vec![json!({ "type": "divider" }); 101] });
+        let messages = split_message(&msg.to_string()).unwrap();
+        assert_eq!(messages.len(), 3);
+        let last: Value = serde_json::from_str(&messages[2]).unwrap();
+        assert_eq!(last["text"], "t");
+        assert_eq!(last["blocks"].as_array().unwrap().len(), 1);
+        assert!(split_message("{}").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_slack_error() {
+        let server = CaptureServer::start(404, "no_team").await;
+        let s = slack(&server.url);
+
+        s.notify(Arc::new(ProbeResult::default())).await;
+        // retried on the error response
+        assert_eq!(server.requests().await.len(), 2);
+
+        let mut s: SlackConfig = serde_yaml::from_str("name: slack\nwebhook: ''").unwrap();
+        assert!(s.config(&NotifierSetting::default()).is_err());
+    }
+}
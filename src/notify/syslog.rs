@@ -0,0 +1,248 @@
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use tokio::{
+    io::AsyncWriteExt,
+    net::{TcpStream, UdpSocket},
+    sync::Mutex,
+};
+
+use crate::Status;
+
+/// The local syslog socket.
+const SYSLOG_SOCKET: &str = "/dev/log";
+const SYSLOG_PORT: u16 = 514;
+/// The `daemon` facility.
+const FACILITY: u8 = 3;
+const APP_NAME: &str = "easeprobe";
+
+/// The syslog severity (RFC 5424 section 6.2.1).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Severity {
+    Error = 3,
+    Warning = 4,
+    Notice = 5,
+    Info = 6,
+}
+
+impl Severity {
+    /// The severity of the probe result, the recovery is a notice.
+    pub(crate) fn of(status: Status, pre_status: Status) -> Self {
+        match status {
+            Status::Down => Severity::Error,
+            Status::Bad | Status::Unknown => Severity::Warning,
+            Status::Up if pre_status != Status::Init && pre_status != Status::Up => {
+                Severity::Notice
+            }
+            Status::Up | Status::Init => Severity::Info,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Target {
+    #[cfg(unix)]
+    Local(String),
+    Udp(String),
+    Tcp(String),
+}
+
+#[derive(Debug)]
+enum Conn {
+    #[cfg(unix)]
+    Local(tokio::net::UnixDatagram),
+    Udp(UdpSocket),
+    Tcp(TcpStream),
+}
+
+/// The syslog writer of the RFC 5424 messages, the connection is
+/// established on the first message and re-established after a failure.
+#[derive(Debug)]
+pub(crate) struct Syslog {
+    target: Target,
+    hostname: String,
+    timeout: Duration,
+    conn: Mutex<Option<Conn>>,
+}
+
+impl Syslog {
+    /// The local syslog if the host is empty, otherwise the remote one
+    /// over the network `udp` (default) or `tcp`.
+    pub(crate) fn new(host: &str, network: &str, timeout: Duration) -> Result<Self> {
+        let target = if host.is_empty() {
+            #[cfg(unix)]
+            {
+                Target::Local(SYSLOG_SOCKET.to_string())
+            }
+            #[cfg(not(unix))]
+            bail!("the local syslog is not supported");
+        } else {
+            let addr = if host
+                .rsplit_once(':')
+                .is_some_and(|(_, p)| p.parse::<u16>().is_ok())
+            {
+                host.to_string()
+            } else {
+                format!("{}:{}", host, SYSLOG_PORT)
+            };
+            match network.to_lowercase().as_str() {
+                "" | "udp" => Target::Udp(addr),
+                "tcp" => Target::Tcp(addr),
+                _ => bail!("invalid syslog network {}, must be udp or tcp", network),
+            }
+        };
+        Ok(Self {
+            target,
+            hostname: hostname(),
+            timeout,
+            conn: Mutex::new(None),
+        })
+    }
+
+    #[cfg(all(test, unix))]
+    pub(crate) fn local(path: &str) -> Self {
+        Self {
+            target: Target::Local(path.to_string()),
+            hostname: hostname(),
+            timeout: Duration::from_secs(5),
+            conn: Mutex::new(None),
+        }
+    }
+
+    /// Formats the RFC 5424 message, the `msg_id` is the type of the notification.
+    fn format(&self, severity: Severity, msg_id: &str, msg: &str) -> String {
+        format!(
+            "<{}>1 {} {} {} {} {} - {}",
+            FACILITY * 8 + severity as u8,
+            chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Micros, true),
+            self.hostname,
+            APP_NAME,
+            std::process::id(),
+            msg_id,
+            msg
+        )
+    }
+
+    async fn connect(&self) -> Result<Conn> {
+        let conn = match &self.target {
+            #[cfg(unix)]
+            Target::Local(path) => {
+                let socket = tokio::net::UnixDatagram::unbound()?;
+                socket.connect(path)?;
+                Conn::Local(socket)
+            }
+            Target::Udp(addr) => {
+                let remote = tokio::net::lookup_host(addr)
+                    .await?
+                    .next()
+                    .with_context(|| format!("failed to resolve {}", addr))?;
+                let local = if remote.is_ipv6() {
+                    "[::]:0"
+                } else {
+                    "0.0.0.0:0"
+                };
+                let socket = UdpSocket::bind(local).await?;
+                socket.connect(remote).await?;
+                Conn::Udp(socket)
+            }
+            Target::Tcp(addr) => Conn::Tcp(TcpStream::connect(addr).await?),
+        };
+        Ok(conn)
+    }
+
+    async fn write(&self, conn: &mut Conn, record: &str) -> Result<()> {
+        match conn {
+            #[cfg(unix)]
+            Conn::Local(socket) => {
+                socket.send(record.as_bytes()).await?;
+            }
+            Conn::Udp(socket) => {
+                socket.send(record.as_bytes()).await?;
+            }
+            // the octet counting framing of RFC 6587
+            Conn::Tcp(stream) => {
+                let frame = format!("{} {}", record.len(), record);
+                stream.write_all(frame.as_bytes()).await?;
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) async fn send(&self, severity: Severity, msg_id: &str, msg: &str) -> Result<()> {
+        let record = self.format(severity, msg_id, msg);
+        let mut conn = self.conn.lock().await;
+        let result = tokio::time::timeout(self.timeout, async {
+            if conn.is_none() {
+                *conn = Some(self.connect().await?);
+            }
+            self.write(conn.as_mut().unwrap(), &record).await
+        })
+        .await
+        .with_context(|| format!("syslog {:?} timed out", self.target))
+        .and_then(|r| r);
+        if result.is_err() {
+            // reconnects on the next message
+            *conn = None;
+        }
+        result
+    }
+}
+
+fn hostname() -> String {
+    std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .ok()
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty() && !h.contains(' '))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_severity() {
+        assert_eq!(Severity::of(Status::Down, Status::Up), Severity::Error);
+        assert_eq!(Severity::of(Status::Unknown, Status::Up), Severity::Warning);
+        assert_eq!(Severity::of(Status::Up, Status::Down), Severity::Notice);
+        assert_eq!(Severity::of(Status::Up, Status::Init), Severity::Info);
+    }
+
+    #[test]
+    fn test_new() {
+        let s = Syslog::new("10.0.0.1", "", Duration::from_secs(1)).unwrap();
+        assert_eq!(s.target, Target::Udp("10.0.0.1:514".to_string()));
+        let s = Syslog::new("[::1]:1514", "TCP", Duration::from_secs(1)).unwrap();
+        assert_eq!(s.target, Target::Tcp("[::1]:1514".to_string()));
+        assert!(Syslog::new("10.0.0.1", "quic", Duration::from_secs(1)).is_err());
+    }
+
+    #[test]
+    fn test_format() {
+        let s = Syslog::new("10.0.0.1", "", Duration::from_secs(1)).unwrap();
+        let record = s.format(Severity::Error, "Notification", "Web Failure");
+        assert!(record.starts_with("<27>1 "));
+        assert!(record.ends_with(&format!(
+            " easeprobe {} Notification - Web Failure",
+            std::process::id()
+        )));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_local() {
+        let path = std::env::temp_dir().join(format!("easeprobe-syslog-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let server = tokio::net::UnixDatagram::bind(&path).unwrap();
+
+        let s = Syslog::local(path.to_str().unwrap());
+        s.send(Severity::Info, "SLA", "report").await.unwrap();
+        let mut buf = [0u8; 1024];
+        let n = server.recv(&mut buf).await.unwrap();
+        let record = String::from_utf8_lossy(&buf[..n]);
+        assert!(record.starts_with("<30>1 "));
+        assert!(record.ends_with("SLA - report"));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
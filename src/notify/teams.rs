@@ -0,0 +1,115 @@
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::RwLock;
+
+use crate::{
+    global::NotifierSetting,
+    probe::{ProbeResult, Prober},
+    Format,
+};
+
+use super::{send_http, DefaultNotifier, Notification, Notifier};
+
+/// The Microsoft Teams notifier posts the message cards to an incoming webhook.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TeamsConfig {
+    #[serde(flatten)]
+    default: DefaultNotifier,
+    webhook: String,
+}
+
+/// Wraps the markdown message into the Office 365 connector card.
+fn message_card(title: &str, text: &str) -> serde_json::Value {
+    json!({
+        "@type": "MessageCard",
+        "@context": "https://schema.org/extensions",
+        "summary": title,
+        "title": title,
+        "sections": [{ "text": text, "markdown": true }],
+    })
+}
+
+#[async_trait]
+impl Notifier for TeamsConfig {
+    fn kind(&self) -> &str {
+        self.default.kind()
+    }
+
+    fn name(&self) -> &str {
+        self.default.name()
+    }
+
+    fn channels(&self) -> Vec<String> {
+        self.default.channels()
+    }
+
+    fn config(&mut self, g_conf: &NotifierSetting) -> Result<()> {
+        self.default.kind = "teams".to_string();
+        self.default.format = Format::Markdown;
+        self.default.config(g_conf)?;
+
+        if self.webhook.is_empty() {
+            bail!("[{} / {}] webhook is required", self.kind(), self.name());
+        }
+
+        let client = Client::builder().timeout(self.default.timeout).build()?;
+        let webhook = self.webhook.clone();
+        self.default.send_func = Some(Box::new(move |notification: Notification| {
+            let request = client
+                .post(&webhook)
+                .json(&message_card(&notification.title, &notification.body));
+            Box::pin(async move { send_http("Teams", request).await.map(|_| ()) })
+        }));
+
+        Ok(())
+    }
+
+    async fn notify(&self, result: Arc<ProbeResult>) {
+        self.default.notify(result).await
+    }
+
+    async fn notify_stat(&self, probers: Vec<Arc<RwLock<dyn Prober>>>) {
+        self.default.notify_stat(probers).await
+    }
+
+    fn dry_notify(&self, res: Arc<ProbeResult>) {
+        self.default.dry_notify(res)
+    }
+
+    fn dry_notify_stat(&self, probers: Vec<Arc<RwLock<dyn Prober>>>) {
+        self.default.dry_notify_stat(probers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{channel::new_dummy_prober, notify::testing::CaptureServer};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_teams() {
+        let server = CaptureServer::start(200, "1").await;
+        let mut t: TeamsConfig =
+            serde_yaml::from_str(&format!("name: teams\nwebhook: {}", server.url)).unwrap();
+        t.config(&NotifierSetting::default()).unwrap();
+
+        let prober: Arc<RwLock<dyn Prober>> =
+            Arc::new(new_dummy_prober("http", "", "dummy", vec![]));
+        prober.write().await.result().name = "a|b".to_string();
+        t.notify_stat(vec![prober]).await;
+
+        let reqs = server.requests().await;
+        let body: serde_json::Value = serde_json::from_str(&reqs[0].body).unwrap();
+        assert_eq!(body["@type"], "MessageCard");
+        assert_eq!(body["summary"], "Overall SLA Report");
+        let text = body["sections"][0]["text"].as_str().unwrap();
+        assert!(text.contains("| Name | Kind | Endpoint | Status | SLA |"));
+        assert!(text.contains("| a\\|b |  |  | ⛔️ unknown | 100.00% | 0 (-) | 0s |"));
+    }
+}
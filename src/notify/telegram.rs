@@ -0,0 +1,474 @@
+use std::sync::{atomic::Ordering, Arc, LazyLock};
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use regex::Regex;
+use reqwest::Client;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::RwLock;
+
+use crate::{
+    global::NotifierSetting,
+    probe::{ProbeResult, Prober},
+    Format,
+};
+
+use super::{send_http, DefaultNotifier, Notification, Notifier};
+
+/// The max number of chars of a Telegram message.
+const MESSAGE_MAX: usize = 4096;
+
+fn default_api() -> String {
+    "https://api.telegram.org".to_string()
+}
+
+fn default_format() -> String {
+    "markdown".to_string()
+}
+
+/// The chat ID is a number for the groups and channels, or `@channelusername`.
+fn deserialize_chat_id<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum ChatId {
+        Id(i64),
+        Name(String),
+    }
+    Ok(match ChatId::deserialize(deserializer)? {
+        ChatId::Id(id) => id.to_string(),
+        ChatId::Name(name) => name,
+    })
+}
+
+/// The Telegram notifier sends the messages via the Bot API `sendMessage`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TelegramConfig {
+    #[serde(flatten)]
+    default: DefaultNotifier,
+    token: String,
+    #[serde(deserialize_with = "deserialize_chat_id")]
+    chat_id: String,
+    /// The message format, `markdown` or `html`
+    #[serde(default = "default_format")]
+    format: String,
+    /// The Bot API base URL
+    #[serde(default = "default_api")]
+    api: String,
+}
+
+/// The HTML tags supported by Telegram.
+const SUPPORTED_TAGS: [&str; 14] = [
+    "b",
+    "strong",
+    "i",
+    "em",
+    "u",
+    "ins",
+    "s",
+    "strike",
+    "del",
+    "a",
+    "code",
+    "pre",
+    "blockquote",
+    "tg-spoiler",
+];
+
+static HTML_TAG: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<(/?)([a-zA-Z][a-zA-Z0-9-]*)[^>]*>(\n?)").unwrap());
+static BLANK_LINES: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\n{3,}").unwrap());
+
+/// Rewrites the HTML with the tags supported by Telegram, the others are replaced by
+/// the line breaks or the cell separators, or removed along with the line break after them.
+fn telegram_html(html: &str) -> String {
+    let text = HTML_TAG.replace_all(html, |c: &regex::Captures| {
+        let closing = !c[1].is_empty();
+        let tag = c[2].to_lowercase();
+        if SUPPORTED_TAGS.contains(&tag.as_str()) {
+            return c[0].to_string();
+        }
+        match (tag.as_str(), closing) {
+            ("br", _) | ("tr" | "p" | "div" | "table", true) => "\n".to_string(),
+            ("td" | "th", true) => " | ".to_string(),
+            _ => String::new(),
+        }
+    });
+    let text = text.replace(" | \n", "\n");
+    BLANK_LINES.replace_all(&text, "\n\n").to_string()
+}
+
+/// Splits the text on the line breaks into the chunks of at most `max` chars.
+/// A longer line is cut, but never right after the markdown escape char.
+fn split_text(text: &str, max: usize) -> Vec<String> {
+    let mut chunks = vec![];
+    let mut chunk = String::new();
+    let mut len = 0;
+    for line in text.split_inclusive('\n') {
+        let n = line.chars().count();
+        if len + n > max && !chunk.is_empty() {
+            chunks.push(std::mem::take(&mut chunk));
+            len = 0;
+        }
+        if n <= max {
+            chunk.push_str(line);
+            len += n;
+            continue;
+        }
+
+        let chars: Vec<char> = line.chars().collect();
+        let mut start = 0;
+        while chars.len() - start > max {
+            let mut end = start + max;
+            while end > start + 1 && chars[end - 1] == '\\' {
+                end -= 1;
+            }
+            chunks.push(chars[start..end].iter().collect());
+            start = end;
+        }
+        chunk = chars[start..].iter().collect();
+        len = chars.len() - start;
+    }
+    if !chunk.trim().is_empty() {
+        chunks.push(chunk);
+    }
+    chunks
+}
+
+static HTML_TOKEN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<[^>]*>|&[a-zA-Z0-9#]+;|[^<&]|[<&]").unwrap());
+
+/// Opens and closes the tags of the text, the open tags are the names and the opening tags.
+fn apply_tags(open: &mut Vec<(String, String)>, text: &str) {
+    for tag in HTML_TAG.captures_iter(text) {
+        let name = tag[2].to_lowercase();
+        if tag[1].is_empty() {
+            open.push((name, tag[0].trim_end().to_string()));
+        } else if let Some(i) = open.iter().rposition(|(n, _)| *n == name) {
+            open.truncate(i);
+        }
+    }
+}
+
+/// The closing tags of the open tags.
+fn closing_tags(open: &[(String, String)]) -> String {
+    open.iter()
+        .rev()
+        .map(|(name, _)| format!("</{}>", name))
+        .collect()
+}
+
+/// The HTML chunk being built, it knows the tags open at its end.
+#[derive(Default)]
+struct HtmlChunk {
+    text: String,
+    len: usize,
+    open: Vec<(String, String)>,
+}
+
+impl HtmlChunk {
+    /// The chars after appending the text and closing the open tags.
+    fn len_with(&self, text: &str) -> usize {
+        let mut open = self.open.clone();
+        apply_tags(&mut open, text);
+        self.len + text.chars().count() + closing_tags(&open).chars().count()
+    }
+
+    /// Whether there is nothing but the reopened tags.
+    fn is_empty(&self) -> bool {
+        HTML_TAG.replace_all(&self.text, "").is_empty()
+    }
+
+    fn push(&mut self, text: &str) {
+        apply_tags(&mut self.open, text);
+        self.text.push_str(text);
+        self.len += text.chars().count();
+    }
+
+    /// Closes the open tags and takes the text, the next chunk reopens the tags.
+    fn flush(&mut self, chunks: &mut Vec<String>) {
+        let text = std::mem::take(&mut self.text) + &closing_tags(&self.open);
+        if !HTML_TAG.replace_all(&text, "").trim().is_empty() {
+            chunks.push(text);
+        }
+        self.text = self.open.iter().map(|(_, tag)| tag.as_str()).collect();
+        self.len = self.text.chars().count();
+    }
+}
+
+/// Splits the HTML on the line breaks into the chunks of at most `max` chars like `split_text()`,
+/// the tags and the entities are never cut, and the tags open at the end of a chunk are closed
+/// and reopened in the next chunk.
+fn split_html(text: &str, max: usize) -> Vec<String> {
+    let mut chunks = vec![];
+    let mut chunk = HtmlChunk::default();
+    for line in text.split_inclusive('\n') {
+        if chunk.len_with(line) > max && !chunk.is_empty() {
+            chunk.flush(&mut chunks);
+        }
+        if chunk.len_with(line) <= max {
+            chunk.push(line);
+            continue;
+        }
+        for token in HTML_TOKEN.find_iter(line) {
+            if chunk.len_with(token.as_str()) > max && !chunk.is_empty() {
+                chunk.flush(&mut chunks);
+            }
+            chunk.push(token.as_str());
+        }
+    }
+    chunk.flush(&mut chunks);
+    chunks
+}
+
+#[async_trait]
+impl Notifier for TelegramConfig {
+    fn kind(&self) -> &str {
+        self.default.kind()
+    }
+
+    fn name(&self) -> &str {
+        self.default.name()
+    }
+
+    fn channels(&self) -> Vec<String> {
+        self.default.channels()
+    }
+
+    fn config(&mut self, g_conf: &NotifierSetting) -> Result<()> {
+        self.default.kind = "telegram".to_string();
+        let parse_mode = match self.format.to_lowercase().as_str() {
+            "markdown" => {
+                self.default.format = Format::MarkdownSocial;
+                "MarkdownV2"
+            }
+            "html" => {
+                self.default.format = Format::HTML;
+                "HTML"
+            }
+            _ => bail!(
+                "[{} / {}] unsupported format `{}`, it must be `markdown` or `html`",
+                self.default.kind,
+                self.name(),
+                self.format
+            ),
+        };
+        self.default.config(g_conf)?;
+
+        if self.token.is_empty() || self.chat_id.is_empty() {
+            bail!(
+                "[{} / {}] token and chat_id are required",
+                self.kind(),
+                self.name()
+            );
+        }
+
+        let client = Client::builder().timeout(self.default.timeout).build()?;
+        let url = format!(
+            "{}/bot{}/sendMessage",
+            self.api.trim_end_matches('/'),
+            self.token
+        );
+        let chat_id = self.chat_id.clone();
+        self.default.send_func = Some(Box::new(move |notification: Notification| {
+            let client = client.clone();
+            let url = url.clone();
+            let chat_id = chat_id.clone();
+            let chunks = if parse_mode == "HTML" {
+                split_html(&telegram_html(&notification.body), MESSAGE_MAX)
+            } else {
+                split_text(&notification.body, MESSAGE_MAX)
+            };
+            let delivered = notification.delivered;
+            Box::pin(async move {
+                // the chunks delivered by the previous attempts are not sent again
+                for (i, chunk) in chunks
+                    .into_iter()
+                    .enumerate()
+                    .skip(delivered.load(Ordering::SeqCst))
+                {
+                    let request = client.post(&url).json(&json!({
+                        "chat_id": chat_id,
+                        "text": chunk,
+                        "parse_mode": parse_mode,
+                        "disable_web_page_preview": true,
+                    }));
+                    let body = send_http("Telegram", request).await?;
+                    let resp: Value = serde_json::from_str(&body)?;
+                    if resp["ok"] != true {
+                        bail!("Error response from Telegram - {}", body);
+                    }
+                    delivered.store(i + 1, Ordering::SeqCst);
+                }
+                Ok(())
+            })
+        }));
+
+        Ok(())
+    }
+
+    async fn notify(&self, result: Arc<ProbeResult>) {
+        self.default.notify(result).await
+    }
+
+    async fn notify_stat(&self, probers: Vec<Arc<RwLock<dyn Prober>>>) {
+        self.default.notify_stat(probers).await
+    }
+
+    fn dry_notify(&self, res: Arc<ProbeResult>) {
+        self.default.dry_notify(res)
+    }
+
+    fn dry_notify_stat(&self, probers: Vec<Arc<RwLock<dyn Prober>>>) {
+        self.default.dry_notify_stat(probers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{channel::new_dummy_prober, notify::testing::CaptureServer, Status};
+
+    use super::*;
+
+    fn telegram(api: &str, format: &str) -> TelegramConfig {
+        let mut t: TelegramConfig = serde_yaml::from_str(&format!(
+            "name: telegram\ntoken: 123:ABC\nchat_id: -100123\nformat: {}\napi: {}",
+            format, api
+        ))
+        .unwrap();
+        t.config(&NotifierSetting::default()).unwrap();
+        t
+    }
+
+    #[tokio::test]
+    async fn test_telegram_markdown() {
+        let server = CaptureServer::start(200, r#"{"ok":true}"#).await;
+        let t = telegram(&server.url, "markdown");
+
+        let result = ProbeResult {
+            name: "web-1 (prod)".to_string(),
+            endpoint: "https://example.com".to_string(),
+            status: Status::Down,
+            message: "x".repeat(5000),
+            ..Default::default()
+        };
+        t.notify(Arc::new(result)).await;
+
+        let reqs = server.requests().await;
+        // the long line is cut into 2 messages after the title lines
+        assert_eq!(reqs.len(), 3);
+        assert_eq!(reqs[0].path, "/bot123:ABC/sendMessage");
+        let body: Value = serde_json::from_str(&reqs[0].body).unwrap();
+        assert_eq!(body["chat_id"], "-100123");
+        assert_eq!(body["parse_mode"], "MarkdownV2");
+        let text = body["text"].as_str().unwrap();
+        assert!(text.starts_with("*web\\-1 \\(prod\\) Failure* ❌\nhttps://example\\.com \\- ⏱"));
+        for req in &reqs {
+            let body: Value = serde_json::from_str(&req.body).unwrap();
+            assert!(body["text"].as_str().unwrap().chars().count() <= MESSAGE_MAX);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_telegram_html() {
+        let server = CaptureServer::start(200, r#"{"ok":true}"#).await;
+        let t = telegram(&server.url, "html");
+
+        let prober: Arc<RwLock<dyn Prober>> =
+            Arc::new(new_dummy_prober("http", "", "dummy", vec![]));
+        prober.write().await.result().name = "<dummy>".to_string();
+        t.notify_stat(vec![prober]).await;
+
+        let reqs = server.requests().await;
+        let body: Value = serde_json::from_str(&reqs[0].body).unwrap();
+        assert_eq!(body["parse_mode"], "HTML");
+        let text = body["text"].as_str().unwrap();
+        assert!(
+            text.starts_with("<b>Overall SLA Report</b>\nName | Kind | Endpoint | Status | SLA |"),
+            "{}",
+            text
+        );
+        assert!(text.contains("&lt;dummy&gt; |  |  | ⛔️ unknown | 100.00% | 0 (-) | 0s |"));
+        assert!(text.contains("\n1 prober - 0 up, 0 down, 1 other - overall SLA 100.00%\n"));
+        assert!(!text.contains("<td>"));
+    }
+
+    #[test]
+    fn test_split_html() {
+        let html = format!(
+            "<b>Title</b>\n<pre>{}</pre>\n<a href=\"https://example.com\">{}</a>",
+            "line &amp; &lt;more&gt;\n".repeat(500),
+            "x".repeat(100)
+        );
+        let chunks = split_html(&html, 1000);
+        assert!(chunks.len() > 10);
+        assert!(chunks[0].starts_with("<b>Title</b>\n<pre>line &amp; &lt;more&gt;\n"));
+        for chunk in &chunks {
+            assert!(chunk.chars().count() <= 1000, "{}", chunk);
+            // the tags are balanced in every chunk
+            let mut open = vec![];
+            apply_tags(&mut open, chunk);
+            assert!(open.is_empty(), "{}", chunk);
+            // the entities are not cut
+            assert!(!chunk.ends_with('&') && !chunk.contains("&a<") && !chunk.contains("&l<"));
+        }
+        for chunk in &chunks[1..chunks.len() - 1] {
+            assert!(
+                chunk.starts_with("<pre>") && chunk.ends_with("</pre>"),
+                "{}",
+                chunk
+            );
+        }
+        assert!(chunks.last().unwrap().ends_with("</a>"));
+
+        // a long line in the tag is cut between the entities and reopened
+        let html = format!("<pre>{}</pre>", "&amp;".repeat(100));
+        let chunks = split_html(&html, 100);
+        assert!(chunks.len() > 5);
+        for chunk in &chunks {
+            assert!(chunk.chars().count() <= 100);
+            assert!(
+                chunk.starts_with("<pre>&amp;") && chunk.ends_with("&amp;</pre>"),
+                "{}",
+                chunk
+            );
+        }
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|c| c.matches("&amp;").count())
+                .sum::<usize>(),
+            100
+        );
+    }
+
+    #[tokio::test]
+    async fn test_telegram_error() {
+        let server = CaptureServer::start(200, r#"{"ok":false}"#).await;
+        let mut t: TelegramConfig = serde_yaml::from_str(&format!(
+            "name: t\ntoken: x\nchat_id: 1\napi: {}\nretry:\n  times: 2\n  interval: {{ secs: 0, nanos: 1000 }}",
+            server.url
+        ))
+        .unwrap();
+        t.config(&NotifierSetting::default()).unwrap();
+        t.notify(Arc::new(ProbeResult::default())).await;
+        // `ok: false` is a failure to retry
+        assert_eq!(server.requests().await.len(), 2);
+
+        let mut t: TelegramConfig =
+            serde_yaml::from_str("name: t\ntoken: x\nchat_id: '@channel'\nformat: text").unwrap();
+        assert!(t.config(&NotifierSetting::default()).is_err());
+    }
+
+    #[test]
+    fn test_split_text() {
+        assert_eq!(split_text("a\nb\nc", 4), vec!["a\nb\n", "c"]);
+        assert_eq!(split_text("abcdef", 4), vec!["abcd", "ef"]);
+        // never leave the escape char at the end of a chunk
+        assert_eq!(split_text("ab\\.cd", 3), vec!["ab", "\\.c", "d"]);
+        assert!(split_text("", 4).is_empty());
+    }
+}
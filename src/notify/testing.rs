@@ -0,0 +1,119 @@
+//! The local HTTP capture server for the notifier tests.
+
+use std::{collections::HashMap, sync::Arc};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::Mutex,
+};
+
+/// A request received by the capture server.
+#[derive(Debug, Clone)]
+pub struct Captured {
+    pub method: String,
+    pub path: String,
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+/// An HTTP server that records every request and replies with the responses in order,
+/// the last response is repeated.
+pub struct CaptureServer {
+    pub url: String,
+    requests: Arc<Mutex<Vec<Captured>>>,
+}
+
+impl CaptureServer {
+    pub async fn start(status: u16, body: &str) -> Self {
+        Self::start_with(&[(status, body)]).await
+    }
+
+    pub async fn start_with(responses: &[(u16, &str)]) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(vec![]));
+
+        let reqs = Arc::clone(&requests);
+        let responses: Arc<Vec<(u16, String)>> = Arc::new(
+            responses
+                .iter()
+                .map(|(status, body)| (*status, body.to_string()))
+                .collect(),
+        );
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let reqs = Arc::clone(&reqs);
+                let responses = Arc::clone(&responses);
+                tokio::spawn(async move { serve(stream, &responses, reqs).await });
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    pub async fn requests(&self) -> Vec<Captured> {
+        self.requests.lock().await.clone()
+    }
+}
+
+async fn serve(
+    mut stream: TcpStream,
+    responses: &[(u16, String)],
+    requests: Arc<Mutex<Vec<Captured>>>,
+) -> Option<()> {
+    let mut buf = vec![];
+    let mut chunk = [0u8; 4096];
+    let head_end = loop {
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buf[..head_end]).to_string();
+    let mut lines = head.lines();
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let path = request_line.next()?.to_string();
+    let headers: HashMap<String, String> = lines
+        .filter_map(|l| l.split_once(':'))
+        .map(|(k, v)| (k.trim().to_lowercase(), v.trim().to_string()))
+        .collect();
+
+    let len: usize = headers
+        .get("content-length")
+        .and_then(|l| l.parse().ok())
+        .unwrap_or(0);
+    while buf.len() < head_end + len {
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+    // record the request before replying, so the sender always sees it
+    let (status, body) = {
+        let mut requests = requests.lock().await;
+        requests.push(Captured {
+            method,
+            path,
+            headers,
+            body: String::from_utf8_lossy(&buf[head_end..]).to_string(),
+        });
+        &responses[(requests.len() - 1).min(responses.len() - 1)]
+    };
+
+    let response = format!(
+        "HTTP/1.1 {} Captured\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await.ok()?;
+    stream.shutdown().await.ok()
+}
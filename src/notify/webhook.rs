@@ -0,0 +1,348 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use regex::Regex;
+use reqwest::{header::CONTENT_TYPE, Client, Method};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::{
+    format_funcs,
+    global::{self, NotifierSetting},
+    probe::{ProbeResult, Prober},
+    Format,
+};
+
+use super::{send_http, DefaultNotifier, Notification, Notifier};
+
+/// The fields available in the body template.
+const FIELDS: [&str; 9] = [
+    "title",
+    "name",
+    "endpoint",
+    "status",
+    "pre_status",
+    "message",
+    "rtt",
+    "time",
+    "timestamp",
+];
+
+/// The placeholder `{{field}}`, or `{{field|json}}` for the JSON string literal.
+static PLACEHOLDER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{\{\s*(\w+)\s*(?:\|\s*(\w+)\s*)?\}\}").unwrap());
+
+fn default_method() -> String {
+    "POST".to_string()
+}
+
+fn default_template() -> String {
+    r#"{"title": {{title|json}}, "name": {{name|json}}, "endpoint": {{endpoint|json}}, "status": {{status|json}}, "pre_status": {{pre_status|json}}, "message": {{message|json}}, "rtt": {{rtt}}, "time": {{time|json}}, "timestamp": {{timestamp}}}"#.to_string()
+}
+
+/// The webhook notifier sends the templated payload to any HTTP endpoint.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    #[serde(flatten)]
+    default: DefaultNotifier,
+    url: String,
+    #[serde(default = "default_method")]
+    method: String,
+    #[serde(default)]
+    headers: HashMap<String, String>,
+    /// The body template, see `FIELDS` for the placeholders
+    #[serde(default = "default_template")]
+    body: String,
+    /// Signs the requests with HMAC-SHA256 if set
+    #[serde(default)]
+    secret: String,
+}
+
+/// Renders the template with the fields, the unknown placeholders are kept as is.
+fn render(template: &str, fields: &HashMap<&str, String>) -> String {
+    PLACEHOLDER
+        .replace_all(template, |c: &regex::Captures| {
+            let Some(value) = fields.get(&c[1]) else {
+                return c[0].to_string();
+            };
+            match c.get(2) {
+                Some(_) => serde_json::Value::String(value.clone()).to_string(),
+                None => value.clone(),
+            }
+        })
+        .to_string()
+}
+
+/// Checks the placeholders of the template are all known.
+fn check_template(template: &str) -> Result<()> {
+    for c in PLACEHOLDER.captures_iter(template) {
+        if !FIELDS.contains(&&c[1]) {
+            bail!("unknown field `{}` in the body template", &c[1]);
+        }
+        if let Some(filter) = c.get(2) {
+            if filter.as_str() != "json" {
+                bail!("unknown filter `{}` in the body template", filter.as_str());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn result_fields(r: &ProbeResult) -> HashMap<&'static str, String> {
+    let time = chrono::DateTime::<chrono::Utc>::from(r.start_time);
+    HashMap::from([
+        ("title", r.title()),
+        ("name", r.name.clone()),
+        ("endpoint", r.endpoint.clone()),
+        ("status", r.status.to_string().to_owned()),
+        ("pre_status", r.pre_status.to_string().to_owned()),
+        ("message", r.message.clone()),
+        ("rtt", r.round_trip_time.as_millis().to_string()),
+        ("time", time.to_rfc3339()),
+        ("timestamp", time.timestamp().to_string()),
+    ])
+}
+
+/// The SLA report is rendered with the same template, the report text is the `message`.
+fn sla_fields(title: &str, report: String) -> HashMap<&'static str, String> {
+    let time = chrono::Utc::now();
+    HashMap::from([
+        ("title", title.to_string()),
+        ("name", title.to_string()),
+        ("endpoint", String::new()),
+        ("status", String::new()),
+        ("pre_status", String::new()),
+        ("message", report),
+        ("rtt", "0".to_string()),
+        ("time", time.to_rfc3339()),
+        ("timestamp", time.timestamp().to_string()),
+    ])
+}
+
+/// The headers of the signature, the `sha256=` HMAC of `{timestamp}.{body}`.
+fn signature_headers(secret: &str, body: &str) -> [(&'static str, String); 2] {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+        .to_string();
+    let sign = global::hmac_sha256(
+        secret.as_bytes(),
+        format!("{}.{}", timestamp, body).as_bytes(),
+    );
+    [
+        ("X-EaseProbe-Timestamp", timestamp),
+        (
+            "X-EaseProbe-Signature",
+            format!("sha256={}", hex::encode(sign)),
+        ),
+    ]
+}
+
+#[async_trait]
+impl Notifier for WebhookConfig {
+    fn kind(&self) -> &str {
+        self.default.kind()
+    }
+
+    fn name(&self) -> &str {
+        self.default.name()
+    }
+
+    fn channels(&self) -> Vec<String> {
+        self.default.channels()
+    }
+
+    fn config(&mut self, g_conf: &NotifierSetting) -> Result<()> {
+        self.default.kind = "webhook".to_string();
+        self.default.config(g_conf)?;
+
+        if self.url.is_empty() {
+            bail!("[{} / {}] url is required", self.kind(), self.name());
+        }
+        let method: Method = self
+            .method
+            .to_uppercase()
+            .parse()
+            .with_context(|| format!("invalid method {}", self.method))?;
+        check_template(&self.body).map_err(|e| {
+            log::error!("[{} / {}] {}", self.kind(), self.name(), e);
+            e
+        })?;
+
+        let client = Client::builder().timeout(self.default.timeout).build()?;
+        let url = self.url.clone();
+        let mut headers = self.headers.clone();
+        if !headers
+            .keys()
+            .any(|k| k.eq_ignore_ascii_case("content-type"))
+        {
+            headers.insert(CONTENT_TYPE.to_string(), "application/json".to_string());
+        }
+        let secret = self.secret.clone();
+        self.default.send_func = Some(Box::new(move |notification: Notification| {
+            let mut request = client.request(method.clone(), &url);
+            for (k, v) in &headers {
+                request = request.header(k, v);
+            }
+            if !secret.is_empty() {
+                for (k, v) in signature_headers(&secret, &notification.body) {
+                    request = request.header(k, v);
+                }
+            }
+            let request = request.body(notification.body);
+            Box::pin(async move { send_http("Webhook", request).await.map(|_| ()) })
+        }));
+
+        Ok(())
+    }
+
+    async fn notify(&self, result: Arc<ProbeResult>) {
+        if self.default.dry {
+            self.dry_notify(result);
+            return;
+        }
+        let notification = Notification {
+            title: result.title(),
+            body: render(&self.body, &result_fields(&result)),
+            result: Some(result),
+            delivered: Default::default(),
+        };
+        self.default
+            .send_with_retry(notification, "Notification")
+            .await;
+    }
+
+    async fn notify_stat(&self, probers: Vec<Arc<RwLock<dyn Prober>>>) {
+        if self.default.dry {
+            self.dry_notify_stat(probers);
+            return;
+        }
+        let title = "Overall SLA Report";
+        let report = (format_funcs(Format::Unknown).stat_fn)(probers);
+        let notification = Notification {
+            title: title.to_string(),
+            body: render(&self.body, &sla_fields(title, report)),
+            result: None,
+            delivered: Default::default(),
+        };
+        self.default.send_with_retry(notification, "SLA").await;
+    }
+
+    fn dry_notify(&self, res: Arc<ProbeResult>) {
+        log::info!(
+            "[{} / {} / dry_notify] - {}",
+            self.kind(),
+            self.name(),
+            render(&self.body, &result_fields(&res))
+        );
+    }
+
+    fn dry_notify_stat(&self, probers: Vec<Arc<RwLock<dyn Prober>>>) {
+        self.default.dry_notify_stat(probers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{notify::testing::CaptureServer, Status};
+
+    use super::*;
+
+    fn webhook(yaml: &str) -> WebhookConfig {
+        let mut w: WebhookConfig = serde_yaml::from_str(yaml).unwrap();
+        w.config(&NotifierSetting::default()).unwrap();
+        w
+    }
+
+    fn result() -> ProbeResult {
+        ProbeResult {
+            name: "Web \"prod\"".to_string(),
+            endpoint: "https://example.com".to_string(),
+            status: Status::Down,
+            pre_status: Status::Up,
+            message: "HTTP Status Code is 500".to_string(),
+            round_trip_time: std::time::Duration::from_millis(42),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_webhook_default() {
+        let server = CaptureServer::start(200, "").await;
+        let w = webhook(&format!(
+            "name: hook\nurl: {}/incident\nsecret: s3cr3t",
+            server.url
+        ));
+        w.notify(Arc::new(result())).await;
+
+        let reqs = server.requests().await;
+        assert_eq!(reqs[0].method, "POST");
+        assert_eq!(reqs[0].path, "/incident");
+        assert_eq!(reqs[0].headers["content-type"], "application/json");
+
+        let body: serde_json::Value = serde_json::from_str(&reqs[0].body).unwrap();
+        assert_eq!(body["name"], "Web \"prod\"");
+        assert_eq!(body["status"], "down");
+        assert_eq!(body["pre_status"], "up");
+        assert_eq!(body["rtt"], 42);
+
+        let timestamp = &reqs[0].headers["x-easeprobe-timestamp"];
+        let sign = global::hmac_sha256(
+            b"s3cr3t",
+            format!("{}.{}", timestamp, reqs[0].body).as_bytes(),
+        );
+        assert_eq!(
+            reqs[0].headers["x-easeprobe-signature"],
+            format!("sha256={}", hex::encode(sign))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_webhook_template() {
+        let server = CaptureServer::start(200, "").await;
+        let w = webhook(&format!(
+            "name: hook\nurl: {}\nmethod: put\nheaders:\n  Content-Type: text/plain\n  X-Team: sre\nbody: '{{{{name}}}} is {{{{ status }}}} ({{{{rtt}}}}ms)'",
+            server.url
+        ));
+        w.notify(Arc::new(result())).await;
+
+        let reqs = server.requests().await;
+        assert_eq!(reqs[0].method, "PUT");
+        assert_eq!(reqs[0].headers["content-type"], "text/plain");
+        assert_eq!(reqs[0].headers["x-team"], "sre");
+        assert!(!reqs[0].headers.contains_key("x-easeprobe-signature"));
+        assert_eq!(reqs[0].body, "Web \"prod\" is down (42ms)");
+    }
+
+    #[tokio::test]
+    async fn test_webhook_sla() {
+        let server = CaptureServer::start(200, "").await;
+        let w = webhook(&format!("name: hook\nurl: {}", server.url));
+        w.notify_stat(vec![]).await;
+
+        let reqs = server.requests().await;
+        let body: serde_json::Value = serde_json::from_str(&reqs[0].body).unwrap();
+        assert_eq!(body["title"], "Overall SLA Report");
+        assert!(body["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("[Overall SLA Report]"));
+    }
+
+    #[test]
+    fn test_check_template() {
+        assert!(check_template(&default_template()).is_ok());
+        assert!(check_template("{{unknown}}").is_err());
+        assert!(check_template("{{name|yaml}}").is_err());
+
+        let mut w: WebhookConfig =
+            serde_yaml::from_str("name: hook\nurl: http://x\nmethod: 'BAD METHOD'").unwrap();
+        assert!(w.config(&NotifierSetting::default()).is_err());
+    }
+}
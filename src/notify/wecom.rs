@@ -0,0 +1,129 @@
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::RwLock;
+
+use crate::{
+    global::NotifierSetting,
+    probe::{ProbeResult, Prober},
+    Format,
+};
+
+use super::{check_error_code, send_http, DefaultNotifier, Notification, Notifier};
+
+/// The WeCom notifier sends the markdown messages to a group robot webhook.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WeComConfig {
+    #[serde(flatten)]
+    default: DefaultNotifier,
+    webhook: String,
+}
+
+#[async_trait]
+impl Notifier for WeComConfig {
+    fn kind(&self) -> &str {
+        self.default.kind()
+    }
+
+    fn name(&self) -> &str {
+        self.default.name()
+    }
+
+    fn channels(&self) -> Vec<String> {
+        self.default.channels()
+    }
+
+    fn config(&mut self, g_conf: &NotifierSetting) -> Result<()> {
+        self.default.kind = "wecom".to_string();
+        self.default.format = Format::Markdown;
+        self.default.config(g_conf)?;
+
+        if self.webhook.is_empty() {
+            bail!("[{} / {}] webhook is required", self.kind(), self.name());
+        }
+
+        let client = Client::builder().timeout(self.default.timeout).build()?;
+        let webhook = self.webhook.clone();
+        self.default.send_func = Some(Box::new(move |notification: Notification| {
+            let request = client.post(&webhook).json(&json!({
+                "msgtype": "markdown",
+                "markdown": { "content": notification.body },
+            }));
+            Box::pin(async move {
+                let body = send_http("WeCom", request).await?;
+                check_error_code("WeCom", &body, "errcode")
+            })
+        }));
+
+        Ok(())
+    }
+
+    async fn notify(&self, result: Arc<ProbeResult>) {
+        self.default.notify(result).await
+    }
+
+    async fn notify_stat(&self, probers: Vec<Arc<RwLock<dyn Prober>>>) {
+        self.default.notify_stat(probers).await
+    }
+
+    fn dry_notify(&self, res: Arc<ProbeResult>) {
+        self.default.dry_notify(res)
+    }
+
+    fn dry_notify_stat(&self, probers: Vec<Arc<RwLock<dyn Prober>>>) {
+        self.default.dry_notify_stat(probers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{notify::testing::CaptureServer, Status};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_wecom() {
+        let server = CaptureServer::start(200, r#"{"errcode":0,"errmsg":"ok"}"#).await;
+        let mut w: WeComConfig = serde_yaml::from_str(&format!(
+            "name: wecom\nwebhook: {}/cgi-bin/webhook/send?key=abc",
+            server.url
+        ))
+        .unwrap();
+        w.config(&NotifierSetting::default()).unwrap();
+
+        let result = ProbeResult {
+            name: "Web".to_string(),
+            status: Status::Up,
+            ..Default::default()
+        };
+        w.notify(Arc::new(result)).await;
+
+        let reqs = server.requests().await;
+        assert_eq!(reqs.len(), 1);
+        assert_eq!(reqs[0].path, "/cgi-bin/webhook/send?key=abc");
+        let body: serde_json::Value = serde_json::from_str(&reqs[0].body).unwrap();
+        assert_eq!(body["msgtype"], "markdown");
+        assert!(body["markdown"]["content"]
+            .as_str()
+            .unwrap()
+            .starts_with("**Web Recovery"));
+    }
+
+    #[tokio::test]
+    async fn test_wecom_error() {
+        let server = CaptureServer::start(200, r#"{"errcode":93000,"errmsg":"invalid"}"#).await;
+        let mut w: WeComConfig = serde_yaml::from_str(&format!(
+            "name: wecom\nwebhook: {}\nretry:\n  times: 2\n  interval: {{ secs: 0, nanos: 1000 }}",
+            server.url
+        ))
+        .unwrap();
+        w.config(&NotifierSetting::default()).unwrap();
+
+        w.notify(Arc::new(ProbeResult::default())).await;
+        assert_eq!(server.requests().await.len(), 2);
+    }
+}
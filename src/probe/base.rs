@@ -9,9 +9,7 @@ use crate::{
     DEFAULT_CHANNEL_NAME,
 };
 
-use super::{ProbeBehavior, ProbeResult, Prober};
-
-pub type ProbeFuncType = fn() -> Result<(bool, String)>;
+use super::{ProbeBehavior, ProbeResult, Prober, Status};
 
 #[derive(Default, Debug, Serialize, Deserialize)]
 pub struct DefaultProber<B: ProbeBehavior> {
@@ -22,9 +20,9 @@ pub struct DefaultProber<B: ProbeBehavior> {
     pub name: String,
     #[serde(default)]
     pub channels: Vec<String>,
-    #[serde(default = "default_timeout")]
+    #[serde(default = "default_timeout", with = "humantime_serde")]
     pub timeout: Duration,
-    #[serde(default = "default_interval")]
+    #[serde(default = "default_interval", with = "humantime_serde")]
     pub interval: Duration,
     #[serde(flatten)]
     pub behavior: B,
@@ -45,6 +43,31 @@ fn default_interval() -> Duration {
 }
 
 impl<B: ProbeBehavior> DefaultProber<B> {
+    /// Changes the status once the same results reach the threshold,
+    /// the first result changes it from `Init` immediately.
+    fn change_status(&mut self, stat: bool, now: SystemTime) {
+        let r = &mut self.result;
+        r.pre_status = r.status;
+        let (status, threshold) = if stat {
+            (Status::Up, self.threshold.success)
+        } else {
+            (Status::Down, self.threshold.failure)
+        };
+        if r.status == status
+            || (r.status != Status::Init && (r.stat.status_counter.status_count as i32) < threshold)
+        {
+            return;
+        }
+
+        r.status = status;
+        r.stat.last_change = now;
+        if status == Status::Down {
+            r.latest_downtime = now;
+        } else if r.pre_status == Status::Down {
+            r.recovery_time = now.duration_since(r.latest_downtime).unwrap_or_default();
+        }
+    }
+
     fn log_title(&self) -> String {
         if self.tag.is_empty() {
             format!("[{} / {} / {}]", self.kind, self.tag, self.name)
@@ -82,16 +105,31 @@ impl<B: ProbeBehavior + Send + Sync> Prober for DefaultProber<B> {
 
     async fn probe(&mut self) -> ProbeResult {
         let now = SystemTime::now();
+        // the time since the last probe is counted as the uptime or downtime, it's
+        // capped by the interval, e.g. the process was stopped, and the first probe counts nothing
+        let elapsed = if self.result.stat.total == 0 {
+            Duration::ZERO
+        } else {
+            now.duration_since(self.result.start_time)
+                .unwrap_or_default()
+                .min(self.interval)
+        };
 
         self.result.start_time = now;
         self.result.start_timestamp = now.duration_since(UNIX_EPOCH).unwrap().as_millis();
 
-        let (stat, msg) = self.behavior.do_probe().await.unwrap();
+        let (stat, msg) = match tokio::time::timeout(self.timeout, self.behavior.do_probe()).await {
+            Ok(Ok(res)) => res,
+            Ok(Err(err)) => (false, err.to_string()),
+            Err(_) => (false, format!("Timeout after {:?}", self.timeout)),
+        };
         self.result.round_trip_time = now.elapsed().unwrap();
         self.result
             .stat
             .status_counter
             .append_status(stat, msg.clone());
+        self.change_status(stat, now);
+        self.result.stat.record(self.result.status, elapsed);
         let title = self.result.status.title();
 
         if self.tag.is_empty() {
@@ -110,6 +148,13 @@ impl<B: ProbeBehavior + Send + Sync> Prober for DefaultProber<B> {
         if self.channels.is_empty() {
             self.channels.push(DEFAULT_CHANNEL_NAME.to_string());
         }
+        self.result.name = self.name.clone();
+        self.result.kind = self.kind.clone();
+        self.result.status = Status::Init;
+        self.result
+            .stat
+            .status_counter
+            .set_max_len(self.threshold.failure.max(self.threshold.success).max(1) as usize);
         log::info!("Probe {} base options are configured!", self.log_title());
         Ok(())
     }
@@ -117,6 +162,59 @@ impl<B: ProbeBehavior + Send + Sync> Prober for DefaultProber<B> {
 
 #[cfg(test)]
 mod tests {
-    #[test]
-    fn test_base() {}
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    use super::*;
+
+    #[derive(Default, Debug, Serialize, Deserialize)]
+    struct Toggle {
+        #[serde(skip)]
+        up: Arc<AtomicBool>,
+    }
+
+    #[async_trait]
+    impl ProbeBehavior for Toggle {
+        async fn do_probe(&self) -> Result<(bool, String)> {
+            Ok((self.up.load(Ordering::SeqCst), String::new()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_base() {
+        let mut p: DefaultProber<Toggle> =
+            serde_yaml::from_str("name: toggle\ninterval: 10s\nfailure: 2").unwrap();
+        p.kind = "toggle".to_string();
+        p.config(&ProbeSettings::default()).await.unwrap();
+        assert_eq!(p.result.status, Status::Init);
+        let up = p.behavior.up.clone();
+
+        // probes as if the last probe was `secs` ago
+        async fn probe(p: &mut DefaultProber<Toggle>, secs: u64) -> ProbeResult {
+            p.result.start_time -= Duration::from_secs(secs);
+            p.probe().await
+        }
+
+        up.store(true, Ordering::SeqCst);
+        assert_eq!(p.probe().await.status, Status::Up);
+
+        // the failure threshold is 2
+        up.store(false, Ordering::SeqCst);
+        assert_eq!(probe(&mut p, 4).await.status, Status::Up);
+        let r = probe(&mut p, 20).await;
+        assert_eq!((r.status, r.pre_status), (Status::Down, Status::Up));
+        assert_eq!(r.stat.last_change, r.start_time);
+
+        up.store(true, Ordering::SeqCst);
+        let r = probe(&mut p, 5).await;
+        assert_eq!((r.status, r.pre_status), (Status::Up, Status::Down));
+        assert_eq!(r.stat.total, 4);
+        assert_eq!(r.stat.status[&Status::Up], 3);
+        assert_eq!(r.stat.status[&Status::Down], 1);
+        // the first probe counts nothing, and the 20s is capped by the interval
+        assert_eq!(r.stat.uptime.as_secs(), 4 + 5);
+        assert_eq!(r.stat.downtime.as_secs(), 10);
+    }
 }
@@ -0,0 +1,91 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use rskafka::client::ClientBuilder;
+
+use super::{ClientProbeBehavior, NativeClient};
+use crate::TLSConfig;
+
+const DEFAULT_PORT: u16 = 9092;
+
+/// The Kafka native client
+#[derive(Debug)]
+pub struct Kafka {
+    broker: String,
+    tls: Option<TLSConfig>,
+}
+
+impl Kafka {
+    pub fn new(conf: &ClientProbeBehavior) -> Result<Self> {
+        let (host, port) = conf.host_port(DEFAULT_PORT)?;
+        let tls = if conf.tls.is_enabled() {
+            // fail fast on the bad certificates
+            conf.tls.rustls_config()?;
+            Some(conf.tls.clone())
+        } else {
+            None
+        };
+
+        Ok(Self {
+            broker: format!("{}:{}", host, port),
+            tls,
+        })
+    }
+}
+
+#[async_trait]
+impl NativeClient for Kafka {
+    fn kind(&self) -> &str {
+        "Kafka"
+    }
+
+    async fn probe(&self) -> Result<(bool, String)> {
+        let mut builder = ClientBuilder::new(vec![self.broker.clone()]);
+        if let Some(tls) = &self.tls {
+            builder = builder.tls_config(tls.rustls_config()?);
+        }
+
+        // building the client fetches the cluster metadata from the bootstrap broker
+        let client = builder.build().await?;
+        let topics = client.list_topics().await?;
+
+        Ok((true, format!("Kafka: Total {} topics", topics.len())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn conf(yaml: &str) -> ClientProbeBehavior {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_config() {
+        let k = Kafka::new(&conf("driver: kafka\nhost: localhost")).unwrap();
+        assert_eq!(k.broker, "localhost:9092");
+        assert!(k.tls.is_none());
+
+        let k = Kafka::new(&conf("driver: kafka\nhost: broker:19092\ninsecure: true")).unwrap();
+        assert_eq!(k.broker, "broker:19092");
+        assert!(k.tls.is_some());
+
+        assert!(Kafka::new(&conf("driver: kafka\nhost: broker:abc")).is_err());
+        assert!(Kafka::new(&conf("driver: kafka\nhost: localhost\nca: /no/such/ca.pem")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unreachable() {
+        // the port is closed once the listener is dropped
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let k = Kafka::new(&conf(&format!("driver: kafka\nhost: {}", addr))).unwrap();
+        // the client retries the bootstrap broker until the timeout of the probe
+        let result = tokio::time::timeout(Duration::from_secs(1), k.probe()).await;
+        assert!(!matches!(result, Ok(Ok(_))));
+    }
+}
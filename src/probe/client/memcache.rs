@@ -0,0 +1,189 @@
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::TcpStream,
+};
+
+use super::{ClientProbeBehavior, NativeClient};
+
+const DEFAULT_PORT: u16 = 11211;
+const MAX_KEY_LEN: usize = 250;
+
+/// The Memcache native client, which talks the memcached text protocol.
+#[derive(Debug)]
+pub struct Memcache {
+    addr: String,
+    data: Vec<(String, String)>,
+}
+
+impl Memcache {
+    pub fn new(conf: &ClientProbeBehavior) -> Result<Self> {
+        let (host, port) = conf.host_port(DEFAULT_PORT)?;
+        // the probe must not fall back to the plaintext silently
+        if conf.tls.is_enabled() {
+            bail!("TLS is not supported by the memcache client");
+        }
+
+        // the namespaced keys like `namespace:key` are plain keys for memcached
+        let mut data: Vec<(String, String)> = conf
+            .data
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        data.sort();
+        for (k, _) in &data {
+            if k.is_empty()
+                || k.len() > MAX_KEY_LEN
+                || k.chars().any(|c| c.is_whitespace() || c.is_control())
+            {
+                bail!("invalid memcache key `{}`", k);
+            }
+        }
+
+        Ok(Self {
+            addr: format!("{}:{}", host, port),
+            data,
+        })
+    }
+
+    async fn read_line(stream: &mut BufReader<TcpStream>) -> Result<String> {
+        let mut line = String::new();
+        if stream.read_line(&mut line).await? == 0 {
+            bail!("connection closed by the memcache server");
+        }
+        Ok(line.trim_end().to_string())
+    }
+
+    /// Sends `get <key>`, returns `None` if the key does not exist.
+    async fn get(stream: &mut BufReader<TcpStream>, key: &str) -> Result<Option<String>> {
+        stream
+            .get_mut()
+            .write_all(format!("get {}\r\n", key).as_bytes())
+            .await?;
+
+        let line = Self::read_line(stream).await?;
+        if line == "END" {
+            return Ok(None);
+        }
+
+        // VALUE <key> <flags> <bytes>
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 4 || fields[0] != "VALUE" {
+            bail!("unexpected memcache response: {}", line);
+        }
+        let len: usize = fields[3].parse()?;
+        let mut value = vec![0u8; len + 2];
+        stream.read_exact(&mut value).await?;
+        value.truncate(len);
+
+        let end = Self::read_line(stream).await?;
+        if end != "END" {
+            bail!("unexpected memcache response: {}", end);
+        }
+        Ok(Some(String::from_utf8_lossy(&value).to_string()))
+    }
+}
+
+#[async_trait]
+impl NativeClient for Memcache {
+    fn kind(&self) -> &str {
+        "Memcache"
+    }
+
+    async fn probe(&self) -> Result<(bool, String)> {
+        let mut stream = BufReader::new(TcpStream::connect(&self.addr).await?);
+
+        if self.data.is_empty() {
+            stream.get_mut().write_all(b"version\r\n").await?;
+            let line = Self::read_line(&mut stream).await?;
+            if !line.starts_with("VERSION") {
+                bail!("unexpected memcache response: {}", line);
+            }
+            return Ok((true, "Check Memcache Server Successfully!".to_string()));
+        }
+
+        for (key, expected) in &self.data {
+            match Self::get(&mut stream, key).await? {
+                None => return Ok((false, format!("Memcache key `{}` not found", key))),
+                Some(v) if &v != expected => {
+                    return Ok((
+                        false,
+                        format!(
+                            "Value mismatched for key `{}` - expected `{}`, got `{}`",
+                            key, expected, v
+                        ),
+                    ))
+                }
+                Some(_) => {}
+            }
+        }
+
+        Ok((true, "Check Memcache Data Successfully!".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    /// A tiny memcached which only knows `ns:name` = `EaseProbe`
+    async fn serve() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut stream = BufReader::new(stream);
+            let mut line = String::new();
+            while stream.read_line(&mut line).await.unwrap() > 0 {
+                let resp = match line.trim_end() {
+                    "get ns:name" => "VALUE ns:name 0 9\r\nEaseProbe\r\nEND\r\n",
+                    _ => "END\r\n",
+                };
+                stream.get_mut().write_all(resp.as_bytes()).await.unwrap();
+                line.clear();
+            }
+        });
+        addr
+    }
+
+    fn client(addr: &str, data: &[(&str, &str)]) -> Memcache {
+        let mut conf: ClientProbeBehavior =
+            serde_yaml::from_str(&format!("driver: memcache\nhost: {}", addr)).unwrap();
+        conf.data = data
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        Memcache::new(&conf).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_memcache() {
+        let addr = serve().await;
+        let (ok, msg) = client(&addr, &[("ns:name", "EaseProbe")])
+            .probe()
+            .await
+            .unwrap();
+        assert!(ok, "{}", msg);
+
+        let addr = serve().await;
+        let (ok, msg) = client(&addr, &[("ns:name", "EaseProbe"), ("ns:other", "x")])
+            .probe()
+            .await
+            .unwrap();
+        assert!(!ok);
+        assert!(msg.contains("`ns:other`"));
+
+        let mut conf: ClientProbeBehavior =
+            serde_yaml::from_str("driver: memcache\nhost: localhost").unwrap();
+        conf.data.insert("bad key".to_string(), "x".to_string());
+        assert!(Memcache::new(&conf).is_err());
+
+        let conf: ClientProbeBehavior =
+            serde_yaml::from_str("driver: memcache\nhost: localhost\ninsecure: true").unwrap();
+        let err = Memcache::new(&conf).unwrap_err();
+        assert!(err.to_string().contains("TLS is not supported"), "{}", err);
+    }
+}
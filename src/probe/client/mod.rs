@@ -0,0 +1,297 @@
+use std::{collections::HashMap, fmt::Debug, time::Duration};
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use serde::{de::Error, Deserialize, Deserializer, Serialize};
+
+use crate::{ProbeSettings, TLSConfig};
+
+use super::{DefaultProber, ProbeBehavior, ProbeResult, Prober};
+
+#[cfg(any(feature = "mysql", feature = "postgres"))]
+mod sql;
+#[cfg(any(feature = "mysql", feature = "postgres"))]
+pub use sql::*;
+#[cfg(feature = "mysql")]
+mod mysql;
+#[cfg(feature = "mysql")]
+pub use mysql::*;
+#[cfg(feature = "postgres")]
+mod postgres;
+#[cfg(feature = "postgres")]
+pub use postgres::*;
+#[cfg(feature = "mongo")]
+mod mongo;
+#[cfg(feature = "mongo")]
+pub use mongo::*;
+#[cfg(feature = "memcache")]
+mod memcache;
+#[cfg(feature = "memcache")]
+pub use memcache::*;
+#[cfg(feature = "kafka")]
+mod kafka;
+#[cfg(feature = "kafka")]
+pub use kafka::*;
+#[cfg(feature = "zookeeper")]
+mod zookeeper;
+#[cfg(feature = "zookeeper")]
+pub use zookeeper::*;
+
+/// The native client driver types, each driver is enabled by the cargo feature of the same name.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DriverType {
+    #[default]
+    Unknown,
+    MySQL,
+    #[serde(alias = "postgresql")]
+    Postgres,
+    #[serde(alias = "mongodb")]
+    Mongo,
+    Memcache,
+    Kafka,
+    Zookeeper,
+}
+
+impl DriverType {
+    pub fn to_string(self) -> &'static str {
+        match self {
+            DriverType::Unknown => "unknown",
+            DriverType::MySQL => "mysql",
+            DriverType::Postgres => "postgres",
+            DriverType::Mongo => "mongo",
+            DriverType::Memcache => "memcache",
+            DriverType::Kafka => "kafka",
+            DriverType::Zookeeper => "zookeeper",
+        }
+    }
+}
+
+/// The native client of a driver, which is created by the client prober.
+#[async_trait]
+pub trait NativeClient: Send + Sync + Debug {
+    fn kind(&self) -> &str;
+    async fn probe(&self) -> Result<(bool, String)>;
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ClientProber {
+    #[serde(flatten)]
+    pub default_prober: DefaultProber<ClientProbeBehavior>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ClientProbeBehavior {
+    pub driver: DriverType,
+    pub host: String,
+    #[serde(default)]
+    pub username: String,
+    #[serde(default)]
+    pub password: String,
+    #[serde(default, deserialize_with = "deserialize_data")]
+    pub data: HashMap<String, String>,
+    /// The TLS of the driver, Memcache doesn't support it. ZooKeeper still verifies the
+    /// certificate chain with `insecure`, only the hostname is not verified.
+    #[serde(flatten)]
+    pub tls: TLSConfig,
+    #[serde(skip)]
+    pub client: Option<Box<dyn NativeClient>>,
+}
+
+/// The expected values could be any YAML scalar, e.g. `"test:employee:age:id:2": 45`
+fn deserialize_data<'de, D>(deserializer: D) -> Result<HashMap<String, String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw = HashMap::<String, serde_yaml::Value>::deserialize(deserializer)?;
+    raw.into_iter()
+        .map(|(k, v)| match v {
+            serde_yaml::Value::String(s) => Ok((k, s)),
+            serde_yaml::Value::Number(n) => Ok((k, n.to_string())),
+            serde_yaml::Value::Bool(b) => Ok((k, b.to_string())),
+            _ => Err(D::Error::custom(format!(
+                "the expected value of data key `{}` must be a scalar",
+                k
+            ))),
+        })
+        .collect()
+}
+
+#[cfg(any(
+    feature = "mysql",
+    feature = "postgres",
+    feature = "mongo",
+    feature = "memcache",
+    feature = "kafka",
+    feature = "zookeeper"
+))]
+impl ClientProbeBehavior {
+    /// Splits the `host` into the host name and the port, using `default_port` if it is absent.
+    pub fn host_port(&self, default_port: u16) -> Result<(String, u16)> {
+        match self.host.rsplit_once(':') {
+            Some((host, port)) if !host.is_empty() => match port.parse::<u16>() {
+                Ok(port) => Ok((host.to_string(), port)),
+                Err(err) => bail!("invalid port in host {} - {}", self.host, err),
+            },
+            Some(_) => bail!("invalid host {}", self.host),
+            None if self.host.is_empty() => bail!("host is required"),
+            None => Ok((self.host.clone(), default_port)),
+        }
+    }
+}
+
+#[async_trait]
+impl ProbeBehavior for ClientProbeBehavior {
+    async fn do_probe(&self) -> Result<(bool, String)> {
+        if let Some(client) = &self.client {
+            return client.probe().await;
+        }
+        bail!("client is not configured")
+    }
+}
+
+#[async_trait]
+impl Prober for ClientProber {
+    fn kind(&self) -> &str {
+        &self.default_prober.kind
+    }
+
+    fn name(&self) -> &str {
+        &self.default_prober.name
+    }
+
+    fn channels(&self) -> Vec<String> {
+        self.default_prober.channels.clone()
+    }
+
+    fn timeout(&self) -> &Duration {
+        &self.default_prober.timeout
+    }
+
+    fn interval(&self) -> &Duration {
+        &self.default_prober.interval
+    }
+
+    fn result(&mut self) -> &mut ProbeResult {
+        &mut self.default_prober.result
+    }
+
+    async fn probe(&mut self) -> ProbeResult {
+        self.default_prober.probe().await
+    }
+
+    async fn config(&mut self, setting: &ProbeSettings) -> Result<()> {
+        let driver = self.default_prober.behavior.driver;
+        self.default_prober.kind = "client".to_string();
+        self.default_prober.tag = driver.to_string().to_string();
+        self.default_prober.result.endpoint = self.default_prober.behavior.host.clone();
+        self.default_prober.config(setting).await?;
+
+        let b = &self.default_prober.behavior;
+        if let Err(err) = b.tls.check() {
+            log::error!(
+                "[{} / {}] TLS configuration is not valid - {}",
+                self.kind(),
+                self.name(),
+                err,
+            );
+            bail!(err)
+        }
+
+        let client: Option<Box<dyn NativeClient>> = match driver {
+            #[cfg(feature = "mysql")]
+            DriverType::MySQL => Some(Box::new(MySQL::new(b)?)),
+            #[cfg(feature = "postgres")]
+            DriverType::Postgres => Some(Box::new(Postgres::new(b)?)),
+            #[cfg(feature = "mongo")]
+            DriverType::Mongo => Some(Box::new(Mongo::new(b)?)),
+            #[cfg(feature = "memcache")]
+            DriverType::Memcache => Some(Box::new(Memcache::new(b)?)),
+            #[cfg(feature = "kafka")]
+            DriverType::Kafka => Some(Box::new(Kafka::new(b)?)),
+            #[cfg(feature = "zookeeper")]
+            DriverType::Zookeeper => Some(Box::new(Zookeeper::new(b)?)),
+            _ => None,
+        };
+        let Some(client) = client else {
+            log::error!(
+                "[{} / {}] driver {} is not supported or not enabled in this build",
+                self.kind(),
+                self.name(),
+                driver.to_string(),
+            );
+            bail!("unsupported driver {}", driver.to_string())
+        };
+
+        log::debug!(
+            "[{} / {}] {} client is configured for {}",
+            self.kind(),
+            self.name(),
+            client.kind(),
+            b.host,
+        );
+        self.default_prober.behavior.client = Some(client);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn behavior(yaml: &str) -> ClientProbeBehavior {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_client_conf() {
+        let b = behavior(
+            r#"
+driver: mysql
+host: localhost:3307
+username: root
+password: pass
+data:
+  "test:product:name:id:1": EaseProbe
+  "test:employee:age:id:2": 45
+ca: /path/to/ca
+"#,
+        );
+        assert_eq!(b.driver, DriverType::MySQL);
+        assert_eq!(b.data.get("test:employee:age:id:2").unwrap(), "45");
+        assert_eq!(b.tls.ca, "/path/to/ca");
+        assert!(b.tls.is_enabled());
+
+        let b = behavior("driver: postgresql\nhost: db.local");
+        assert_eq!(b.driver, DriverType::Postgres);
+        assert!(!b.tls.is_enabled());
+    }
+
+    #[cfg(any(
+        feature = "mysql",
+        feature = "postgres",
+        feature = "mongo",
+        feature = "memcache",
+        feature = "kafka",
+        feature = "zookeeper"
+    ))]
+    #[test]
+    fn test_host_port() {
+        let b = behavior("driver: mysql\nhost: localhost:3307");
+        assert_eq!(b.host_port(3306).unwrap(), ("localhost".to_string(), 3307));
+        let b = behavior("driver: postgres\nhost: db.local");
+        assert_eq!(b.host_port(5432).unwrap(), ("db.local".to_string(), 5432));
+        let b = behavior("driver: postgres\nhost: db.local:abc");
+        assert!(b.host_port(5432).is_err());
+        let b = behavior("driver: postgres\nhost: :5432");
+        assert!(b.host_port(5432).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_driver() {
+        let mut p: ClientProber =
+            serde_yaml::from_str("name: db\ndriver: unknown\nhost: localhost").unwrap();
+        assert!(p.config(&ProbeSettings::default()).await.is_err());
+    }
+}
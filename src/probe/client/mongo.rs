@@ -0,0 +1,250 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use mongodb::{
+    bson::{doc, Document},
+    options::{ClientOptions, Credential, ServerAddress, Tls, TlsOptions},
+    Client,
+};
+
+use super::{ClientProbeBehavior, NativeClient};
+
+const DEFAULT_PORT: u16 = 27017;
+
+/// A `find` check parsed from the data `"database:collection": "{JSON filter}"`
+#[derive(Debug)]
+struct FindCheck {
+    key: String,
+    database: String,
+    collection: String,
+    filter: Document,
+}
+
+/// The client cert and key in one PEM file, which is what the MongoDB driver reads.
+/// It's removed when the client is dropped.
+#[derive(Debug)]
+struct CertKeyFile(PathBuf);
+
+impl CertKeyFile {
+    fn new(cert: &str, key: &str) -> Result<Self> {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let mut pem =
+            fs::read(cert).with_context(|| format!("failed to read cert file {}", cert))?;
+        if !pem.ends_with(b"\n") {
+            pem.push(b'\n');
+        }
+        pem.extend(fs::read(key).with_context(|| format!("failed to read key file {}", key))?);
+
+        let path = std::env::temp_dir().join(format!(
+            "easeprobe-mongo-{}-{}.pem",
+            std::process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            // it has the private key
+            options.mode(0o600);
+        }
+        options.open(&path)?.write_all(&pem)?;
+        Ok(Self(path))
+    }
+}
+
+impl Drop for CertKeyFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// The MongoDB native client
+#[derive(Debug)]
+pub struct Mongo {
+    client: Client,
+    checks: Vec<FindCheck>,
+    /// The combined PEM file if the cert and the key are separate files
+    _cert_key: Option<CertKeyFile>,
+}
+
+impl Mongo {
+    pub fn new(conf: &ClientProbeBehavior) -> Result<Self> {
+        let (host, port) = conf.host_port(DEFAULT_PORT)?;
+        let mut options = ClientOptions::builder()
+            .hosts(vec![ServerAddress::Tcp {
+                host,
+                port: Some(port),
+            }])
+            .direct_connection(true)
+            .build();
+
+        if !conf.username.is_empty() {
+            options.credential = Some(
+                Credential::builder()
+                    .username(conf.username.clone())
+                    .password(conf.password.clone())
+                    .build(),
+            );
+        }
+
+        let tls = &conf.tls;
+        let mut cert_key = None;
+        if tls.is_enabled() {
+            let mut tls_options = TlsOptions::default();
+            if !tls.ca.is_empty() {
+                tls_options.ca_file_path = Some(tls.ca.clone().into());
+            }
+            if !tls.cert.is_empty() {
+                // the MongoDB driver reads the client certificate and key from one PEM file
+                tls_options.cert_key_file_path = Some(if tls.cert == tls.key {
+                    tls.cert.clone().into()
+                } else {
+                    let file = CertKeyFile::new(&tls.cert, &tls.key)?;
+                    let path = file.0.clone();
+                    cert_key = Some(file);
+                    path
+                });
+            }
+            if tls.insecure {
+                tls_options.allow_invalid_certificates = Some(true);
+            }
+            options.tls = Some(Tls::Enabled(tls_options));
+        }
+
+        let mut checks = conf
+            .data
+            .iter()
+            .map(|(k, v)| Self::parse(k, v))
+            .collect::<Result<Vec<_>>>()?;
+        checks.sort_by(|a, b| a.key.cmp(&b.key));
+
+        Ok(Self {
+            client: Client::with_options(options)?,
+            checks,
+            _cert_key: cert_key,
+        })
+    }
+
+    fn parse(key: &str, filter: &str) -> Result<FindCheck> {
+        let (database, collection) = match key.split_once(':') {
+            Some((d, c)) if !d.is_empty() && !c.is_empty() => (d, c),
+            _ => bail!(
+                "invalid data key `{}`, the format is `database:collection`",
+                key
+            ),
+        };
+        let filter: Document = serde_json::from_str(filter)
+            .with_context(|| format!("invalid JSON filter of data key `{}`", key))?;
+
+        Ok(FindCheck {
+            key: key.to_string(),
+            database: database.to_string(),
+            collection: collection.to_string(),
+            filter,
+        })
+    }
+}
+
+#[async_trait]
+impl NativeClient for Mongo {
+    fn kind(&self) -> &str {
+        "MongoDB"
+    }
+
+    async fn probe(&self) -> Result<(bool, String)> {
+        if self.checks.is_empty() {
+            self.client
+                .database("admin")
+                .run_command(doc! { "ping": 1 })
+                .await?;
+            return Ok((true, "Ping MongoDB Server Successfully!".to_string()));
+        }
+
+        for check in &self.checks {
+            let found = self
+                .client
+                .database(&check.database)
+                .collection::<Document>(&check.collection)
+                .find_one(check.filter.clone())
+                .await?;
+            if found.is_none() {
+                return Ok((
+                    false,
+                    format!(
+                        "No document found for key `{}` with filter {}",
+                        check.key, check.filter
+                    ),
+                ));
+            }
+        }
+
+        Ok((true, "Check MongoDB Data Successfully!".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let c = Mongo::parse("test:employee", r#"{"name":"Hao Chen"}"#).unwrap();
+        assert_eq!(c.database, "test");
+        assert_eq!(c.collection, "employee");
+        assert_eq!(c.filter, doc! { "name": "Hao Chen" });
+
+        assert!(Mongo::parse("test", "{}").is_err());
+        assert!(Mongo::parse("test:employee", "{name}").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cert_key() {
+        let dir = std::env::temp_dir();
+        let cert = dir.join(format!("easeprobe-mongo-test-{}.crt", std::process::id()));
+        let key = dir.join(format!("easeprobe-mongo-test-{}.key", std::process::id()));
+        let client = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let (cert_pem, key_pem) = (client.cert.pem(), client.signing_key.serialize_pem());
+        fs::write(&cert, &cert_pem).unwrap();
+        fs::write(&key, &key_pem).unwrap();
+
+        let conf: ClientProbeBehavior = serde_yaml::from_str(&format!(
+            "driver: mongo\nhost: localhost\ncert: {}\nkey: {}",
+            cert.display(),
+            key.display()
+        ))
+        .unwrap();
+        let mongo = Mongo::new(&conf).unwrap();
+        let combined = mongo._cert_key.as_ref().unwrap().0.clone();
+        assert_eq!(
+            fs::read_to_string(&combined).unwrap(),
+            format!("{}{}", cert_pem, key_pem)
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&combined).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        drop(mongo);
+        assert!(!combined.exists());
+
+        // the same file is used as it is
+        fs::write(&cert, format!("{}{}", cert_pem, key_pem)).unwrap();
+        let conf: ClientProbeBehavior = serde_yaml::from_str(&format!(
+            "driver: mongo\nhost: localhost\ncert: {0}\nkey: {0}",
+            cert.display()
+        ))
+        .unwrap();
+        assert!(Mongo::new(&conf).unwrap()._cert_key.is_none());
+
+        let _ = fs::remove_file(&cert);
+        let _ = fs::remove_file(&key);
+    }
+}
@@ -0,0 +1,115 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use sqlx::{
+    mysql::{MySqlConnectOptions, MySqlPool, MySqlPoolOptions, MySqlSslMode},
+    AssertSqlSafe, Connection,
+};
+
+use super::{ClientProbeBehavior, NativeClient, SqlCheck, SqlValue};
+
+const DEFAULT_PORT: u16 = 3306;
+
+/// The MySQL native client
+#[derive(Debug)]
+pub struct MySQL {
+    /// The connection is reused by the probes
+    pool: MySqlPool,
+    checks: Vec<SqlCheck>,
+}
+
+impl MySQL {
+    pub fn new(conf: &ClientProbeBehavior) -> Result<Self> {
+        let (host, port) = conf.host_port(DEFAULT_PORT)?;
+        let mut options = MySqlConnectOptions::new()
+            .host(&host)
+            .port(port)
+            .username(&conf.username)
+            .password(&conf.password);
+
+        let tls = &conf.tls;
+        if tls.is_enabled() {
+            options = options.ssl_mode(if tls.insecure {
+                MySqlSslMode::Required
+            } else {
+                MySqlSslMode::VerifyCa
+            });
+            if !tls.ca.is_empty() {
+                options = options.ssl_ca(&tls.ca);
+            }
+            if !tls.cert.is_empty() {
+                options = options.ssl_client_cert(&tls.cert).ssl_client_key(&tls.key);
+            }
+        }
+
+        Ok(Self {
+            // the pool connects on the first probe
+            pool: MySqlPoolOptions::new()
+                .max_connections(1)
+                .connect_lazy_with(options),
+            checks: SqlCheck::parse_all(&conf.data)?,
+        })
+    }
+
+    /// The identifiers are validated by `SqlCheck::parse()`, and the value is bound as a parameter.
+    fn query(check: &SqlCheck) -> String {
+        format!(
+            "SELECT CAST(`{}` AS CHAR) FROM `{}`.`{}` WHERE `{}` = ? LIMIT 1",
+            check.column, check.database, check.table, check.primary_key
+        )
+    }
+}
+
+#[async_trait]
+impl NativeClient for MySQL {
+    fn kind(&self) -> &str {
+        "MySQL"
+    }
+
+    async fn probe(&self) -> Result<(bool, String)> {
+        let mut conn = self.pool.acquire().await?;
+        conn.ping().await?;
+
+        for check in &self.checks {
+            let query = sqlx::query_scalar::<_, Option<String>>(AssertSqlSafe(Self::query(check)));
+            let query = match &check.value {
+                SqlValue::Int(v) => query.bind(*v),
+                SqlValue::Text(v) => query.bind(v.clone()),
+            };
+            let actual = query.fetch_optional(&mut *conn).await?;
+            let (ok, msg) = check.verify(actual);
+            if !ok {
+                return Ok((false, msg));
+            }
+        }
+
+        if self.checks.is_empty() {
+            Ok((true, "Ping MySQL Server Successfully!".to_string()))
+        } else {
+            Ok((true, "Check MySQL Data Successfully!".to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query() {
+        let c = SqlCheck::parse("test:product:name:id:1", "EaseProbe").unwrap();
+        assert_eq!(
+            MySQL::query(&c),
+            "SELECT CAST(`name` AS CHAR) FROM `test`.`product` WHERE `id` = ? LIMIT 1"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pool() {
+        let b: ClientProbeBehavior =
+            serde_yaml::from_str("driver: mysql\nhost: localhost").unwrap();
+        let mysql = MySQL::new(&b).unwrap();
+        // it's not connected until the first probe
+        assert_eq!(mysql.pool.size(), 0);
+        assert_eq!(mysql.pool.options().get_max_connections(), 1);
+    }
+}
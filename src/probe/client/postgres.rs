@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use sqlx::{
+    postgres::{PgConnectOptions, PgPool, PgPoolOptions, PgSslMode},
+    AssertSqlSafe, Connection,
+};
+
+use super::{ClientProbeBehavior, NativeClient, SqlCheck, SqlValue};
+
+const DEFAULT_PORT: u16 = 5432;
+const DEFAULT_DATABASE: &str = "postgres";
+
+/// The PostgreSQL native client
+#[derive(Debug)]
+pub struct Postgres {
+    /// The connection pools by the database, the connections are reused by the probes
+    pools: HashMap<String, PgPool>,
+    checks: Vec<SqlCheck>,
+}
+
+impl Postgres {
+    pub fn new(conf: &ClientProbeBehavior) -> Result<Self> {
+        let (host, port) = conf.host_port(DEFAULT_PORT)?;
+        let mut options = PgConnectOptions::new_without_pgpass()
+            .host(&host)
+            .port(port)
+            .username(&conf.username)
+            .password(&conf.password)
+            .database(DEFAULT_DATABASE)
+            .ssl_mode(PgSslMode::Disable);
+
+        let tls = &conf.tls;
+        if tls.is_enabled() {
+            options = options.ssl_mode(if tls.insecure {
+                PgSslMode::Require
+            } else {
+                PgSslMode::VerifyCa
+            });
+            if !tls.ca.is_empty() {
+                options = options.ssl_root_cert(&tls.ca);
+            }
+            if !tls.cert.is_empty() {
+                options = options.ssl_client_cert(&tls.cert).ssl_client_key(&tls.key);
+            }
+        }
+
+        let checks = SqlCheck::parse_all(&conf.data)?;
+        // the pools connect on the first probe
+        let pool = |database: &str| {
+            PgPoolOptions::new()
+                .max_connections(1)
+                .connect_lazy_with(options.clone().database(database))
+        };
+        let mut pools = HashMap::from([(DEFAULT_DATABASE.to_string(), pool(DEFAULT_DATABASE))]);
+        for check in &checks {
+            if !pools.contains_key(&check.database) {
+                pools.insert(check.database.clone(), pool(&check.database));
+            }
+        }
+        Ok(Self { pools, checks })
+    }
+
+    /// PostgreSQL cannot query across databases, so the database is selected by the connection.
+    /// The identifiers are validated by `SqlCheck::parse()`, and the value is bound with its type,
+    /// the primary key is not cast so its index is used.
+    fn query(check: &SqlCheck) -> String {
+        format!(
+            "SELECT \"{}\"::text FROM \"{}\" WHERE \"{}\" = $1 LIMIT 1",
+            check.column, check.table, check.primary_key
+        )
+    }
+
+    async fn check(&self, check: &SqlCheck) -> Result<(bool, String)> {
+        let query = sqlx::query_scalar::<_, Option<String>>(AssertSqlSafe(Self::query(check)));
+        let query = match &check.value {
+            SqlValue::Int(v) => query.bind(*v),
+            SqlValue::Text(v) => query.bind(v.clone()),
+        };
+        let actual = query.fetch_optional(&self.pools[&check.database]).await?;
+        Ok(check.verify(actual))
+    }
+}
+
+#[async_trait]
+impl NativeClient for Postgres {
+    fn kind(&self) -> &str {
+        "PostgreSQL"
+    }
+
+    async fn probe(&self) -> Result<(bool, String)> {
+        if self.checks.is_empty() {
+            self.pools[DEFAULT_DATABASE].acquire().await?.ping().await?;
+            return Ok((true, "Ping PostgreSQL Server Successfully!".to_string()));
+        }
+
+        for check in &self.checks {
+            let (ok, msg) = self.check(check).await?;
+            if !ok {
+                return Ok((false, msg));
+            }
+        }
+
+        Ok((true, "Check PostgreSQL Data Successfully!".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query() {
+        let c = SqlCheck::parse("test:product:name:id:1", "EaseProbe").unwrap();
+        assert_eq!(
+            Postgres::query(&c),
+            "SELECT \"name\"::text FROM \"product\" WHERE \"id\" = $1 LIMIT 1"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pools() {
+        let b: ClientProbeBehavior = serde_yaml::from_str(
+            r#"
+driver: postgres
+host: localhost
+data:
+  "test:product:name:id:1": EaseProbe
+  "test:product:name:id:2": Probe
+  "shop:order:state:id:1": paid
+"#,
+        )
+        .unwrap();
+        let pg = Postgres::new(&b).unwrap();
+        let mut databases: Vec<_> = pg.pools.keys().cloned().collect();
+        databases.sort();
+        assert_eq!(databases, ["postgres", "shop", "test"]);
+    }
+}
@@ -0,0 +1,150 @@
+use anyhow::{bail, Result};
+
+/// The max length of the MySQL and PostgreSQL identifiers.
+const MAX_IDENTIFIER_LEN: usize = 63;
+
+/// The primary key value of a data check, which is bound as a query parameter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SqlValue {
+    Int(i64),
+    Text(String),
+}
+
+/// A row-value assertion parsed from the data key
+/// `database:table:column:primary_key:value`, which is translated to
+/// `SELECT column FROM database.table WHERE primary_key = value`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SqlCheck {
+    pub key: String,
+    pub database: String,
+    pub table: String,
+    pub column: String,
+    pub primary_key: String,
+    pub value: SqlValue,
+    pub expected: String,
+}
+
+impl SqlCheck {
+    pub fn parse(key: &str, expected: &str) -> Result<Self> {
+        let fields: Vec<&str> = key.splitn(5, ':').collect();
+        if fields.len() != 5 {
+            bail!(
+                "invalid data key `{}`, the format is `database:table:column:primary_key:value`",
+                key
+            );
+        }
+
+        for (name, ident) in [
+            ("database", fields[0]),
+            ("table", fields[1]),
+            ("column", fields[2]),
+            ("primary_key", fields[3]),
+        ] {
+            if !is_valid_identifier(ident) {
+                bail!("invalid {} `{}` in data key `{}`", name, ident, key);
+            }
+        }
+
+        if fields[4].is_empty() {
+            bail!("empty primary key value in data key `{}`", key);
+        }
+        let value = match fields[4].parse::<i64>() {
+            Ok(v) => SqlValue::Int(v),
+            Err(_) => SqlValue::Text(fields[4].to_string()),
+        };
+
+        Ok(Self {
+            key: key.to_string(),
+            database: fields[0].to_string(),
+            table: fields[1].to_string(),
+            column: fields[2].to_string(),
+            primary_key: fields[3].to_string(),
+            value,
+            expected: expected.to_string(),
+        })
+    }
+
+    pub fn parse_all<'a, I>(data: I) -> Result<Vec<Self>>
+    where
+        I: IntoIterator<Item = (&'a String, &'a String)>,
+    {
+        let mut checks = data
+            .into_iter()
+            .map(|(k, v)| Self::parse(k, v))
+            .collect::<Result<Vec<_>>>()?;
+        // keep the probe order stable, as the data comes from a map
+        checks.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(checks)
+    }
+
+    /// Compares the fetched value with the expected one.
+    pub fn verify(&self, actual: Option<Option<String>>) -> (bool, String) {
+        match actual {
+            None => (false, format!("No data found for key `{}`", self.key)),
+            Some(None) => (
+                false,
+                format!(
+                    "Value mismatched for key `{}` - expected `{}`, got NULL",
+                    self.key, self.expected
+                ),
+            ),
+            Some(Some(v)) if v != self.expected => (
+                false,
+                format!(
+                    "Value mismatched for key `{}` - expected `{}`, got `{}`",
+                    self.key, self.expected, v
+                ),
+            ),
+            Some(Some(_)) => (true, String::new()),
+        }
+    }
+}
+
+/// Only the plain identifiers are allowed, so that the data keys cannot inject any SQL.
+pub fn is_valid_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+    s.len() <= MAX_IDENTIFIER_LEN
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let c = SqlCheck::parse("test:product:name:id:1", "EaseProbe").unwrap();
+        assert_eq!(c.database, "test");
+        assert_eq!(c.table, "product");
+        assert_eq!(c.column, "name");
+        assert_eq!(c.primary_key, "id");
+        assert_eq!(c.value, SqlValue::Int(1));
+
+        let c = SqlCheck::parse("test:product:name:sku:a:b", "x").unwrap();
+        assert_eq!(c.value, SqlValue::Text("a:b".to_string()));
+
+        assert!(SqlCheck::parse("test:product:name:id", "x").is_err());
+        assert!(SqlCheck::parse("test:product:name:id:", "x").is_err());
+        assert!(SqlCheck::parse("test:product; DROP TABLE x:name:id:1", "x").is_err());
+        assert!(SqlCheck::parse("test:product:`name`:id:1", "x").is_err());
+        assert!(SqlCheck::parse("test:1product:name:id:1", "x").is_err());
+    }
+
+    #[test]
+    fn test_verify() {
+        let c = SqlCheck::parse("test:employee:age:id:2", "45").unwrap();
+        assert!(c.verify(Some(Some("45".to_string()))).0);
+
+        let (ok, msg) = c.verify(Some(Some("46".to_string())));
+        assert!(!ok);
+        assert!(msg.contains("`test:employee:age:id:2`"));
+        assert!(msg.contains("`46`"));
+
+        assert!(!c.verify(Some(None)).0);
+        assert!(c.verify(None).1.contains("No data found"));
+    }
+}
@@ -0,0 +1,159 @@
+use std::fs;
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use zookeeper_client::{Client, TlsOptions};
+
+use super::{ClientProbeBehavior, NativeClient};
+
+const DEFAULT_PORT: u16 = 2181;
+
+/// The ZooKeeper native client
+#[derive(Debug)]
+pub struct Zookeeper {
+    cluster: String,
+    tls: Option<TlsOptions>,
+    data: Vec<(String, String)>,
+}
+
+impl Zookeeper {
+    pub fn new(conf: &ClientProbeBehavior) -> Result<Self> {
+        let (host, port) = conf.host_port(DEFAULT_PORT)?;
+
+        let tls = &conf.tls;
+        let tls = if tls.is_enabled() {
+            let mut options = TlsOptions::new();
+            if !tls.ca.is_empty() {
+                options = options.with_pem_ca(&fs::read_to_string(&tls.ca)?)?;
+            }
+            if !tls.cert.is_empty() {
+                options = options.with_pem_identity(
+                    &fs::read_to_string(&tls.cert)?,
+                    &fs::read_to_string(&tls.key)?,
+                )?;
+            }
+            if tls.insecure {
+                // the client can't skip the chain verification like the other drivers,
+                // so `insecure` only skips the hostname verification.
+                // SAFETY: this is what the `insecure` option asks for.
+                options = unsafe { options.with_no_hostname_verification() };
+            }
+            Some(options)
+        } else {
+            None
+        };
+
+        let mut data: Vec<(String, String)> = conf
+            .data
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        data.sort();
+        for (path, _) in &data {
+            if !path.starts_with('/') {
+                bail!("invalid zookeeper path `{}`, it must be absolute", path);
+            }
+        }
+
+        Ok(Self {
+            cluster: format!("{}:{}", host, port),
+            tls,
+            data,
+        })
+    }
+
+    async fn connect(&self) -> Result<Client> {
+        let client = match &self.tls {
+            Some(tls) => {
+                Client::connector()
+                    .with_tls(tls.clone())
+                    .secure_connect(&self.cluster)
+                    .await?
+            }
+            None => Client::connector().connect(&self.cluster).await?,
+        };
+        Ok(client)
+    }
+}
+
+#[async_trait]
+impl NativeClient for Zookeeper {
+    fn kind(&self) -> &str {
+        "ZooKeeper"
+    }
+
+    async fn probe(&self) -> Result<(bool, String)> {
+        let client = self.connect().await?;
+
+        if self.data.is_empty() {
+            client.check_stat("/").await?;
+            return Ok((true, "Check ZooKeeper Server Successfully!".to_string()));
+        }
+
+        for (path, expected) in &self.data {
+            let value = match client.get_data(path).await {
+                Ok((value, _)) => String::from_utf8_lossy(&value).to_string(),
+                Err(zookeeper_client::Error::NoNode) => {
+                    return Ok((false, format!("ZooKeeper path `{}` not found", path)))
+                }
+                Err(err) => bail!(err),
+            };
+            if &value != expected {
+                return Ok((
+                    false,
+                    format!(
+                        "Value mismatched for path `{}` - expected `{}`, got `{}`",
+                        path, expected, value
+                    ),
+                ));
+            }
+        }
+
+        Ok((true, "Check ZooKeeper Data Successfully!".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn conf(yaml: &str) -> ClientProbeBehavior {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_config() {
+        let z = Zookeeper::new(&conf(
+            "driver: zookeeper\nhost: localhost\ndata:\n  /b: x\n  /a: y",
+        ))
+        .unwrap();
+        assert_eq!(z.cluster, "localhost:2181");
+        assert!(z.tls.is_none());
+        assert_eq!(
+            z.data,
+            [
+                ("/a".to_string(), "y".to_string()),
+                ("/b".to_string(), "x".to_string())
+            ]
+        );
+
+        assert!(Zookeeper::new(&conf("driver: zookeeper\nhost: zk:abc")).is_err());
+        assert!(Zookeeper::new(&conf("driver: zookeeper\nhost: zk\ndata:\n  a/b: x")).is_err());
+        assert!(Zookeeper::new(&conf("driver: zookeeper\nhost: zk\nca: /no/such/ca.pem")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unreachable() {
+        // the port is closed once the listener is dropped
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let z = Zookeeper::new(&conf(&format!("driver: zookeeper\nhost: {}", addr))).unwrap();
+        // the client retries the servers until the timeout of the probe
+        let result = tokio::time::timeout(Duration::from_secs(1), z.probe()).await;
+        assert!(!matches!(result, Ok(Ok(_))));
+    }
+}
@@ -0,0 +1,329 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use super::{ProbeResult, Prober, Snapshot};
+
+/// The default data file under the working directory.
+pub const DEFAULT_DATA_FILE: &str = "data/data.yaml";
+/// The data file setting to disable the persistence.
+pub const DATA_FILE_DISABLED: &str = "-";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Meta {
+    name: String,
+    version: String,
+    #[serde(with = "humantime_serde")]
+    saved_at: SystemTime,
+}
+
+/// The data file, the results are loaded one by one,
+/// so a broken result doesn't discard the others.
+#[derive(Debug, Serialize, Deserialize)]
+struct DataFile<D> {
+    meta: Meta,
+    #[serde(default)]
+    data: D,
+}
+
+/// Persists the results of the probers, so the SLA survives the restarts.
+#[derive(Debug, Clone)]
+pub struct DataStore {
+    path: PathBuf,
+    /// The max number of the backups, all of them are kept if it's negative
+    backups: i32,
+}
+
+impl DataStore {
+    /// The store of the data file, `None` if the persistence is disabled by `-`.
+    pub fn new(data_file: &str, backups: i32) -> Option<Self> {
+        let path = match data_file.trim() {
+            DATA_FILE_DISABLED => return None,
+            "" => DEFAULT_DATA_FILE,
+            f => f,
+        };
+        Some(Self {
+            path: PathBuf::from(path),
+            backups,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Loads the results, the broken file or results are skipped with a warning.
+    pub fn load(&self) -> Vec<ProbeResult> {
+        let content = match std::fs::read_to_string(&self.path) {
+            Ok(c) => c,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return vec![],
+            Err(e) => {
+                log::warn!("Failed to read the data file {:?}: {}", self.path, e);
+                return vec![];
+            }
+        };
+        let file: DataFile<Vec<serde_yaml::Value>> = match serde_yaml::from_str(&content) {
+            Ok(f) => f,
+            Err(e) => {
+                log::warn!("Ignored the corrupt data file {:?}: {}", self.path, e);
+                return vec![];
+            }
+        };
+        log::info!(
+            "Loaded the data file {:?} of {} {} saved at {}",
+            self.path,
+            file.meta.name,
+            file.meta.version,
+            crate::global::format_time(file.meta.saved_at)
+        );
+        file.data
+            .into_iter()
+            .filter_map(|v| {
+                serde_yaml::from_value::<ProbeResult>(v)
+                    .inspect_err(|e| {
+                        log::warn!("Ignored a corrupt result in {:?}: {}", self.path, e)
+                    })
+                    .ok()
+            })
+            .collect()
+    }
+
+    /// Saves the results, the file is replaced atomically so it's never partial.
+    pub async fn save(&self, results: &[ProbeResult]) -> Result<()> {
+        let file = DataFile {
+            meta: Meta {
+                name: "EaseProbe".to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                saved_at: SystemTime::now(),
+            },
+            data: results,
+        };
+        let content = serde_yaml::to_string(&file)?;
+        if let Some(dir) = self.path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        let tmp = self.path.with_extension("tmp");
+        tokio::fs::write(&tmp, content)
+            .await
+            .with_context(|| format!("failed to write {:?}", tmp))?;
+        tokio::fs::rename(&tmp, &self.path)
+            .await
+            .with_context(|| format!("failed to replace {:?}", self.path))?;
+        Ok(())
+    }
+
+    /// Saves the published results of the probers, it doesn't wait for the probes in flight.
+    pub async fn save_probers(&self, probers: &[Arc<RwLock<dyn Prober>>]) -> Result<()> {
+        let mut results = Vec::with_capacity(probers.len());
+        for p in probers {
+            results.push(Snapshot::of(p).await.result);
+        }
+        self.save(&results).await
+    }
+
+    /// Restores the results of the probers by the kind and name,
+    /// the configured name and endpoint are kept. It locks the probers, so it's
+    /// called at the startup before they run.
+    pub async fn restore(&self, probers: &[Arc<RwLock<dyn Prober>>]) {
+        let mut results = self.load();
+        for p in probers {
+            let mut p = p.write().await;
+            let (kind, name) = (p.kind().to_string(), p.name().to_string());
+            let Some(i) = results
+                .iter()
+                .position(|r| r.kind == kind && r.name == name)
+            else {
+                continue;
+            };
+            let saved = results.swap_remove(i);
+            let r = p.result();
+            let max_len = r.stat.status_counter.max_len();
+            *r = ProbeResult {
+                name: r.name.clone(),
+                kind: r.kind.clone(),
+                endpoint: r.endpoint.clone(),
+                ..saved
+            };
+            r.stat.status_counter.set_max_len(max_len);
+            log::debug!("[{} / {}] restored the result", kind, name);
+        }
+    }
+
+    /// Backs up the data file with the timestamp suffix, and removes the oldest backups.
+    pub fn backup(&self) -> Result<()> {
+        if !self.path.exists() {
+            return Ok(());
+        }
+        if self.backups != 0 {
+            let suffix = chrono::Local::now().format("%Y%m%d%H%M%S");
+            let backup = PathBuf::from(format!("{}-{}", self.path.display(), suffix));
+            std::fs::copy(&self.path, &backup)
+                .with_context(|| format!("failed to backup {:?}", self.path))?;
+        }
+        if self.backups < 0 {
+            return Ok(());
+        }
+
+        let mut backups = self.backup_files()?;
+        // the timestamps sort the backups from the oldest
+        backups.sort();
+        let remove = backups.len().saturating_sub(self.backups as usize);
+        for f in &backups[..remove] {
+            if let Err(e) = std::fs::remove_file(f) {
+                log::warn!("Failed to remove the backup {:?}: {}", f, e);
+            }
+        }
+        Ok(())
+    }
+
+    fn backup_files(&self) -> Result<Vec<PathBuf>> {
+        let dir = match self.path.parent() {
+            Some(d) if !d.as_os_str().is_empty() => d,
+            _ => Path::new("."),
+        };
+        let prefix = format!(
+            "{}-",
+            self.path.file_name().unwrap_or_default().to_string_lossy()
+        );
+        let mut files = vec![];
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name
+                .strip_prefix(&prefix)
+                .is_some_and(|ts| !ts.is_empty() && ts.chars().all(|c| c.is_ascii_digit()))
+            {
+                files.push(entry.path());
+            }
+        }
+        Ok(files)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{channel::new_dummy_prober, Status};
+
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("easeprobe-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_new() {
+        assert!(DataStore::new("-", 5).is_none());
+        assert_eq!(
+            DataStore::new("", 5).unwrap().path(),
+            Path::new(DEFAULT_DATA_FILE)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_save_restore() {
+        let dir = temp_dir("data");
+        let store = DataStore::new(dir.join("data.yaml").to_str().unwrap(), 5).unwrap();
+
+        let prober: Arc<RwLock<dyn Prober>> = Arc::new(new_dummy_prober("http", "", "web", vec![]));
+        {
+            let mut p = prober.write().await;
+            let r = p.result();
+            r.name = "web".to_string();
+            r.kind = "http".to_string();
+            r.status = Status::Down;
+            r.stat.total = 42;
+            r.stat.uptime = Duration::from_secs(400);
+            r.stat.status.insert(Status::Up, 40);
+        }
+        Snapshot::publish_all(std::slice::from_ref(&prober)).await;
+        // it's saved while the prober is being probed
+        let _probing = prober.write().await;
+        tokio::time::timeout(
+            Duration::from_millis(100),
+            store.save_probers(std::slice::from_ref(&prober)),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        let restored: Arc<RwLock<dyn Prober>> =
+            Arc::new(new_dummy_prober("http", "", "web", vec![]));
+        restored.write().await.result().endpoint = "https://new.example.com".to_string();
+        let other: Arc<RwLock<dyn Prober>> = Arc::new(new_dummy_prober("tcp", "", "web", vec![]));
+        store.restore(&[restored.clone(), other.clone()]).await;
+
+        let mut p = restored.write().await;
+        let r = p.result();
+        assert_eq!(r.status, Status::Down);
+        assert_eq!(r.stat.total, 42);
+        assert_eq!(r.stat.uptime, Duration::from_secs(400));
+        assert_eq!(r.stat.status[&Status::Up], 40);
+        assert_eq!(r.endpoint, "https://new.example.com");
+        assert_eq!(other.write().await.result().stat.total, 0);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_corrupt() {
+        let dir = temp_dir("corrupt");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("data.yaml");
+        let store = DataStore::new(path.to_str().unwrap(), 5).unwrap();
+        assert!(store.load().is_empty());
+
+        std::fs::write(&path, "meta: [broken").unwrap();
+        assert!(store.load().is_empty());
+
+        let r = ProbeResult {
+            name: "web".to_string(),
+            kind: "http".to_string(),
+            ..Default::default()
+        };
+        store.save(&[r]).await.unwrap();
+        // a partial result is skipped, the others are loaded
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, format!("{}- name: partial\n", content)).unwrap();
+        let results = store.load();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "web");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_backup() {
+        let dir = temp_dir("backup");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("data.yaml");
+        let store = DataStore::new(path.to_str().unwrap(), 2).unwrap();
+        store.backup().unwrap();
+        assert!(store.backup_files().unwrap().is_empty());
+
+        std::fs::write(&path, "data").unwrap();
+        for ts in ["20240101000000", "20240102000000", "20240103000000"] {
+            std::fs::write(dir.join(format!("data.yaml-{}", ts)), "old").unwrap();
+        }
+        std::fs::write(dir.join("data.yaml-keep"), "not a backup").unwrap();
+        store.backup().unwrap();
+
+        let mut files = store.backup_files().unwrap();
+        files.sort();
+        assert_eq!(files.len(), 2);
+        assert!(files[0].ends_with("data.yaml-20240103000000"));
+        assert_eq!(std::fs::read_to_string(&files[1]).unwrap(), "data");
+        assert!(dir.join("data.yaml-keep").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
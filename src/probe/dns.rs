@@ -0,0 +1,575 @@
+use std::{net::SocketAddr, str::FromStr, sync::Arc, time::Duration};
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use hickory_proto::{
+    op::{Message, Query, ResponseCode},
+    rr::{Name, RecordType},
+};
+use rustls::{pki_types::ServerName, ClientConfig};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::{lookup_host, TcpStream, UdpSocket},
+    time::Instant,
+};
+use tokio_rustls::TlsConnector;
+
+use crate::{ProbeSettings, TLSConfig};
+
+use super::{DefaultProber, ProbeBehavior, ProbeResult, Prober};
+
+const SUPPORTED_RECORD_TYPES: [RecordType; 6] = [
+    RecordType::A,
+    RecordType::AAAA,
+    RecordType::CNAME,
+    RecordType::MX,
+    RecordType::TXT,
+    RecordType::SRV,
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DnsProber {
+    #[serde(flatten)]
+    pub default_prober: DefaultProber<DnsProbeBehavior>,
+}
+
+/// The transport protocol to query the DNS server.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DnsProtocol {
+    #[default]
+    Udp,
+    Tcp,
+    #[serde(alias = "tls")]
+    Dot,
+}
+
+impl DnsProtocol {
+    pub fn to_string(self) -> &'static str {
+        match self {
+            DnsProtocol::Udp => "udp",
+            DnsProtocol::Tcp => "tcp",
+            DnsProtocol::Dot => "dot",
+        }
+    }
+
+    fn default_port(self) -> u16 {
+        match self {
+            DnsProtocol::Udp | DnsProtocol::Tcp => 53,
+            DnsProtocol::Dot => 853,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DnsProbeBehavior {
+    /// The DNS server, such as `8.8.8.8` or `10.0.0.2:53`
+    pub server: String,
+    #[serde(default)]
+    pub protocol: DnsProtocol,
+    /// The domain name to resolve
+    pub domain: String,
+    /// The record type: A, AAAA, CNAME, MX, TXT or SRV
+    #[serde(default = "default_record_type", rename = "type")]
+    pub record_type: String,
+    /// The answers must contain all of these values, e.g. `10.0.0.1` or `10 mail.example.com`
+    #[serde(default)]
+    pub expect: Vec<String>,
+    /// The minimum number of answers
+    #[serde(default = "default_min_answers")]
+    pub min_answers: usize,
+    /// The server name to verify the DoT certificate, default is the host of `server`
+    #[serde(default)]
+    pub tls_server_name: String,
+    #[serde(flatten)]
+    pub tls: TLSConfig,
+    #[serde(skip)]
+    pub query: Option<DnsQuery>,
+}
+
+fn default_record_type() -> String {
+    "A".to_string()
+}
+
+fn default_min_answers() -> usize {
+    1
+}
+
+/// The resolved query target, prepared by `DnsProber::config()`.
+#[derive(Debug, Clone)]
+pub struct DnsQuery {
+    addr: SocketAddr,
+    name: Name,
+    record_type: RecordType,
+    tls: Option<(Arc<ClientConfig>, ServerName<'static>)>,
+}
+
+impl DnsProbeBehavior {
+    fn request(&self, q: &DnsQuery) -> Message {
+        let mut msg = Message::query();
+        msg.metadata.recursion_desired = true;
+        msg.add_query(Query::query(q.name.clone(), q.record_type));
+        msg
+    }
+
+    async fn exchange_udp(&self, q: &DnsQuery, req: &[u8], id: u16) -> Result<Message> {
+        let bind = if q.addr.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        let socket = UdpSocket::bind(bind).await?;
+        socket.connect(q.addr).await?;
+        socket.send(req).await?;
+
+        let mut buf = vec![0u8; 4096];
+        loop {
+            let n = socket.recv(&mut buf).await?;
+            let msg = Message::from_vec(&buf[..n])?;
+            // ignore the stray responses
+            if msg.metadata.id == id {
+                return Ok(msg);
+            }
+        }
+    }
+
+    /// DNS over a stream, each message is prefixed with its 2 bytes length.
+    async fn exchange_stream<S>(stream: &mut S, req: &[u8]) -> Result<Message>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let mut buf = Vec::with_capacity(req.len() + 2);
+        buf.extend_from_slice(&(req.len() as u16).to_be_bytes());
+        buf.extend_from_slice(req);
+        stream.write_all(&buf).await?;
+        stream.flush().await?;
+
+        let len = stream.read_u16().await? as usize;
+        let mut resp = vec![0u8; len];
+        stream.read_exact(&mut resp).await?;
+        Ok(Message::from_vec(&resp)?)
+    }
+
+    async fn exchange(&self, q: &DnsQuery) -> Result<Message> {
+        let req = self.request(q);
+        let id = req.metadata.id;
+        let bytes = req.to_vec()?;
+
+        match self.protocol {
+            DnsProtocol::Udp => {
+                let resp = self.exchange_udp(q, &bytes, id).await?;
+                if !resp.metadata.truncation {
+                    return Ok(resp);
+                }
+                // the answer is too large for UDP, retry with TCP
+                let mut stream = TcpStream::connect(q.addr).await?;
+                Self::exchange_stream(&mut stream, &bytes).await
+            }
+            DnsProtocol::Tcp => {
+                let mut stream = TcpStream::connect(q.addr).await?;
+                Self::exchange_stream(&mut stream, &bytes).await
+            }
+            DnsProtocol::Dot => {
+                let Some((config, server_name)) = &q.tls else {
+                    bail!("DoT is not configured");
+                };
+                let stream = TcpStream::connect(q.addr).await?;
+                let mut stream = TlsConnector::from(Arc::clone(config))
+                    .connect(server_name.clone(), stream)
+                    .await?;
+                Self::exchange_stream(&mut stream, &bytes).await
+            }
+        }
+    }
+
+    /// Checks the answers against `expect` and `min_answers`.
+    fn check(&self, q: &DnsQuery, resp: &Message) -> (bool, Vec<String>, String) {
+        if resp.metadata.response_code != ResponseCode::NoError {
+            return (
+                false,
+                vec![],
+                format!(
+                    "DNS server returned {} for {} {}",
+                    resp.metadata.response_code, self.domain, q.record_type
+                ),
+            );
+        }
+
+        let answers: Vec<String> = resp
+            .answers
+            .iter()
+            .filter(|r| r.record_type() == q.record_type)
+            .map(|r| normalize(&r.data.to_string()))
+            .collect();
+
+        if answers.len() < self.min_answers {
+            let msg = format!(
+                "Got {} answer(s) for {} {}, expected at least {}",
+                answers.len(),
+                self.domain,
+                q.record_type,
+                self.min_answers
+            );
+            return (false, answers, msg);
+        }
+
+        let missing: Vec<&String> = self
+            .expect
+            .iter()
+            .filter(|e| !answers.contains(&normalize(e)))
+            .collect();
+        if !missing.is_empty() {
+            let msg = format!(
+                "Missing expected answer(s) {:?} for {} {}, got {:?}",
+                missing, self.domain, q.record_type, answers
+            );
+            return (false, answers, msg);
+        }
+
+        (true, answers, String::new())
+    }
+}
+
+/// The names are compared case-insensitively without the trailing dot.
+fn normalize(s: &str) -> String {
+    s.split_whitespace()
+        .map(|f| f.trim_end_matches('.'))
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+#[async_trait]
+impl ProbeBehavior for DnsProbeBehavior {
+    async fn do_probe(&self) -> Result<(bool, String)> {
+        let Some(q) = &self.query else {
+            bail!("DNS query is not configured");
+        };
+
+        let start = Instant::now();
+        let resp = self.exchange(q).await?;
+        let latency = start.elapsed();
+
+        let (ok, answers, msg) = self.check(q, &resp);
+        if !ok {
+            return Ok((false, msg));
+        }
+        Ok((
+            true,
+            format!(
+                "Resolved {} {} via {} ({}) in {:?}: {}",
+                self.domain,
+                q.record_type,
+                q.addr,
+                self.protocol.to_string(),
+                latency,
+                answers.join(", ")
+            ),
+        ))
+    }
+}
+
+#[async_trait]
+impl Prober for DnsProber {
+    fn kind(&self) -> &str {
+        &self.default_prober.kind
+    }
+
+    fn name(&self) -> &str {
+        &self.default_prober.name
+    }
+
+    fn channels(&self) -> Vec<String> {
+        self.default_prober.channels.clone()
+    }
+
+    fn timeout(&self) -> &Duration {
+        &self.default_prober.timeout
+    }
+
+    fn interval(&self) -> &Duration {
+        &self.default_prober.interval
+    }
+
+    fn result(&mut self) -> &mut ProbeResult {
+        &mut self.default_prober.result
+    }
+
+    async fn probe(&mut self) -> ProbeResult {
+        self.default_prober.probe().await
+    }
+
+    async fn config(&mut self, setting: &ProbeSettings) -> Result<()> {
+        let b = &self.default_prober.behavior;
+        self.default_prober.kind = "dns".to_string();
+        self.default_prober.tag = b.protocol.to_string().to_string();
+        self.default_prober.result.endpoint = format!("{} @ {}", b.domain, b.server);
+        self.default_prober.config(setting).await?;
+
+        let b = &self.default_prober.behavior;
+        let record_type = RecordType::from_str(&b.record_type.to_uppercase())
+            .ok()
+            .filter(|t| SUPPORTED_RECORD_TYPES.contains(t));
+        let Some(record_type) = record_type else {
+            log::error!(
+                "[{} / {}] record type {} is not supported",
+                self.kind(),
+                self.name(),
+                b.record_type,
+            );
+            bail!("unsupported record type {}", b.record_type)
+        };
+
+        let name =
+            Name::from_str(&b.domain).with_context(|| format!("invalid domain {}", b.domain))?;
+
+        // the server could be `host` or `host:port`
+        let (host, addr) = match b.server.parse::<SocketAddr>() {
+            Ok(addr) => (addr.ip().to_string(), addr),
+            Err(_) => {
+                let (host, server) = match b.server.rsplit_once(':') {
+                    Some((h, p)) if p.parse::<u16>().is_ok() => (h.to_string(), b.server.clone()),
+                    _ => (
+                        b.server.clone(),
+                        format!("{}:{}", b.server, b.protocol.default_port()),
+                    ),
+                };
+                let host = host.trim_matches(|c| c == '[' || c == ']').to_string();
+                let addr = lookup_host(&server)
+                    .await?
+                    .next()
+                    .with_context(|| format!("failed to resolve DNS server {}", b.server))?;
+                (host, addr)
+            }
+        };
+
+        let tls = if b.protocol == DnsProtocol::Dot {
+            b.tls.check()?;
+            let sni = if b.tls_server_name.is_empty() {
+                host
+            } else {
+                b.tls_server_name.clone()
+            };
+            Some((b.tls.rustls_config()?, ServerName::try_from(sni)?))
+        } else {
+            None
+        };
+
+        log::debug!(
+            "[{} / {}] query {} {} via {} ({})",
+            self.kind(),
+            self.name(),
+            b.domain,
+            record_type,
+            addr,
+            b.protocol.to_string(),
+        );
+
+        self.default_prober.behavior.query = Some(DnsQuery {
+            addr,
+            name,
+            record_type,
+            tls,
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use hickory_proto::{
+        op::{MessageType, OpCode},
+        rr::{
+            rdata::{A, MX},
+            RData, Record,
+        },
+    };
+    use tokio::{io::AsyncRead, net::TcpListener};
+    use tokio_rustls::TlsAcceptor;
+
+    use crate::probe::testing::tls_certs;
+
+    use super::*;
+
+    /// Answers `api.internal` A with two addresses and `example.com` MX, others are NXDomain.
+    fn answer(req: &[u8]) -> Vec<u8> {
+        let req = Message::from_vec(req).unwrap();
+        let q = req.queries[0].clone();
+        let mut resp = Message::new(req.metadata.id, MessageType::Response, OpCode::Query);
+        resp.add_query(q.clone());
+        let name = q.name().clone();
+        match (name.to_string().as_str(), q.query_type()) {
+            ("api.internal.", RecordType::A) => {
+                for ip in [[10, 0, 0, 1], [10, 0, 0, 2]] {
+                    resp.add_answer(Record::from_rdata(
+                        name.clone(),
+                        60,
+                        RData::A(A(Ipv4Addr::from(ip))),
+                    ));
+                }
+            }
+            ("example.com.", RecordType::MX) => {
+                let exchange = Name::from_str("Mail.Example.com.").unwrap();
+                resp.add_answer(Record::from_rdata(
+                    name,
+                    60,
+                    RData::MX(MX::new(10, exchange)),
+                ));
+            }
+            _ => resp.metadata.response_code = ResponseCode::NXDomain,
+        }
+        resp.to_vec().unwrap()
+    }
+
+    async fn serve_udp() -> String {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let mut buf = vec![0u8; 4096];
+            loop {
+                let (n, peer) = socket.recv_from(&mut buf).await.unwrap();
+                socket.send_to(&answer(&buf[..n]), peer).await.unwrap();
+            }
+        });
+        addr
+    }
+
+    /// Answers a query with the length prefix of TCP and DoT.
+    async fn respond<S: AsyncRead + AsyncWrite + Unpin>(stream: &mut S) {
+        let len = stream.read_u16().await.unwrap() as usize;
+        let mut req = vec![0u8; len];
+        stream.read_exact(&mut req).await.unwrap();
+        let resp = answer(&req);
+        stream.write_u16(resp.len() as u16).await.unwrap();
+        stream.write_all(&resp).await.unwrap();
+        stream.flush().await.unwrap();
+    }
+
+    async fn serve_tcp() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                respond(&mut stream).await;
+            }
+        });
+        addr
+    }
+
+    /// Serves DoT with the certificate of `localhost`.
+    async fn serve_dot(config: rustls::ServerConfig) -> String {
+        let acceptor = TlsAcceptor::from(Arc::new(config));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let acceptor = acceptor.clone();
+                tokio::spawn(async move {
+                    if let Ok(mut stream) = acceptor.accept(stream).await {
+                        respond(&mut stream).await;
+                    }
+                });
+            }
+        });
+        addr
+    }
+
+    async fn probe(yaml: &str) -> ProbeResult {
+        let mut p: DnsProber = serde_yaml::from_str(yaml).unwrap();
+        p.config(&ProbeSettings::default()).await.unwrap();
+        p.probe().await
+    }
+
+    #[tokio::test]
+    async fn test_dns_udp() {
+        let server = serve_udp().await;
+        let r = probe(&format!(
+            "name: dns\nserver: {}\ndomain: api.internal\nexpect: [10.0.0.2]\nmin_answers: 2",
+            server
+        ))
+        .await;
+        assert!(r.stat.status_counter.current_status, "{}", r.message);
+        assert!(r.message.contains("10.0.0.1, 10.0.0.2"));
+
+        let r = probe(&format!(
+            "name: dns\nserver: {}\ndomain: api.internal\nexpect: [10.0.0.3]",
+            server
+        ))
+        .await;
+        assert!(!r.stat.status_counter.current_status);
+        assert!(r.message.contains("Missing expected answer"));
+
+        let r = probe(&format!(
+            "name: dns\nserver: {}\ndomain: api.internal\nmin_answers: 3",
+            server
+        ))
+        .await;
+        assert!(!r.stat.status_counter.current_status);
+
+        let r = probe(&format!(
+            "name: dns\nserver: {}\ndomain: nx.internal",
+            server
+        ))
+        .await;
+        assert!(!r.stat.status_counter.current_status);
+        assert!(r.message.contains("Non-Existent Domain"), "{}", r.message);
+    }
+
+    #[tokio::test]
+    async fn test_dns_tcp() {
+        let server = serve_tcp().await;
+        let r = probe(&format!(
+            "name: dns\nserver: {}\nprotocol: tcp\ndomain: example.com\ntype: mx\nexpect: ['10 mail.example.com']\ntimeout: 5s",
+            server
+        ))
+        .await;
+        assert!(r.stat.status_counter.current_status, "{}", r.message);
+    }
+
+    #[tokio::test]
+    async fn test_dns_dot() {
+        let (ca, config) = tls_certs();
+        let server = serve_dot(config).await;
+        let dir = std::env::temp_dir().join(format!("easeprobe-dot-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let ca_file = dir.join("ca.pem");
+        std::fs::write(&ca_file, ca).unwrap();
+        let other_file = dir.join("other.pem");
+        std::fs::write(&other_file, tls_certs().0).unwrap();
+
+        let yaml = |ca: &std::path::Path| {
+            format!(
+                "name: dns\nserver: {}\nprotocol: dot\ndomain: api.internal\ntls_server_name: localhost\nca: {}",
+                server,
+                ca.display()
+            )
+        };
+
+        // trusted by the CA
+        let r = probe(&yaml(&ca_file)).await;
+        assert!(r.stat.status_counter.current_status, "{}", r.message);
+        assert!(r.message.contains("10.0.0.1, 10.0.0.2"));
+
+        // the certificate is not signed by the other CA
+        let r = probe(&yaml(&other_file)).await;
+        assert!(!r.stat.status_counter.current_status);
+        assert!(
+            r.message.contains("invalid peer certificate"),
+            "{}",
+            r.message
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_dns_config() {
+        let mut p: DnsProber =
+            serde_yaml::from_str("name: dns\nserver: 127.0.0.1\ndomain: a.com\ntype: SOA").unwrap();
+        assert!(p.config(&ProbeSettings::default()).await.is_err());
+    }
+}
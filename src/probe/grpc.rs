@@ -0,0 +1,339 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use hyper_util::rt::TokioIo;
+use rustls::{pki_types::ServerName, ClientConfig};
+use serde::{Deserialize, Serialize};
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+use tonic::{
+    metadata::{MetadataKey, MetadataMap, MetadataValue},
+    transport::{Endpoint, Uri},
+};
+use tonic_health::pb::{
+    health_check_response::ServingStatus, health_client::HealthClient, HealthCheckRequest,
+};
+
+use crate::{ProbeSettings, TLSConfig};
+
+use super::{DefaultProber, ProbeBehavior, ProbeResult, Prober};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GrpcProber {
+    #[serde(flatten)]
+    pub default_prober: DefaultProber<GrpcProbeBehavior>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GrpcProbeBehavior {
+    /// The gRPC server, such as `10.0.0.5:50051`
+    pub host: String,
+    /// The service name to check, empty means the overall health of the server
+    #[serde(default)]
+    pub service: String,
+    /// The custom metadata headers of the health check request
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// Use TLS, it is also enabled if any of `ca`, `cert`, `key` or `insecure` is set
+    #[serde(default, rename = "tls")]
+    pub use_tls: bool,
+    #[serde(flatten)]
+    pub tls: TLSConfig,
+    #[serde(skip)]
+    pub target: Option<GrpcTarget>,
+}
+
+/// The connection target prepared by `GrpcProber::config()`.
+#[derive(Debug, Clone)]
+pub struct GrpcTarget {
+    endpoint: Endpoint,
+    metadata: MetadataMap,
+    tls: Option<(Arc<ClientConfig>, ServerName<'static>)>,
+}
+
+impl GrpcProbeBehavior {
+    fn service_name(&self) -> &str {
+        if self.service.is_empty() {
+            "server"
+        } else {
+            &self.service
+        }
+    }
+
+    async fn check(&self, target: &GrpcTarget) -> Result<ServingStatus, tonic::Status> {
+        let channel = match &target.tls {
+            None => target.endpoint.connect().await,
+            Some((config, server_name)) => {
+                let host = self.host.clone();
+                let config = Arc::clone(config);
+                let server_name = server_name.clone();
+                let connector = tower::service_fn(move |_: Uri| {
+                    let host = host.clone();
+                    let connector = TlsConnector::from(Arc::clone(&config));
+                    let server_name = server_name.clone();
+                    async move {
+                        let stream = TcpStream::connect(&host).await?;
+                        let stream = connector.connect(server_name, stream).await?;
+                        Ok::<_, std::io::Error>(TokioIo::new(stream))
+                    }
+                });
+                target.endpoint.connect_with_connector(connector).await
+            }
+        }
+        .map_err(|e| tonic::Status::unavailable(format!("{:?}", e)))?;
+
+        let mut request = tonic::Request::new(HealthCheckRequest {
+            service: self.service.clone(),
+        });
+        *request.metadata_mut() = target.metadata.clone();
+
+        let response = HealthClient::new(channel).check(request).await?;
+        Ok(response.into_inner().status())
+    }
+}
+
+#[async_trait]
+impl ProbeBehavior for GrpcProbeBehavior {
+    async fn do_probe(&self) -> Result<(bool, String)> {
+        let Some(target) = &self.target else {
+            bail!("gRPC target is not configured");
+        };
+
+        match self.check(target).await {
+            Ok(ServingStatus::Serving) => {
+                Ok((true, format!("gRPC {} is SERVING", self.service_name())))
+            }
+            Ok(status) => Ok((
+                false,
+                format!("gRPC {} is {}", self.service_name(), status.as_str_name()),
+            )),
+            Err(status) => Ok((
+                false,
+                format!(
+                    "gRPC health check of {} failed - {:?}: {}",
+                    self.service_name(),
+                    status.code(),
+                    status.message()
+                ),
+            )),
+        }
+    }
+}
+
+#[async_trait]
+impl Prober for GrpcProber {
+    fn kind(&self) -> &str {
+        &self.default_prober.kind
+    }
+
+    fn name(&self) -> &str {
+        &self.default_prober.name
+    }
+
+    fn channels(&self) -> Vec<String> {
+        self.default_prober.channels.clone()
+    }
+
+    fn timeout(&self) -> &Duration {
+        &self.default_prober.timeout
+    }
+
+    fn interval(&self) -> &Duration {
+        &self.default_prober.interval
+    }
+
+    fn result(&mut self) -> &mut ProbeResult {
+        &mut self.default_prober.result
+    }
+
+    async fn probe(&mut self) -> ProbeResult {
+        self.default_prober.probe().await
+    }
+
+    async fn config(&mut self, setting: &ProbeSettings) -> Result<()> {
+        self.default_prober.kind = "grpc".to_string();
+        self.default_prober.result.endpoint = self.default_prober.behavior.host.clone();
+        self.default_prober.config(setting).await?;
+
+        let b = &self.default_prober.behavior;
+        let Some((host, _)) = b.host.rsplit_once(':') else {
+            log::error!(
+                "[{} / {}] host must be `host:port` - {}",
+                self.kind(),
+                self.name(),
+                b.host
+            );
+            bail!("invalid host {}", b.host)
+        };
+
+        let mut metadata = MetadataMap::new();
+        for (k, v) in &b.headers {
+            let key = MetadataKey::from_bytes(k.to_lowercase().as_bytes())
+                .with_context(|| format!("invalid metadata key {}", k))?;
+            let value = MetadataValue::try_from(v.as_str())
+                .with_context(|| format!("invalid metadata value of {}", k))?;
+            metadata.insert(key, value);
+        }
+
+        let secure = b.use_tls || b.tls.is_enabled();
+        let tls = if secure {
+            b.tls.check()?;
+            let mut config = (*b.tls.rustls_config()?).clone();
+            config.alpn_protocols = vec![b"h2".to_vec()];
+            let host = host.trim_matches(|c| c == '[' || c == ']').to_string();
+            Some((Arc::new(config), ServerName::try_from(host)?))
+        } else {
+            None
+        };
+
+        let scheme = if secure { "https" } else { "http" };
+        let endpoint = Endpoint::from_shared(format!("{}://{}", scheme, b.host))
+            .with_context(|| format!("invalid host {}", b.host))?;
+
+        self.default_prober.tag = scheme.to_string();
+        self.default_prober.behavior.target = Some(GrpcTarget {
+            endpoint,
+            metadata,
+            tls,
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+    use tokio_rustls::TlsAcceptor;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::{transport::Server, Request, Status};
+    use tonic_health::{
+        pb::health_server::HealthServer,
+        server::{HealthReporter, HealthService},
+    };
+
+    use crate::probe::testing::tls_certs;
+
+    use super::*;
+
+    /// Serves the health service, which requires the `x-token: easeprobe` header.
+    async fn serve() -> String {
+        let reporter = HealthReporter::new();
+        reporter
+            .set_service_status("up", tonic_health::ServingStatus::Serving)
+            .await;
+        reporter
+            .set_service_status("down", tonic_health::ServingStatus::NotServing)
+            .await;
+
+        let auth = |req: Request<()>| match req.metadata().get("x-token") {
+            Some(t) if t == "easeprobe" => Ok(req),
+            _ => Err(Status::unauthenticated("bad token")),
+        };
+        let service =
+            HealthServer::with_interceptor(HealthService::from_health_reporter(reporter), auth);
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        tokio::spawn(
+            Server::builder()
+                .add_service(service)
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+        addr
+    }
+
+    /// Serves the health service behind a rustls listener, the TLS is terminated
+    /// and the streams are forwarded to the plain server.
+    async fn serve_tls(config: rustls::ServerConfig) -> String {
+        let upstream = serve().await;
+        let acceptor = TlsAcceptor::from(Arc::new(config));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let acceptor = acceptor.clone();
+                let upstream = upstream.clone();
+                tokio::spawn(async move {
+                    let Ok(mut stream) = acceptor.accept(stream).await else {
+                        return;
+                    };
+                    let mut upstream = TcpStream::connect(upstream).await.unwrap();
+                    let _ = tokio::io::copy_bidirectional(&mut stream, &mut upstream).await;
+                });
+            }
+        });
+        format!("localhost:{}", port)
+    }
+
+    async fn probe(host: &str, service: &str, token: &str) -> ProbeResult {
+        let mut p: GrpcProber = serde_yaml::from_str(&format!(
+            "name: grpc\nhost: {}\nservice: {}\nheaders:\n  X-Token: {}",
+            host, service, token
+        ))
+        .unwrap();
+        p.config(&ProbeSettings::default()).await.unwrap();
+        p.probe().await
+    }
+
+    #[tokio::test]
+    async fn test_grpc() {
+        let host = serve().await;
+
+        let r = probe(&host, "up", "easeprobe").await;
+        assert!(r.stat.status_counter.current_status, "{}", r.message);
+        assert!(r.message.contains("up is SERVING"));
+
+        let r = probe(&host, "down", "easeprobe").await;
+        assert!(!r.stat.status_counter.current_status);
+        assert!(r.message.contains("NOT_SERVING"), "{}", r.message);
+
+        let r = probe(&host, "unknown", "easeprobe").await;
+        assert!(!r.stat.status_counter.current_status);
+        assert!(r.message.contains("NotFound"), "{}", r.message);
+
+        let r = probe(&host, "up", "bad").await;
+        assert!(!r.stat.status_counter.current_status);
+        assert!(r.message.contains("Unauthenticated"), "{}", r.message);
+    }
+
+    #[tokio::test]
+    async fn test_grpc_tls() {
+        let (ca, mut config) = tls_certs();
+        config.alpn_protocols = vec![b"h2".to_vec()];
+        let host = serve_tls(config).await;
+        let dir = std::env::temp_dir().join(format!("easeprobe-grpc-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let ca_file = dir.join("ca.pem");
+        std::fs::write(&ca_file, ca).unwrap();
+        let other_file = dir.join("other.pem");
+        std::fs::write(&other_file, tls_certs().0).unwrap();
+
+        let probe = |ca: &std::path::Path| {
+            let yaml = format!(
+                "name: grpc\nhost: {}\nservice: up\nca: {}\nheaders:\n  X-Token: easeprobe",
+                host,
+                ca.display()
+            );
+            async move {
+                let mut p: GrpcProber = serde_yaml::from_str(&yaml).unwrap();
+                p.config(&ProbeSettings::default()).await.unwrap();
+                assert_eq!(p.default_prober.tag, "https");
+                p.probe().await
+            }
+        };
+
+        // trusted by the CA
+        let r = probe(&ca_file).await;
+        assert!(r.stat.status_counter.current_status, "{}", r.message);
+        assert!(r.message.contains("up is SERVING"));
+
+        // the certificate is not signed by the other CA
+        let r = probe(&other_file).await;
+        assert!(!r.stat.status_counter.current_status);
+        assert!(r.message.contains("Unavailable"), "{}", r.message);
+        assert!(r.message.contains("InvalidCertificate"), "{}", r.message);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -75,6 +75,7 @@ impl ProbeBehavior for HttpProbeBehavior {
 }
 
 impl HttpProber {
+    #[allow(dead_code)]
     pub fn new(
         name: &str,
         url: &str,
@@ -161,7 +162,7 @@ impl Prober for HttpProber {
 
         // proxy server
         if let Some(proxy_url) = &b.proxy {
-            if let Err(err) = Url::parse(&proxy_url) {
+            if let Err(err) = Url::parse(proxy_url) {
                 log::error!(
                     "[{} / {}] proxy URL is not valid - {} url={}",
                     self.kind(),
@@ -12,8 +12,22 @@ mod base;
 pub use base::*;
 mod http;
 pub use http::*;
+mod client;
+pub use client::*;
+mod dns;
+pub use dns::*;
+mod grpc;
+pub use grpc::*;
+mod websocket;
+pub use websocket::*;
 mod status_counter;
 pub use status_counter::*;
+mod data;
+pub use data::*;
+mod snapshot;
+pub use snapshot::*;
+#[cfg(test)]
+pub(crate) mod testing;
 
 use crate::ProbeSettings;
 
@@ -10,6 +10,9 @@ use super::{NotificationStrategyData, Status, StatusCounter};
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ProbeResult {
     pub name: String,
+    /// The kind of the prober, e.g. `http`
+    #[serde(default)]
+    pub kind: String,
     pub endpoint: String,
     pub start_time: SystemTime,
     pub start_timestamp: u128,
@@ -26,6 +29,7 @@ impl Default for ProbeResult {
     fn default() -> Self {
         Self {
             name: Default::default(),
+            kind: Default::default(),
             endpoint: Default::default(),
             start_time: SystemTime::now(),
             start_timestamp: Default::default(),
@@ -65,6 +69,9 @@ pub struct Stat {
     pub status: HashMap<Status, i64>,
     pub uptime: Duration,
     pub downtime: Duration,
+    /// The time of the latest status change
+    #[serde(default = "SystemTime::now")]
+    pub last_change: SystemTime,
     pub notification_strategy_data: NotificationStrategyData,
     pub status_counter: StatusCounter,
 }
@@ -77,8 +84,22 @@ impl Default for Stat {
             status: Default::default(),
             uptime: Default::default(),
             downtime: Default::default(),
+            last_change: SystemTime::now(),
             notification_strategy_data: Default::default(),
             status_counter: StatusCounter::new(10),
         }
     }
 }
+
+impl Stat {
+    /// Records a probe of the status, the elapsed time is counted as the uptime or downtime.
+    pub fn record(&mut self, status: Status, elapsed: Duration) {
+        self.total += 1;
+        *self.status.entry(status).or_default() += 1;
+        if status == Status::Up {
+            self.uptime += elapsed;
+        } else {
+            self.downtime += elapsed;
+        }
+    }
+}
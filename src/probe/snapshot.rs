@@ -0,0 +1,113 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock, Weak},
+};
+
+use tokio::sync::RwLock;
+
+use super::{ProbeResult, Prober};
+
+/// The latest result of a prober, it's read without waiting for the prober being probed.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub result: ProbeResult,
+    pub channels: Vec<String>,
+}
+
+struct Entry {
+    /// It keeps the allocation of the prober, so the key is not reused by another prober
+    prober: Weak<RwLock<dyn Prober>>,
+    snapshot: Snapshot,
+}
+
+/// The snapshots by the address of the probers.
+static SNAPSHOTS: LazyLock<std::sync::RwLock<HashMap<usize, Entry>>> =
+    LazyLock::new(Default::default);
+
+fn key(prober: &Arc<RwLock<dyn Prober>>) -> usize {
+    Arc::as_ptr(prober) as *const () as usize
+}
+
+impl Snapshot {
+    /// Publishes the latest result of the prober, it's called after every probe.
+    pub fn publish(prober: &Arc<RwLock<dyn Prober>>, result: &ProbeResult, channels: Vec<String>) {
+        let mut snapshots = SNAPSHOTS.write().unwrap();
+        snapshots.retain(|_, e| e.prober.strong_count() > 0);
+        snapshots.insert(
+            key(prober),
+            Entry {
+                prober: Arc::downgrade(prober),
+                snapshot: Snapshot {
+                    result: result.clone(),
+                    channels,
+                },
+            },
+        );
+    }
+
+    /// Publishes the current results, e.g. the probers are configured and restored.
+    pub async fn publish_all(probers: &[Arc<RwLock<dyn Prober>>]) {
+        for p in probers {
+            let mut prober = p.write().await;
+            let channels = prober.channels();
+            Self::publish(p, prober.result(), channels);
+        }
+    }
+
+    /// The published snapshot of the prober, `None` if it's never published.
+    pub fn get(prober: &Arc<RwLock<dyn Prober>>) -> Option<Snapshot> {
+        SNAPSHOTS
+            .read()
+            .unwrap()
+            .get(&key(prober))
+            .map(|e| e.snapshot.clone())
+    }
+
+    /// The snapshot of the prober, it waits for the prober only if it's never published.
+    pub async fn of(prober: &Arc<RwLock<dyn Prober>>) -> Snapshot {
+        if let Some(snapshot) = Self::get(prober) {
+            return snapshot;
+        }
+        let mut p = prober.write().await;
+        Snapshot {
+            channels: p.channels(),
+            result: p.result().clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{channel::new_dummy_prober, Status};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_snapshot() {
+        let p: Arc<RwLock<dyn Prober>> = Arc::new(new_dummy_prober(
+            "http",
+            "",
+            "snapshot",
+            vec!["a".to_string()],
+        ));
+        assert!(Snapshot::get(&p).is_none());
+        assert_eq!(Snapshot::of(&p).await.channels, ["a"]);
+
+        Snapshot::publish_all(std::slice::from_ref(&p)).await;
+        let result = ProbeResult {
+            status: Status::Down,
+            ..Default::default()
+        };
+        Snapshot::publish(&p, &result, vec!["b".to_string()]);
+
+        // it's read while the prober is being probed
+        let _probing = p.write().await;
+        let snapshot = tokio::time::timeout(Duration::from_millis(100), Snapshot::of(&p))
+            .await
+            .unwrap();
+        assert_eq!(snapshot.result.status, Status::Down);
+        assert_eq!(snapshot.channels, ["b"]);
+    }
+}
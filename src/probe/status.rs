@@ -2,24 +2,19 @@ use std::fmt::Display;
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Status {
     Init,
     Up,
     Down,
+    #[default]
     Unknown,
     Bad,
 }
 
-impl Default for Status {
-    fn default() -> Self {
-        Self::Unknown
-    }
-}
-
 impl Display for Status {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.to_string())
+        write!(f, "{}", Status::to_string(*self))
     }
 }
 
@@ -34,7 +29,7 @@ impl Status {
         }
     }
 
-    pub fn to_string(&self) -> &str {
+    pub fn to_string(self) -> &'static str {
         match self {
             Status::Init => "init",
             Status::Up => "up",
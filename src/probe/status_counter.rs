@@ -45,6 +45,10 @@ impl StatusCounter {
         }
     }
 
+    pub fn max_len(&self) -> usize {
+        self.max_len
+    }
+
     pub fn set_max_len(&mut self, max_len: usize) {
         self.max_len = max_len;
 
@@ -0,0 +1,280 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use futures_util::{SinkExt, StreamExt};
+use regex::Regex;
+use rustls::ClientConfig;
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+use tokio_tungstenite::{
+    connect_async_tls_with_config,
+    tungstenite::{
+        client::IntoClientRequest,
+        http::{HeaderName, HeaderValue},
+        Message,
+    },
+    Connector,
+};
+
+use crate::{ProbeSettings, TLSConfig};
+
+use super::{DefaultProber, ProbeBehavior, ProbeResult, Prober};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WebSocketProber {
+    #[serde(flatten)]
+    pub default_prober: DefaultProber<WebSocketProbeBehavior>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WebSocketProbeBehavior {
+    /// The WebSocket URL, `ws://` or `wss://`
+    pub url: String,
+    /// The custom headers of the upgrade request
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// The text message sent after the handshake
+    #[serde(default)]
+    pub message: Option<String>,
+    /// The first reply must contain this string
+    #[serde(default)]
+    pub contain: String,
+    /// The first reply must match this regular expression
+    #[serde(default)]
+    pub regex: String,
+    #[serde(flatten)]
+    pub tls: TLSConfig,
+    #[serde(skip)]
+    pub matcher: Option<Regex>,
+    #[serde(skip)]
+    pub tls_config: Option<Arc<ClientConfig>>,
+}
+
+impl WebSocketProbeBehavior {
+    /// Returns true if the probe should wait for the first reply.
+    fn expect_reply(&self) -> bool {
+        self.message.is_some() || !self.contain.is_empty() || self.matcher.is_some()
+    }
+
+    /// Checks the first reply against `contain` and `regex`.
+    fn check_reply(&self, reply: &str) -> Result<(), String> {
+        if !self.contain.is_empty() && !reply.contains(&self.contain) {
+            return Err(format!("the reply doesn't contain `{}`", self.contain));
+        }
+        if let Some(re) = &self.matcher {
+            if !re.is_match(reply) {
+                return Err(format!("the reply doesn't match `{}`", re));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ProbeBehavior for WebSocketProbeBehavior {
+    async fn do_probe(&self) -> Result<(bool, String)> {
+        let mut request = self.url.as_str().into_client_request()?;
+        for (k, v) in &self.headers {
+            request.headers_mut().insert(
+                HeaderName::from_bytes(k.as_bytes())?,
+                HeaderValue::from_str(v)?,
+            );
+        }
+        let connector = self.tls_config.clone().map(Connector::Rustls);
+
+        let start = Instant::now();
+        let (mut ws, _) = connect_async_tls_with_config(request, None, true, connector).await?;
+        let handshake = start.elapsed();
+
+        if !self.expect_reply() {
+            let _ = ws.close(None).await;
+            return Ok((true, format!("WebSocket handshake {:?}", handshake)));
+        }
+
+        if let Some(message) = &self.message {
+            ws.send(Message::text(message.as_str())).await?;
+        }
+
+        let start = Instant::now();
+        let reply = loop {
+            match ws.next().await {
+                Some(Ok(Message::Text(t))) => break t.as_str().to_string(),
+                Some(Ok(Message::Binary(b))) => break String::from_utf8_lossy(&b).to_string(),
+                Some(Ok(Message::Close(frame))) => {
+                    return Ok((
+                        false,
+                        format!("WebSocket closed before the first message - {:?}", frame),
+                    ))
+                }
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => return Err(e.into()),
+                None => bail!("WebSocket closed before the first message"),
+            }
+        };
+        let first_message = start.elapsed();
+        let _ = ws.close(None).await;
+
+        let latency = format!(
+            "handshake {:?}, first message {:?}",
+            handshake, first_message
+        );
+        match self.check_reply(&reply) {
+            Ok(()) => Ok((true, format!("WebSocket {}", latency))),
+            Err(e) => Ok((false, format!("WebSocket {} - {}", latency, e))),
+        }
+    }
+}
+
+#[async_trait]
+impl Prober for WebSocketProber {
+    fn kind(&self) -> &str {
+        &self.default_prober.kind
+    }
+
+    fn name(&self) -> &str {
+        &self.default_prober.name
+    }
+
+    fn channels(&self) -> Vec<String> {
+        self.default_prober.channels.clone()
+    }
+
+    fn timeout(&self) -> &Duration {
+        &self.default_prober.timeout
+    }
+
+    fn interval(&self) -> &Duration {
+        &self.default_prober.interval
+    }
+
+    fn result(&mut self) -> &mut ProbeResult {
+        &mut self.default_prober.result
+    }
+
+    async fn probe(&mut self) -> ProbeResult {
+        self.default_prober.probe().await
+    }
+
+    async fn config(&mut self, setting: &ProbeSettings) -> Result<()> {
+        self.default_prober.kind = "websocket".to_string();
+        self.default_prober.result.endpoint = self.default_prober.behavior.url.clone();
+        self.default_prober.config(setting).await?;
+
+        let b = &mut self.default_prober.behavior;
+        if !b.url.starts_with("ws://") && !b.url.starts_with("wss://") {
+            log::error!(
+                "[{} / {}] url must start with ws:// or wss:// - {}",
+                self.default_prober.kind,
+                self.default_prober.name,
+                b.url
+            );
+            bail!("invalid url {}", b.url)
+        }
+
+        if !b.regex.is_empty() {
+            b.matcher =
+                Some(Regex::new(&b.regex).with_context(|| format!("invalid regex {}", b.regex))?);
+        }
+
+        if b.tls.is_enabled() {
+            b.tls.check()?;
+            b.tls_config = Some(b.tls.rustls_config()?);
+        } else if b.url.starts_with("wss://") {
+            b.tls_config = Some(TLSConfig::default().rustls_config()?);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::{
+        accept_hdr_async,
+        tungstenite::handshake::server::{ErrorResponse, Request, Response},
+    };
+
+    use super::*;
+
+    /// Serves an echo WebSocket server, which requires the `x-token: easeprobe` header.
+    async fn serve() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    // the handshake callback signature is defined by tungstenite
+                    #[allow(clippy::result_large_err)]
+                    let auth = |req: &Request, resp: Response| match req.headers().get("x-token") {
+                        Some(t) if t == "easeprobe" => Ok(resp),
+                        _ => Err(ErrorResponse::new(Some("bad token".to_string()))),
+                    };
+                    let Ok(mut ws) = accept_hdr_async(stream, auth).await else {
+                        return;
+                    };
+                    while let Some(Ok(msg)) = ws.next().await {
+                        if msg.is_text() && ws.send(msg).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        format!("ws://{}", addr)
+    }
+
+    async fn probe(yaml: &str) -> ProbeResult {
+        let mut p: WebSocketProber = serde_yaml::from_str(yaml).unwrap();
+        p.config(&ProbeSettings::default()).await.unwrap();
+        p.probe().await
+    }
+
+    #[tokio::test]
+    async fn test_websocket() {
+        let url = serve().await;
+        let yaml = |extra: &str| {
+            format!(
+                "name: ws\nurl: {}\nheaders:\n  X-Token: easeprobe\n{}",
+                url, extra
+            )
+        };
+
+        let r = probe(&yaml("")).await;
+        assert!(r.stat.status_counter.current_status, "{}", r.message);
+        assert!(r.message.contains("WebSocket handshake"), "{}", r.message);
+
+        let r = probe(&yaml(
+            "message: ping easeprobe\ncontain: easeprobe\nregex: ^ping",
+        ))
+        .await;
+        assert!(r.stat.status_counter.current_status, "{}", r.message);
+        assert!(r.message.contains("first message"));
+
+        let r = probe(&yaml("message: ping\ncontain: pong")).await;
+        assert!(!r.stat.status_counter.current_status);
+        assert!(
+            r.message.contains("doesn't contain `pong`"),
+            "{}",
+            r.message
+        );
+
+        let r = probe(&yaml("message: ping\nregex: \"^\\\\d+$\"")).await;
+        assert!(!r.stat.status_counter.current_status);
+        assert!(r.message.contains("doesn't match"), "{}", r.message);
+
+        let r = probe(&format!("name: ws\nurl: {}", url)).await;
+        assert!(!r.stat.status_counter.current_status);
+    }
+
+    #[tokio::test]
+    async fn test_websocket_config() {
+        let mut p: WebSocketProber = serde_yaml::from_str("name: ws\nurl: http://x").unwrap();
+        assert!(p.config(&ProbeSettings::default()).await.is_err());
+
+        let mut p: WebSocketProber =
+            serde_yaml::from_str("name: ws\nurl: ws://x\nregex: \"(\"").unwrap();
+        assert!(p.config(&ProbeSettings::default()).await.is_err());
+    }
+}
@@ -1,7 +1,16 @@
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
 use anyhow::Result;
+use tokio::sync::RwLock;
+
+use crate::{metric, ProbeResult, Prober, Snapshot, Stat, Status};
 
 pub fn log_send(kind: &str, name: &str, tag: &str, msg: &str, err: Result<()>) {
     let msg = if msg.is_empty() { "  " } else { msg };
+    metric::observe_send(kind, name, tag, err.is_ok());
 
     match err {
         Ok(()) => log::info!(
@@ -21,3 +30,186 @@ pub fn log_send(kind: &str, name: &str, tag: &str, msg: &str, err: Result<()>) {
         ),
     }
 }
+
+/// The latest results of the probers from the snapshots, the probers being probed are not waited for.
+pub(crate) fn probe_results(probers: &[Arc<RwLock<dyn Prober>>]) -> Vec<ProbeResult> {
+    probers
+        .iter()
+        .filter_map(|p| {
+            if let Some(snapshot) = Snapshot::get(p) {
+                return Some(snapshot.result);
+            }
+            // the prober is never published, e.g. it's not run by the `run_probers`
+            match p.try_write() {
+                Ok(mut p) => Some(p.result().clone()),
+                Err(_) => {
+                    log::warn!("The result of a prober is not published yet, it's skipped");
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// The SLA percentage of the prober, 100% if there's no data yet.
+pub(crate) fn sla_percent(stat: &Stat) -> f64 {
+    let total = stat.uptime + stat.downtime;
+    if total.is_zero() {
+        return 100.0;
+    }
+    stat.uptime.as_secs_f64() / total.as_secs_f64() * 100.0
+}
+
+/// The SLA statistics of a prober.
+#[derive(Debug, Clone)]
+pub(crate) struct SlaStat {
+    pub name: String,
+    pub kind: String,
+    pub endpoint: String,
+    pub status: Status,
+    pub sla: f64,
+    pub total: i64,
+    /// The probes of each status, in the order of `STATUSES`
+    pub counts: Vec<(Status, i64)>,
+    pub downtime: Duration,
+    pub last_change: SystemTime,
+}
+
+/// The statuses in the order of the reports.
+pub(crate) const STATUSES: [Status; 5] = [
+    Status::Up,
+    Status::Down,
+    Status::Bad,
+    Status::Unknown,
+    Status::Init,
+];
+
+impl SlaStat {
+    pub(crate) fn new(r: &ProbeResult) -> Self {
+        Self {
+            name: r.name.clone(),
+            kind: r.kind.clone(),
+            endpoint: r.endpoint.clone(),
+            status: r.status,
+            sla: sla_percent(&r.stat),
+            total: r.stat.total,
+            counts: STATUSES
+                .iter()
+                .map(|s| (*s, r.stat.status.get(s).copied().unwrap_or_default()))
+                .collect(),
+            downtime: r.stat.downtime,
+            last_change: r.stat.last_change,
+        }
+    }
+
+    /// The non-zero counts, e.g. `up 9, down 1`.
+    pub(crate) fn counts_string(&self) -> String {
+        let counts: Vec<_> = self
+            .counts
+            .iter()
+            .filter(|(_, n)| *n > 0)
+            .map(|(s, n)| format!("{} {}", s, n))
+            .collect();
+        if counts.is_empty() {
+            "-".to_string()
+        } else {
+            counts.join(", ")
+        }
+    }
+}
+
+/// The summary of all the probers.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SlaSummary {
+    pub probers: usize,
+    pub up: usize,
+    pub down: usize,
+    pub other: usize,
+    /// The SLA of the total uptime and downtime of all the probers
+    pub sla: f64,
+}
+
+impl SlaSummary {
+    pub(crate) fn new(results: &[ProbeResult]) -> Self {
+        let count = |s: Status| results.iter().filter(|r| r.status == s).count();
+        let (up, down) = (count(Status::Up), count(Status::Down));
+        let total = Stat {
+            uptime: results.iter().map(|r| r.stat.uptime).sum(),
+            downtime: results.iter().map(|r| r.stat.downtime).sum(),
+            ..Default::default()
+        };
+        Self {
+            probers: results.len(),
+            up,
+            down,
+            other: results.len() - up - down,
+            sla: sla_percent(&total),
+        }
+    }
+}
+
+impl std::fmt::Display for SlaSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} prober{} - {} up, {} down, {} other - overall SLA {:.2}%",
+            self.probers,
+            if self.probers == 1 { "" } else { "s" },
+            self.up,
+            self.down,
+            self.other,
+            self.sla
+        )
+    }
+}
+
+/// Formats the duration in days, hours, minutes and seconds, e.g. `1d 2h 0m 5s`.
+pub(crate) fn format_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    let (days, hours, mins, secs) = (
+        secs / 86400,
+        secs % 86400 / 3600,
+        secs % 3600 / 60,
+        secs % 60,
+    );
+    if days > 0 {
+        format!("{}d {}h {}m {}s", days, hours, mins, secs)
+    } else if hours > 0 {
+        format!("{}h {}m {}s", hours, mins, secs)
+    } else if mins > 0 {
+        format!("{}m {}s", mins, secs)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+/// Escapes the reserved chars of the social markdown (Telegram MarkdownV2).
+pub(crate) fn escape_markdown_social(s: &str) -> String {
+    const RESERVED: &str = "\\_*[]()~`>#+-=|{}.!";
+    let mut e = String::with_capacity(s.len());
+    for c in s.chars() {
+        if RESERVED.contains(c) {
+            e.push('\\');
+        }
+        e.push(c);
+    }
+    e
+}
+
+/// Escapes the HTML special chars.
+pub(crate) fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Truncates the text to the max number of chars, with `…` at the end if truncated.
+pub fn truncate(s: &str, max: usize) -> String {
+    if s.chars().count() <= max {
+        return s.to_string();
+    }
+    let mut t: String = s.chars().take(max - 1).collect();
+    t.push('…');
+    t
+}
@@ -10,8 +10,10 @@ mod result;
 use result::*;
 mod common;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Format {
+    #[default]
     Unknown,
     MarkdownSocial, // *text* is bold
     Markdown,       // **text** is bold
@@ -25,15 +27,10 @@ pub enum Format {
     SMS,
     Shell,
 }
-impl Default for Format {
-    fn default() -> Self {
-        Self::Unknown
-    }
-}
 
 impl Format {
     #[allow(dead_code)]
-    fn to_string(&self) -> &'static str {
+    fn to_string(self) -> &'static str {
         match self {
             Format::Unknown => "unknown",
             Format::MarkdownSocial => "markdown-social",
@@ -88,6 +85,237 @@ pub static FORMAT_FUNCS: LazyLock<HashMap<Format, FormatFuncStruct>> = LazyLock:
             stat_fn: sla_text,
         },
     );
+    m.insert(
+        Format::Text,
+        FormatFuncStruct {
+            result_fn: to_text,
+            stat_fn: sla_text,
+        },
+    );
+    m.insert(
+        Format::JSON,
+        FormatFuncStruct {
+            result_fn: to_json,
+            stat_fn: sla_json,
+        },
+    );
+    m.insert(
+        Format::Log,
+        FormatFuncStruct {
+            result_fn: to_log,
+            stat_fn: sla_log,
+        },
+    );
+    m.insert(
+        Format::SMS,
+        FormatFuncStruct {
+            result_fn: to_sms,
+            stat_fn: sla_sms,
+        },
+    );
+    m.insert(
+        Format::Slack,
+        FormatFuncStruct {
+            result_fn: to_slack,
+            stat_fn: sla_slack,
+        },
+    );
+    m.insert(
+        Format::Discord,
+        FormatFuncStruct {
+            result_fn: to_discord,
+            stat_fn: sla_discord,
+        },
+    );
+    m.insert(
+        Format::MarkdownSocial,
+        FormatFuncStruct {
+            result_fn: to_markdown_social,
+            stat_fn: sla_markdown_social,
+        },
+    );
+    m.insert(
+        Format::HTML,
+        FormatFuncStruct {
+            result_fn: to_html,
+            stat_fn: sla_html,
+        },
+    );
+    m.insert(
+        Format::Markdown,
+        FormatFuncStruct {
+            result_fn: to_markdown,
+            stat_fn: sla_markdown,
+        },
+    );
+    m.insert(
+        Format::Lark,
+        FormatFuncStruct {
+            result_fn: to_lark,
+            stat_fn: sla_lark,
+        },
+    );
+    m.insert(
+        Format::Shell,
+        FormatFuncStruct {
+            result_fn: to_shell,
+            stat_fn: sla_csv,
+        },
+    );
 
     m
 });
+
+/// Returns the formatters of the format, the unregistered one falls back to the text.
+pub fn format_funcs(format: Format) -> &'static FormatFuncStruct {
+    FORMAT_FUNCS.get(&format).unwrap_or_else(|| {
+        log::warn!(
+            "The format [{}] is not supported, fallback to the text",
+            format.to_string()
+        );
+        &FORMAT_FUNCS[&Format::Unknown]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        path::PathBuf,
+        time::{Duration, SystemTime},
+    };
+
+    use regex::Regex;
+
+    use super::*;
+    use crate::{channel::new_dummy_prober, Status};
+
+    const FORMATS: [Format; 12] = [
+        Format::Unknown,
+        Format::MarkdownSocial,
+        Format::Markdown,
+        Format::HTML,
+        Format::JSON,
+        Format::Text,
+        Format::Log,
+        Format::Slack,
+        Format::Discord,
+        Format::Lark,
+        Format::SMS,
+        Format::Shell,
+    ];
+
+    fn result(name: &str, status: Status, uptime: u64, downtime: u64) -> ProbeResult {
+        let mut r = ProbeResult {
+            name: name.to_string(),
+            kind: "http".to_string(),
+            endpoint: format!("https://{}.example.com", name.to_lowercase()),
+            start_time: SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+            round_trip_time: Duration::from_millis(42),
+            status,
+            pre_status: Status::Up,
+            message: "Error (http): HTTP Status Code is 500 <Internal Server Error>".to_string(),
+            ..Default::default()
+        };
+        // probed every 10 seconds
+        r.stat.uptime = Duration::from_secs(uptime);
+        r.stat.downtime = Duration::from_secs(downtime);
+        r.stat.status = HashMap::from([
+            (Status::Up, (uptime / 10) as i64),
+            (Status::Down, (downtime / 10) as i64),
+        ]);
+        r.stat.total = ((uptime + downtime) / 10) as i64;
+        r.stat.last_change = r.start_time;
+        r
+    }
+
+    async fn probers() -> Vec<Arc<RwLock<dyn Prober>>> {
+        let mut probers: Vec<Arc<RwLock<dyn Prober>>> = Vec::new();
+        for r in [
+            result("Web", Status::Up, 86390, 10),
+            result("API|v2", Status::Down, 3000, 3730),
+        ] {
+            let p = Arc::new(new_dummy_prober("http", "", &r.name, vec![]));
+            *p.write().await.result() = r;
+            probers.push(p);
+        }
+        probers
+    }
+
+    /// Masks the times (maybe escaped), they depend on the clock and the local time zone.
+    fn mask_time(s: &str) -> String {
+        let re =
+            Regex::new(r"\d{4}\\?-\d{2}\\?-\d{2}[ T]\d{2}:\d{2}:\d{2}(\.\d+)?(Z|[+-]\d{2}:\d{2})?")
+                .unwrap();
+        re.replace_all(s, "<TIME>").to_string()
+    }
+
+    /// Compares with the golden file, `UPDATE_GOLDEN=1` rewrites the golden files.
+    fn check_golden(name: &str, actual: &str) {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("src/report/testdata")
+            .join(name);
+        let actual = mask_time(actual);
+        if std::env::var("UPDATE_GOLDEN").is_ok() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, &actual).unwrap();
+        }
+        let expected = std::fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("failed to read {}: {}", path.display(), e));
+        assert_eq!(actual, expected, "golden file {}", name);
+    }
+
+    #[tokio::test]
+    async fn test_golden() {
+        for format in FORMATS {
+            let funcs = format_funcs(format);
+            let r = Arc::new(result("Web", Status::Down, 0, 0));
+            check_golden(
+                &format!("{}.result.golden", format.to_string()),
+                &(funcs.result_fn)(r),
+            );
+            check_golden(
+                &format!("{}.sla.golden", format.to_string()),
+                &(funcs.stat_fn)(probers().await),
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sla_stat() {
+        let results = probe_results(&probers().await);
+        let s = SlaStat::new(&results[1]);
+        assert_eq!(s.total, 673);
+        assert_eq!(s.counts_string(), "up 300, down 373");
+        assert_eq!(format_duration(s.downtime), "1h 2m 10s");
+        assert_eq!(format_duration(Duration::from_secs(90061)), "1d 1h 1m 1s");
+        assert_eq!(format_duration(Duration::ZERO), "0s");
+
+        let summary = SlaSummary::new(&results);
+        assert_eq!((summary.probers, summary.up, summary.down), (2, 1, 1));
+        // (86390 + 3000) / (86400 + 6730)
+        assert_eq!(
+            summary.to_string(),
+            "2 probers - 1 up, 1 down, 0 other - overall SLA 95.98%"
+        );
+        assert_eq!(SlaStat::new(&ProbeResult::default()).counts_string(), "-");
+    }
+
+    #[tokio::test]
+    async fn test_probe_results_while_probing() {
+        let probers = probers().await;
+        crate::Snapshot::publish_all(&probers).await;
+        // the prober is being probed
+        let _probing = probers[0].write().await;
+        let results = probe_results(&probers);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].name, "Web");
+    }
+
+    #[test]
+    fn test_format_funcs() {
+        for format in FORMATS {
+            assert!(FORMAT_FUNCS.contains_key(&format), "{:?}", format);
+            assert_eq!(Format::from_string(format.to_string()), format);
+        }
+    }
+}
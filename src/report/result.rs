@@ -1,7 +1,12 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::SystemTime};
 
+use serde_json::json;
 use tokio::sync::RwLock;
 
+use super::{
+    escape_html, escape_markdown_social, format_duration, probe_results, sla_percent, truncate,
+    SlaStat, SlaSummary,
+};
 use crate::{global, probe, Prober};
 
 pub(crate) fn to_text(r: Arc<probe::ProbeResult>) -> String {
@@ -17,6 +22,604 @@ pub(crate) fn to_text(r: Arc<probe::ProbeResult>) -> String {
         global::format_time(r.start_time)
     )
 }
-pub(crate) fn sla_text(_: Vec<Arc<RwLock<dyn Prober>>>) -> String {
-    format!("{}", "[Overall SLA Report]\n\n")
+/// Renders the SLA report with one paragraph per prober and the summary line.
+pub(crate) fn sla_text(probers: Vec<Arc<RwLock<dyn Prober>>>) -> String {
+    let results = probe_results(&probers);
+    let mut text = "[Overall SLA Report]\n".to_string();
+    for s in results.iter().map(SlaStat::new) {
+        text.push_str(&format!(
+            "\n{} {} ({}) - {}\nSLA: {:.2}% - Probes: {} ({}) - Downtime: {}\nStatus: {} since {}\n",
+            s.status.emoji(),
+            s.name,
+            s.kind,
+            s.endpoint,
+            s.sla,
+            s.total,
+            s.counts_string(),
+            format_duration(s.downtime),
+            s.status,
+            global::format_time(s.last_change)
+        ));
+    }
+    text.push_str(&format!(
+        "\n{}\n\n{} at {}",
+        SlaSummary::new(&results),
+        global::footer_string(),
+        global::format_time(SystemTime::now())
+    ));
+    text
+}
+
+/// The probe result in JSON, the time is in RFC 3339 and the RTT is in milliseconds.
+fn result_json(r: &probe::ProbeResult) -> serde_json::Value {
+    let time = chrono::DateTime::<chrono::Utc>::from(r.start_time);
+    json!({
+        "name": r.name,
+        "kind": r.kind,
+        "endpoint": r.endpoint,
+        "status": r.status.to_string(),
+        "pre_status": r.pre_status.to_string(),
+        "title": r.title(),
+        "message": r.message,
+        "rtt": r.round_trip_time.as_millis() as u64,
+        "time": time.to_rfc3339(),
+        "timestamp": time.timestamp(),
+    })
+}
+
+pub(crate) fn to_json(r: Arc<probe::ProbeResult>) -> String {
+    result_json(&r).to_string()
+}
+
+pub(crate) fn sla_json(probers: Vec<Arc<RwLock<dyn Prober>>>) -> String {
+    let results = probe_results(&probers);
+    let probers: Vec<_> = results
+        .iter()
+        .map(SlaStat::new)
+        .map(|s| {
+            let counts: serde_json::Map<_, _> = s
+                .counts
+                .iter()
+                .map(|(status, n)| (status.to_string().to_owned(), json!(n)))
+                .collect();
+            json!({
+                "name": s.name,
+                "kind": s.kind,
+                "endpoint": s.endpoint,
+                "status": s.status.to_string(),
+                "sla": round2(s.sla),
+                "total": s.total,
+                "counts": counts,
+                "downtime": s.downtime.as_secs(),
+                "last_change": chrono::DateTime::<chrono::Utc>::from(s.last_change).to_rfc3339(),
+            })
+        })
+        .collect();
+    let summary = SlaSummary::new(&results);
+    json!({
+        "title": "Overall SLA Report",
+        "probers": probers,
+        "summary": {
+            "probers": summary.probers,
+            "up": summary.up,
+            "down": summary.down,
+            "other": summary.other,
+            "sla": round2(summary.sla),
+        },
+        "time": chrono::Utc::now().to_rfc3339(),
+    })
+    .to_string()
+}
+
+fn round2(f: f64) -> f64 {
+    (f * 100.0).round() / 100.0
+}
+
+/// Renders the probe result in one line, the fields are separated by `; `.
+pub(crate) fn to_log(r: Arc<probe::ProbeResult>) -> String {
+    format!(
+        "{}; {}; {}; {}; {}ms; {}",
+        r.title(),
+        global::format_time(r.start_time),
+        r.endpoint,
+        r.status,
+        r.round_trip_time.as_millis(),
+        r.message.replace('\n', " ")
+    )
+}
+
+/// Renders the SLA report with one line per prober.
+pub(crate) fn sla_log(probers: Vec<Arc<RwLock<dyn Prober>>>) -> String {
+    let time = global::format_time(SystemTime::now());
+    probe_results(&probers)
+        .iter()
+        .map(|r| {
+            format!(
+                "SLA; {}; {}; {}; {}; {:.2}%",
+                time,
+                r.name,
+                r.endpoint,
+                r.status,
+                sla_percent(&r.stat)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The max number of chars of the SMS.
+const SMS_MAX: usize = 140;
+
+/// Renders the probe result as the short text of the SMS.
+pub(crate) fn to_sms(r: Arc<probe::ProbeResult>) -> String {
+    truncate(
+        &format!(
+            "{} {} at {}: {}",
+            r.status.emoji(),
+            r.title(),
+            global::format_time(r.start_time),
+            r.message
+        ),
+        SMS_MAX,
+    )
+}
+
+/// Renders the SLA report as the short summary of the SMS.
+pub(crate) fn sla_sms(probers: Vec<Arc<RwLock<dyn Prober>>>) -> String {
+    let results = probe_results(&probers);
+    let up = results
+        .iter()
+        .filter(|r| r.status == probe::Status::Up)
+        .count();
+    let mut sms = format!("SLA Report: {}/{} up", up, results.len());
+    for r in results.iter().filter(|r| r.status != probe::Status::Up) {
+        sms.push_str(&format!(
+            "; {} {} {:.2}%",
+            r.name,
+            r.status,
+            sla_percent(&r.stat)
+        ));
+    }
+    truncate(&sms, SMS_MAX)
+}
+
+/// The max length of the text in a Slack header block.
+const SLACK_HEADER_MAX: usize = 150;
+/// The max length of the text in a Slack section block.
+const SLACK_TEXT_MAX: usize = 3000;
+/// The max length of the text in a Slack section field.
+const SLACK_FIELD_MAX: usize = 2000;
+/// The max number of fields in a Slack section block.
+const SLACK_FIELDS_MAX: usize = 10;
+
+/// Escapes the control characters of the Slack `mrkdwn` text.
+fn slack_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn slack_mrkdwn(text: &str) -> serde_json::Value {
+    json!({ "type": "mrkdwn", "text": truncate(text, SLACK_TEXT_MAX) })
+}
+
+fn slack_field(text: &str) -> serde_json::Value {
+    json!({ "type": "mrkdwn", "text": truncate(text, SLACK_FIELD_MAX) })
+}
+
+fn slack_footer(time: SystemTime) -> serde_json::Value {
+    json!({
+        "type": "context",
+        "elements": [slack_mrkdwn(&format!(
+            "{} at {}",
+            global::footer_string(),
+            global::format_time(time)
+        ))],
+    })
+}
+
+/// Renders the probe result as the Slack Block Kit message.
+pub(crate) fn to_slack(r: Arc<probe::ProbeResult>) -> String {
+    let title = format!("{} {}", r.status.emoji(), r.title());
+    json!({
+        "text": title,
+        "blocks": [
+            {
+                "type": "header",
+                "text": { "type": "plain_text", "text": truncate(&title, SLACK_HEADER_MAX) },
+            },
+            {
+                "type": "section",
+                "text": slack_mrkdwn(&slack_escape(&r.message)),
+                "fields": [
+                    slack_field(&format!("*Endpoint*\n{}", slack_escape(&r.endpoint))),
+                    slack_field(&format!("*RTT*\n⏱ {}ms", r.round_trip_time.as_millis())),
+                ],
+            },
+            slack_footer(r.start_time),
+        ],
+    })
+    .to_string()
+}
+
+/// Renders the SLA report as the Slack Block Kit message, one field per prober.
+pub(crate) fn sla_slack(probers: Vec<Arc<RwLock<dyn Prober>>>) -> String {
+    let title = "Overall SLA Report";
+    let results = probe_results(&probers);
+    let fields: Vec<_> = results
+        .iter()
+        .map(|r| {
+            slack_field(&format!(
+                "*{}* {}\n{}\nSLA: {:.2}%",
+                slack_escape(&r.name),
+                r.status.emoji(),
+                slack_escape(&r.endpoint),
+                sla_percent(&r.stat)
+            ))
+        })
+        .collect();
+
+    let mut blocks = vec![json!({
+        "type": "header",
+        "text": { "type": "plain_text", "text": title },
+    })];
+    // the blocks over the limit of a message are split by the Slack notifier
+    for chunk in fields.chunks(SLACK_FIELDS_MAX) {
+        blocks.push(json!({ "type": "section", "fields": chunk }));
+    }
+    blocks.push(slack_footer(SystemTime::now()));
+
+    json!({ "text": title, "blocks": blocks }).to_string()
+}
+
+/// The max number of fields in a Discord embed.
+const DISCORD_FIELDS_MAX: usize = 25;
+/// The max length of a Discord embed field value.
+const DISCORD_FIELD_VALUE_MAX: usize = 1024;
+
+/// The Discord embed color of the status.
+fn discord_color(status: probe::Status) -> u32 {
+    match status {
+        probe::Status::Init => 0x3498DB,
+        probe::Status::Up => 0x2ECC71,
+        probe::Status::Down => 0xE74C3C,
+        probe::Status::Unknown => 0x95A5A6,
+        probe::Status::Bad => 0xE67E22,
+    }
+}
+
+fn discord_field(name: &str, value: &str, inline: bool) -> serde_json::Value {
+    // Discord rejects the empty field value
+    let value = if value.is_empty() { "-" } else { value };
+    json!({
+        "name": name,
+        "value": truncate(value, DISCORD_FIELD_VALUE_MAX),
+        "inline": inline,
+    })
+}
+
+fn discord_timestamp(time: SystemTime) -> String {
+    chrono::DateTime::<chrono::Utc>::from(time).to_rfc3339()
+}
+
+/// Renders the probe result as the Discord webhook message with one embed.
+pub(crate) fn to_discord(r: Arc<probe::ProbeResult>) -> String {
+    json!({
+        "embeds": [{
+            "title": format!("{} {}", r.status.emoji(), r.title()),
+            "color": discord_color(r.status),
+            "fields": [
+                discord_field("Endpoint", &r.endpoint, true),
+                discord_field("RTT", &format!("⏱ {}ms", r.round_trip_time.as_millis()), true),
+                discord_field("Time", &global::format_time(r.start_time), true),
+                discord_field("Message", &r.message, false),
+            ],
+            "footer": { "text": global::footer_string() },
+            "timestamp": discord_timestamp(r.start_time),
+        }],
+    })
+    .to_string()
+}
+
+/// Renders the SLA report as the Discord webhook message, split into embeds of 25 fields.
+pub(crate) fn sla_discord(probers: Vec<Arc<RwLock<dyn Prober>>>) -> String {
+    let results = probe_results(&probers);
+    let fields: Vec<_> = results
+        .iter()
+        .map(|r| {
+            discord_field(
+                &format!("{} {}", r.status.emoji(), r.name),
+                &format!("{}\nSLA: {:.2}%", r.endpoint, sla_percent(&r.stat)),
+                true,
+            )
+        })
+        .collect();
+
+    let now = SystemTime::now();
+    let pages = fields.len().div_ceil(DISCORD_FIELDS_MAX).max(1);
+    let embeds: Vec<_> = (0..pages)
+        .map(|i| {
+            let chunk: Vec<_> = fields
+                .iter()
+                .skip(i * DISCORD_FIELDS_MAX)
+                .take(DISCORD_FIELDS_MAX)
+                .collect();
+            let title = if pages > 1 {
+                format!("Overall SLA Report ({}/{})", i + 1, pages)
+            } else {
+                "Overall SLA Report".to_string()
+            };
+            json!({
+                "title": title,
+                "color": discord_color(probe::Status::Init),
+                "fields": chunk,
+                "footer": { "text": global::footer_string() },
+                "timestamp": discord_timestamp(now),
+            })
+        })
+        .collect();
+
+    json!({ "embeds": embeds }).to_string()
+}
+
+/// Renders the probe result as the social markdown, `*text*` is bold.
+pub(crate) fn to_markdown_social(r: Arc<probe::ProbeResult>) -> String {
+    let e = escape_markdown_social;
+    format!(
+        "*{}* {}\n{} \\- ⏱ {}ms\n{}\n_{}_",
+        e(&r.title()),
+        r.status.emoji(),
+        e(&r.endpoint),
+        r.round_trip_time.as_millis(),
+        e(&r.message),
+        e(&format!(
+            "{} at {}",
+            global::footer_string(),
+            global::format_time(r.start_time)
+        ))
+    )
+}
+
+/// Renders the SLA report as the social markdown.
+pub(crate) fn sla_markdown_social(probers: Vec<Arc<RwLock<dyn Prober>>>) -> String {
+    let e = escape_markdown_social;
+    let mut md = "*Overall SLA Report*\n".to_string();
+    for r in probe_results(&probers) {
+        md.push_str(&format!(
+            "\n*{}* {}\n{}\n{}\n",
+            e(&r.name),
+            r.status.emoji(),
+            e(&r.endpoint),
+            e(&format!("SLA: {:.2}%", sla_percent(&r.stat)))
+        ));
+    }
+    md.push_str(&format!(
+        "\n_{}_",
+        e(&format!(
+            "{} at {}",
+            global::footer_string(),
+            global::format_time(SystemTime::now())
+        ))
+    ));
+    md
+}
+
+/// Renders the probe result as the HTML fragment, the lines are separated by `\n`.
+pub(crate) fn to_html(r: Arc<probe::ProbeResult>) -> String {
+    let e = escape_html;
+    format!(
+        "<b>{}</b> {}\n{} - ⏱ {}ms\n{}\n<i>{} at {}</i>",
+        e(&r.title()),
+        r.status.emoji(),
+        e(&r.endpoint),
+        r.round_trip_time.as_millis(),
+        e(&r.message),
+        e(&global::footer_string()),
+        global::format_time(r.start_time)
+    )
+}
+
+/// Renders the SLA report as the HTML table.
+pub(crate) fn sla_html(probers: Vec<Arc<RwLock<dyn Prober>>>) -> String {
+    let e = escape_html;
+    let results = probe_results(&probers);
+    let mut html = "<b>Overall SLA Report</b>\n<table>\n\
+        <tr><th>Name</th><th>Kind</th><th>Endpoint</th><th>Status</th><th>SLA</th>\
+        <th>Probes</th><th>Downtime</th><th>Last Change</th></tr>\n"
+        .to_string();
+    for s in results.iter().map(SlaStat::new) {
+        html.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{} {}</td><td>{:.2}%</td>\
+            <td>{} ({})</td><td>{}</td><td>{}</td></tr>\n",
+            e(&s.name),
+            e(&s.kind),
+            e(&s.endpoint),
+            s.status.emoji(),
+            s.status,
+            s.sla,
+            s.total,
+            s.counts_string(),
+            format_duration(s.downtime),
+            global::format_time(s.last_change)
+        ));
+    }
+    html.push_str(&format!(
+        "</table>\n<p>{}</p>\n<i>{} at {}</i>",
+        e(&SlaSummary::new(&results).to_string()),
+        e(&global::footer_string()),
+        global::format_time(SystemTime::now())
+    ));
+    html
+}
+
+/// Escapes the markdown table cell.
+fn markdown_cell(s: &str) -> String {
+    s.replace('|', "\\|").replace('\n', " ")
+}
+
+/// Renders the probe result as the markdown, `**text**` is bold.
+pub(crate) fn to_markdown(r: Arc<probe::ProbeResult>) -> String {
+    format!(
+        "**{}** {}\n\n{} - ⏱ {}ms\n\n{}\n\n> {} at {}",
+        r.title(),
+        r.status.emoji(),
+        r.endpoint,
+        r.round_trip_time.as_millis(),
+        r.message,
+        global::footer_string(),
+        global::format_time(r.start_time)
+    )
+}
+
+/// Renders the SLA report as the markdown table.
+pub(crate) fn sla_markdown(probers: Vec<Arc<RwLock<dyn Prober>>>) -> String {
+    let results = probe_results(&probers);
+    let mut md = "**Overall SLA Report**\n\n\
+        | Name | Kind | Endpoint | Status | SLA | Probes | Downtime | Last Change |\n\
+        | --- | --- | --- | --- | --- | --- | --- | --- |\n"
+        .to_string();
+    for s in results.iter().map(SlaStat::new) {
+        md.push_str(&format!(
+            "| {} | {} | {} | {} {} | {:.2}% | {} ({}) | {} | {} |\n",
+            markdown_cell(&s.name),
+            markdown_cell(&s.kind),
+            markdown_cell(&s.endpoint),
+            s.status.emoji(),
+            s.status,
+            s.sla,
+            s.total,
+            s.counts_string(),
+            format_duration(s.downtime),
+            global::format_time(s.last_change)
+        ));
+    }
+    md.push_str(&format!(
+        "\n**{}**\n\n> {} at {}",
+        SlaSummary::new(&results),
+        global::footer_string(),
+        global::format_time(SystemTime::now())
+    ));
+    md
+}
+
+/// The Lark card header color of the status.
+fn lark_template(status: probe::Status) -> &'static str {
+    match status {
+        probe::Status::Init => "blue",
+        probe::Status::Up => "green",
+        probe::Status::Down => "red",
+        probe::Status::Unknown => "grey",
+        probe::Status::Bad => "orange",
+    }
+}
+
+fn lark_card(title: &str, template: &str, content: &str, time: SystemTime) -> String {
+    json!({
+        "msg_type": "interactive",
+        "card": {
+            "config": { "wide_screen_mode": true },
+            "header": {
+                "title": { "tag": "plain_text", "content": title },
+                "template": template,
+            },
+            "elements": [
+                { "tag": "div", "text": { "tag": "lark_md", "content": content } },
+                { "tag": "hr" },
+                {
+                    "tag": "note",
+                    "elements": [{
+                        "tag": "plain_text",
+                        "content": format!(
+                            "{} at {}",
+                            global::footer_string(),
+                            global::format_time(time)
+                        ),
+                    }],
+                },
+            ],
+        },
+    })
+    .to_string()
+}
+
+/// Renders the probe result as the Lark interactive card.
+pub(crate) fn to_lark(r: Arc<probe::ProbeResult>) -> String {
+    lark_card(
+        &format!("{} {}", r.status.emoji(), r.title()),
+        lark_template(r.status),
+        &format!(
+            "**Endpoint**: {}\n**RTT**: ⏱ {}ms\n{}",
+            r.endpoint,
+            r.round_trip_time.as_millis(),
+            r.message
+        ),
+        r.start_time,
+    )
+}
+
+/// Renders the SLA report as the Lark interactive card.
+pub(crate) fn sla_lark(probers: Vec<Arc<RwLock<dyn Prober>>>) -> String {
+    let content = probe_results(&probers)
+        .iter()
+        .map(|r| {
+            format!(
+                "**{}** {}\n{} - SLA: {:.2}%",
+                r.name,
+                r.status.emoji(),
+                r.endpoint,
+                sla_percent(&r.stat)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    lark_card(
+        "Overall SLA Report",
+        lark_template(probe::Status::Init),
+        &content,
+        SystemTime::now(),
+    )
+}
+
+/// The text piped to the stdin of the shell notifier.
+pub(crate) fn to_shell(r: Arc<probe::ProbeResult>) -> String {
+    to_text(r)
+}
+
+/// Quotes the CSV field if it has the separator, quote or line break.
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Renders the SLA report in CSV, one row per prober and the summary row `TOTAL`,
+/// it's piped to the stdin of the shell notifier.
+pub(crate) fn sla_csv(probers: Vec<Arc<RwLock<dyn Prober>>>) -> String {
+    let results = probe_results(&probers);
+    let mut csv =
+        "name,kind,endpoint,status,sla,total,up,down,bad,unknown,init,downtime,last_change\n"
+            .to_string();
+    for s in results.iter().map(SlaStat::new) {
+        let counts: Vec<_> = s.counts.iter().map(|(_, n)| n.to_string()).collect();
+        csv.push_str(&format!(
+            "{},{},{},{},{:.2},{},{},{},{}\n",
+            csv_field(&s.name),
+            csv_field(&s.kind),
+            csv_field(&s.endpoint),
+            s.status,
+            s.sla,
+            s.total,
+            counts.join(","),
+            s.downtime.as_secs(),
+            chrono::DateTime::<chrono::Utc>::from(s.last_change).to_rfc3339()
+        ));
+    }
+    let summary = SlaSummary::new(&results);
+    csv.push_str(&format!(
+        "TOTAL,,,{} up / {} down / {} other,{:.2},,,,,,,,\n",
+        summary.up, summary.down, summary.other, summary.sla
+    ));
+    csv
 }
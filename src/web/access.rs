@@ -0,0 +1,104 @@
+use std::{net::SocketAddr, sync::Arc, time::Instant};
+
+use axum::{
+    body::HttpBody,
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderMap},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::logger::LogWriter;
+
+fn header_or_dash(headers: &HeaderMap, name: header::HeaderName) -> String {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("-")
+        .to_string()
+}
+
+/// Writes the access log in the combined log format with the latency, e.g.
+/// `127.0.0.1 - - [02/Jan/2024:03:04:05 +0000] "GET / HTTP/1.1" 200 1024 "-" "curl/8.0" 1.234ms`.
+pub async fn access_log(State(log): State<Arc<LogWriter>>, req: Request, next: Next) -> Response {
+    let start = Instant::now();
+    let remote = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map_or("-".to_string(), |c| c.0.ip().to_string());
+    let request = format!("{} {} {:?}", req.method(), req.uri(), req.version());
+    let referer = header_or_dash(req.headers(), header::REFERER);
+    let agent = header_or_dash(req.headers(), header::USER_AGENT);
+
+    let resp = next.run(req).await;
+    // the content length is set by the server after the middlewares
+    let size = resp
+        .body()
+        .size_hint()
+        .exact()
+        .map_or_else(|| "-".to_string(), |n| n.to_string());
+    let line = format!(
+        "{} - - [{}] \"{}\" {} {} \"{}\" \"{}\" {:.3}ms",
+        remote,
+        chrono::Local::now().format("%d/%b/%Y:%H:%M:%S %z"),
+        request,
+        resp.status().as_u16(),
+        size,
+        referer,
+        agent,
+        start.elapsed().as_secs_f64() * 1000.0
+    );
+    if let Err(e) = log.write_line(line.as_bytes()) {
+        log::warn!("Failed to write the access log: {:#}", e);
+    }
+    resp
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, middleware, Router};
+    use tower::ServiceExt;
+
+    use crate::{conf::Log, web::router, web::WebState};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_access_log() {
+        let path =
+            std::env::temp_dir().join(format!("easeprobe-access-{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let log = Arc::new(
+            LogWriter::new(&Log {
+                file: path.to_string_lossy().to_string(),
+                ..Default::default()
+            })
+            .unwrap(),
+        );
+        let state = Arc::new(WebState::new(Default::default(), vec![]).await);
+        let app: Router = router(state).layer(middleware::from_fn_with_state(log, access_log));
+        for uri in ["/api/v1/status", "/api/v1/probers/http/nil"] {
+            app.clone()
+                .oneshot(
+                    Request::get(uri)
+                        .header(header::USER_AGENT, "curl/8.0")
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+        }
+
+        let lines = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<_> = lines.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("- - - ["));
+        assert!(
+            lines[0].contains("] \"GET /api/v1/status HTTP/1.1\" 200 2 \"-\" \"curl/8.0\" "),
+            "{}",
+            lines[0]
+        );
+        assert!(lines[1].contains("\"GET /api/v1/probers/http/nil HTTP/1.1\" 404 "));
+        let _ = std::fs::remove_file(&path);
+    }
+}
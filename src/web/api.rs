@@ -0,0 +1,369 @@
+use std::{collections::HashMap, sync::Arc};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::RwLock;
+
+use crate::{manager, Channel, ProbeResult, Prober, SlaStat, Snapshot, Status};
+
+use super::{ProberStatus, WebState};
+
+const DEFAULT_PAGE_SIZE: usize = 50;
+const MAX_PAGE_SIZE: usize = 1000;
+
+/// The error of the API in JSON, e.g. `{"error": "invalid status"}`.
+#[derive(Debug)]
+pub struct ApiError(pub StatusCode, pub String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+fn bad_request(msg: String) -> ApiError {
+    ApiError(StatusCode::BAD_REQUEST, msg)
+}
+
+/// The filters and the pagination of the probers.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ProberQuery {
+    /// The statuses separated by commas, e.g. `down,bad`
+    pub status: Option<String>,
+    /// The kinds separated by commas, e.g. `http,tcp`
+    pub kind: Option<String>,
+    /// The regex of the name
+    pub name: Option<String>,
+    /// The channel of the probers
+    pub channel: Option<String>,
+    /// The page number from 1
+    pub page: Option<usize>,
+    pub page_size: Option<usize>,
+}
+
+/// The compiled filters of the query.
+struct Filter {
+    statuses: Vec<Status>,
+    kinds: Vec<String>,
+    name: Option<Regex>,
+    /// The probers of the channel, `None` if it's not filtered by the channel
+    channel: Option<Vec<Arc<RwLock<dyn Prober>>>>,
+}
+
+fn split(s: &Option<String>) -> Vec<String> {
+    s.iter()
+        .flat_map(|s| s.split(','))
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+impl Filter {
+    /// Compiles the filters, the channel is looked up in the channels, it's not found if it's unknown.
+    async fn new(
+        q: &ProberQuery,
+        channels: &HashMap<String, Arc<Channel>>,
+    ) -> Result<Self, ApiError> {
+        let mut statuses = vec![];
+        for s in split(&q.status) {
+            let status = Status::from_string(&s);
+            // the unknown status is the fallback of the invalid ones
+            if status == Status::Unknown && !s.eq_ignore_ascii_case("unknown") {
+                return Err(bad_request(format!("invalid status `{}`", s)));
+            }
+            statuses.push(status);
+        }
+        let name = match q.name.as_deref().filter(|n| !n.is_empty()) {
+            Some(n) => Some(
+                Regex::new(n).map_err(|e| bad_request(format!("invalid name regex - {}", e)))?,
+            ),
+            None => None,
+        };
+        let channel = match q.channel.as_deref().filter(|c| !c.is_empty()) {
+            Some(c) => match channels.get(c) {
+                Some(ch) => Some(ch.get_probers().await),
+                None => {
+                    return Err(ApiError(
+                        StatusCode::NOT_FOUND,
+                        format!("channel {} is not found", c),
+                    ))
+                }
+            },
+            None => None,
+        };
+        Ok(Self {
+            statuses,
+            kinds: split(&q.kind),
+            name,
+            channel,
+        })
+    }
+
+    fn matches(&self, prober: &Arc<RwLock<dyn Prober>>, r: &ProbeResult) -> bool {
+        (self.statuses.is_empty() || self.statuses.contains(&r.status))
+            && (self.kinds.is_empty() || self.kinds.contains(&r.kind))
+            && self.name.as_ref().is_none_or(|re| re.is_match(&r.name))
+            && self
+                .channel
+                .as_ref()
+                .is_none_or(|ps| ps.iter().any(|p| Arc::ptr_eq(p, prober)))
+    }
+}
+
+/// The SLA statistics of a prober.
+#[derive(Debug, Clone, Serialize)]
+pub struct StatDetail {
+    /// The SLA percentage
+    pub sla: f64,
+    pub total: i64,
+    pub counts: serde_json::Map<String, serde_json::Value>,
+    /// The uptime in seconds
+    pub uptime: u64,
+    /// The downtime in seconds
+    pub downtime: u64,
+    pub since: String,
+    pub last_change: String,
+}
+
+/// The status, the channels and the SLA of a prober.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProberDetail {
+    #[serde(flatten)]
+    pub status: ProberStatus,
+    pub channels: Vec<String>,
+    pub stat: StatDetail,
+}
+
+fn rfc3339(time: std::time::SystemTime) -> String {
+    chrono::DateTime::<chrono::Utc>::from(time).to_rfc3339()
+}
+
+impl ProberDetail {
+    pub(crate) fn new(r: &ProbeResult, channels: Vec<String>) -> Self {
+        let s = SlaStat::new(r);
+        Self {
+            status: ProberStatus::from(r),
+            channels,
+            stat: StatDetail {
+                sla: (s.sla * 100.0).round() / 100.0,
+                total: s.total,
+                counts: s
+                    .counts
+                    .iter()
+                    .map(|(status, n)| (status.to_string().to_owned(), json!(n)))
+                    .collect(),
+                uptime: r.stat.uptime.as_secs(),
+                downtime: r.stat.downtime.as_secs(),
+                since: rfc3339(r.stat.since),
+                last_change: rfc3339(s.last_change),
+            },
+        }
+    }
+}
+
+/// A page of the probers.
+#[derive(Debug, Serialize)]
+pub struct ProberPage {
+    /// The number of the probers matching the filters
+    pub total: usize,
+    pub page: usize,
+    pub page_size: usize,
+    pub probers: Vec<ProberDetail>,
+}
+
+/// Lists the SLA of the probers, e.g. `/api/v1/sla?status=down&name=^web&page=2`.
+pub async fn sla(
+    State(state): State<Arc<WebState>>,
+    Query(q): Query<ProberQuery>,
+) -> Result<Json<ProberPage>, ApiError> {
+    let filter = Filter::new(&q, &manager::get_all_channels().await).await?;
+    let page = q.page.unwrap_or(1);
+    let page_size = q.page_size.unwrap_or(DEFAULT_PAGE_SIZE);
+    if page == 0 {
+        return Err(bad_request("page starts from 1".to_string()));
+    }
+    if page_size == 0 || page_size > MAX_PAGE_SIZE {
+        return Err(bad_request(format!(
+            "page_size must be in 1..={}",
+            MAX_PAGE_SIZE
+        )));
+    }
+
+    let mut matched = vec![];
+    for p in &state.probers {
+        let snapshot = Snapshot::of(p).await;
+        if filter.matches(p, &snapshot.result) {
+            matched.push(ProberDetail::new(&snapshot.result, snapshot.channels));
+        }
+    }
+    let total = matched.len();
+    let probers = matched
+        .into_iter()
+        .skip((page - 1).saturating_mul(page_size))
+        .take(page_size)
+        .collect();
+    Ok(Json(ProberPage {
+        total,
+        page,
+        page_size,
+        probers,
+    }))
+}
+
+/// Gets the prober by the kind and name.
+pub async fn prober(
+    State(state): State<Arc<WebState>>,
+    Path((kind, name)): Path<(String, String)>,
+) -> Result<Json<ProberDetail>, ApiError> {
+    for p in &state.probers {
+        let snapshot = Snapshot::of(p).await;
+        if snapshot.result.kind == kind && snapshot.result.name == name {
+            return Ok(Json(ProberDetail::new(&snapshot.result, snapshot.channels)));
+        }
+    }
+    Err(ApiError(
+        StatusCode::NOT_FOUND,
+        format!("prober {}/{} is not found", kind, name),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request, Router};
+    use tower::ServiceExt;
+
+    use crate::{channel::new_dummy_prober, web::router};
+
+    use super::*;
+
+    async fn get(app: Router, uri: &str) -> (StatusCode, serde_json::Value) {
+        let resp = app
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = resp.status();
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    async fn probers() -> Vec<Arc<RwLock<dyn Prober>>> {
+        let mut probers: Vec<Arc<RwLock<dyn Prober>>> = vec![];
+        for (kind, name, status, channel) in [
+            ("http", "web-1", Status::Up, "a"),
+            ("http", "web-2", Status::Down, "b"),
+            ("tcp", "db", Status::Down, "a"),
+            ("tcp", "cache", Status::Bad, "b"),
+        ] {
+            let p: Arc<RwLock<dyn Prober>> =
+                Arc::new(new_dummy_prober(kind, "", name, vec![channel.to_string()]));
+            {
+                let mut p = p.write().await;
+                let r = p.result();
+                r.name = name.to_string();
+                r.kind = kind.to_string();
+                r.status = status;
+                r.stat.total = 10;
+                r.stat.status.insert(status, 10);
+            }
+            probers.push(p);
+        }
+        probers
+    }
+
+    async fn app() -> Router {
+        router(Arc::new(
+            WebState::new(Default::default(), probers().await).await,
+        ))
+    }
+
+    fn names(page: &serde_json::Value) -> Vec<&str> {
+        page["probers"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["name"].as_str().unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_sla() {
+        let app = app().await;
+        let (status, page) = get(app.clone(), "/api/v1/sla").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(page["total"], 4);
+        assert_eq!(page["probers"][0]["channels"][0], "a");
+        assert_eq!(page["probers"][0]["stat"]["counts"]["up"], 10);
+
+        let (_, page) = get(app.clone(), "/api/v1/sla?status=down,bad&kind=tcp").await;
+        assert_eq!(names(&page), ["db", "cache"]);
+        let (_, page) = get(app.clone(), "/api/v1/sla?name=%5Eweb&status=down").await;
+        assert_eq!(names(&page), ["web-2"]);
+        let (status, err) = get(app.clone(), "/api/v1/sla?channel=no-such-channel").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(err["error"], "channel no-such-channel is not found");
+
+        let (_, page) = get(app.clone(), "/api/v1/sla?page=2&page_size=3").await;
+        assert_eq!(page["total"], 4);
+        assert_eq!(names(&page), ["cache"]);
+
+        for uri in [
+            "/api/v1/sla?status=gone",
+            "/api/v1/sla?name=(",
+            "/api/v1/sla?page=0",
+            "/api/v1/sla?page_size=0",
+        ] {
+            let (status, err) = get(app.clone(), uri).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{}", uri);
+            assert!(err["error"].is_string());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_channel_filter() {
+        let probers = probers().await;
+        let channel = Channel::new("a").await;
+        for p in &probers {
+            if p.read().await.channels().contains(&"a".to_string()) {
+                channel.add_prober(p.clone()).await;
+            }
+        }
+        let channels = HashMap::from([("a".to_string(), Arc::new(channel))]);
+        let q = ProberQuery {
+            channel: Some("a".to_string()),
+            ..Default::default()
+        };
+        let filter = Filter::new(&q, &channels).await.unwrap();
+        let mut matched = vec![];
+        for p in &probers {
+            let mut prober = p.write().await;
+            if filter.matches(p, prober.result()) {
+                matched.push(prober.name().to_string());
+            }
+        }
+        assert_eq!(matched, ["web-1", "db"]);
+    }
+
+    #[tokio::test]
+    async fn test_prober() {
+        let app = app().await;
+        let (status, p) = get(app.clone(), "/api/v1/probers/tcp/db").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(p["name"], "db");
+        assert_eq!(p["status"], "down");
+        assert_eq!(p["stat"]["total"], 10);
+
+        let (status, err) = get(app, "/api/v1/probers/http/db").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(err["error"], "prober http/db is not found");
+    }
+}
@@ -0,0 +1,305 @@
+use std::{sync::Arc, time::SystemTime};
+
+use axum::{
+    extract::{Path, Query, Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::Response,
+    Json,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use subtle::ConstantTimeEq;
+use tokio::sync::RwLock;
+
+use crate::{manager, probe_and_send, Prober, Snapshot};
+
+use super::{ApiError, ProberDetail, WebState};
+
+/// Rejects the request without the bearer token, the control API is disabled without the token.
+pub async fn auth(State(state): State<Arc<WebState>>, req: Request, next: Next) -> Response {
+    use axum::response::IntoResponse;
+
+    if state.token.is_empty() {
+        return ApiError(
+            StatusCode::FORBIDDEN,
+            "the control API is disabled, `http.token` is not set".to_string(),
+        )
+        .into_response();
+    }
+    let token = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    // it's compared in the constant time to avoid leaking the token by the timing
+    let valid = token.is_some_and(|t| bool::from(t.as_bytes().ct_eq(state.token.as_bytes())));
+    if !valid {
+        log::warn!(
+            "Rejected the unauthorized control request {} {}",
+            req.method(),
+            req.uri()
+        );
+        return ApiError(StatusCode::UNAUTHORIZED, "invalid token".to_string()).into_response();
+    }
+    next.run(req).await
+}
+
+/// Finds the prober by the snapshot, it doesn't wait for the prober being probed.
+async fn find_prober(
+    state: &WebState,
+    kind: &str,
+    name: &str,
+) -> Result<Arc<RwLock<dyn Prober>>, ApiError> {
+    for p in &state.probers {
+        let r = Snapshot::of(p).await.result;
+        if r.kind == kind && r.name == name {
+            return Ok(p.clone());
+        }
+    }
+    Err(ApiError(
+        StatusCode::NOT_FOUND,
+        format!("prober {}/{} is not found", kind, name),
+    ))
+}
+
+/// Pauses the prober, its latest result is kept.
+pub async fn pause(
+    State(state): State<Arc<WebState>>,
+    Path((kind, name)): Path<(String, String)>,
+) -> Result<Json<Value>, ApiError> {
+    find_prober(&state, &kind, &name).await?;
+    manager::pause_prober(&kind, &name);
+    Ok(Json(json!({ "kind": kind, "name": name, "paused": true })))
+}
+
+pub async fn resume(
+    State(state): State<Arc<WebState>>,
+    Path((kind, name)): Path<(String, String)>,
+) -> Result<Json<Value>, ApiError> {
+    find_prober(&state, &kind, &name).await?;
+    manager::resume_prober(&kind, &name);
+    Ok(Json(json!({ "kind": kind, "name": name, "paused": false })))
+}
+
+/// Probes right now, even if it's paused, the result is sent to the channels as usual.
+pub async fn trigger(
+    State(state): State<Arc<WebState>>,
+    Path((kind, name)): Path<(String, String)>,
+) -> Result<Json<ProberDetail>, ApiError> {
+    let prober = find_prober(&state, &kind, &name).await?;
+    log::info!("[{} / {}] - triggered the probe", kind, name);
+    let result = probe_and_send(&prober).await;
+    let channels = Snapshot::of(&prober).await.channels;
+    Ok(Json(ProberDetail::new(&result, channels)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MuteQuery {
+    /// The duration of the mute, e.g. `30m`
+    duration: String,
+}
+
+fn mute_until(q: &MuteQuery) -> Result<SystemTime, ApiError> {
+    let d = humantime_serde::re::humantime::parse_duration(&q.duration).map_err(|e| {
+        ApiError(
+            StatusCode::BAD_REQUEST,
+            format!("invalid duration `{}` - {}", q.duration, e),
+        )
+    })?;
+    SystemTime::now().checked_add(d).ok_or_else(|| {
+        ApiError(
+            StatusCode::BAD_REQUEST,
+            format!("the duration `{}` is too long", q.duration),
+        )
+    })
+}
+
+fn muted(kind: &str, name: &str, until: Option<SystemTime>) -> Json<Value> {
+    Json(json!({
+        "kind": kind,
+        "name": name,
+        "muted_until": until.map(|t| chrono::DateTime::<chrono::Utc>::from(t).to_rfc3339()),
+    }))
+}
+
+async fn check_notifier(name: &str) -> Result<(), ApiError> {
+    let channels = manager::get_all_channels().await.into_keys().collect();
+    if manager::get_notifiers(channels).await.contains_key(name) {
+        Ok(())
+    } else {
+        Err(ApiError(
+            StatusCode::NOT_FOUND,
+            format!("notifier {} is not found", name),
+        ))
+    }
+}
+
+async fn check_channel(name: &str) -> Result<(), ApiError> {
+    match manager::get_channel(name).await {
+        Some(_) => Ok(()),
+        None => Err(ApiError(
+            StatusCode::NOT_FOUND,
+            format!("channel {} is not found", name),
+        )),
+    }
+}
+
+/// Mutes the notifier for the duration, e.g. `?duration=30m`.
+pub async fn mute_notifier(
+    Path(name): Path<String>,
+    Query(q): Query<MuteQuery>,
+) -> Result<Json<Value>, ApiError> {
+    check_notifier(&name).await?;
+    let until = mute_until(&q)?;
+    manager::mute_notifier(&name, Some(until));
+    Ok(muted("notifier", &name, Some(until)))
+}
+
+pub async fn unmute_notifier(Path(name): Path<String>) -> Result<Json<Value>, ApiError> {
+    check_notifier(&name).await?;
+    manager::mute_notifier(&name, None);
+    Ok(muted("notifier", &name, None))
+}
+
+/// Mutes all the notifiers of the channel for the duration, e.g. `?duration=1h`.
+pub async fn mute_channel(
+    Path(name): Path<String>,
+    Query(q): Query<MuteQuery>,
+) -> Result<Json<Value>, ApiError> {
+    check_channel(&name).await?;
+    let until = mute_until(&q)?;
+    manager::mute_channel(&name, Some(until));
+    Ok(muted("channel", &name, Some(until)))
+}
+
+pub async fn unmute_channel(Path(name): Path<String>) -> Result<Json<Value>, ApiError> {
+    check_channel(&name).await?;
+    manager::mute_channel(&name, None);
+    Ok(muted("channel", &name, None))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, Router};
+    use tower::ServiceExt;
+
+    use crate::{channel::new_dummy_prober, web::router, Status};
+
+    use super::*;
+
+    async fn post(app: Router, uri: &str, token: &str) -> (StatusCode, Value) {
+        let resp = app
+            .oneshot(
+                Request::post(uri)
+                    .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = resp.status();
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    async fn app(name: &str, token: &str) -> Router {
+        let p: Arc<RwLock<dyn Prober>> = Arc::new(new_dummy_prober("http", "", name, vec![]));
+        {
+            let mut p = p.write().await;
+            let r = p.result();
+            r.name = name.to_string();
+            r.kind = "http".to_string();
+        }
+        let mut state = WebState::new(Default::default(), vec![p]).await;
+        state.token = token.to_string();
+        router(Arc::new(state))
+    }
+
+    #[tokio::test]
+    async fn test_auth() {
+        let uri = "/api/v1/probers/http/control-auth/pause";
+        let (status, _) = post(app("control-auth", "").await, uri, "").await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        for token in ["guess", "s3cret-", "s3cre"] {
+            let (status, err) = post(app("control-auth", "s3cret").await, uri, token).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+            assert_eq!(err["error"], "invalid token");
+        }
+        assert!(!manager::is_prober_paused("http", "control-auth"));
+    }
+
+    #[tokio::test]
+    async fn test_control() {
+        let app = app("control-web", "s3cret").await;
+        let (status, body) = post(
+            app.clone(),
+            "/api/v1/probers/http/control-web/pause",
+            "s3cret",
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["paused"], true);
+        assert!(manager::is_prober_paused("http", "control-web"));
+
+        // the paused prober is shown on the dashboard
+        let resp = app
+            .clone()
+            .oneshot(Request::get("/api/v1/status").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let status: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(status[0]["paused"], true);
+
+        let (_, body) = post(
+            app.clone(),
+            "/api/v1/probers/http/control-web/resume",
+            "s3cret",
+        )
+        .await;
+        assert_eq!(body["paused"], false);
+        assert!(!manager::is_prober_paused("http", "control-web"));
+
+        let (status, body) = post(
+            app.clone(),
+            "/api/v1/probers/http/control-web/probe",
+            "s3cret",
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], Status::Up.to_string());
+        assert_eq!(body["stat"]["total"], 1);
+
+        for uri in [
+            "/api/v1/probers/http/nil/pause",
+            "/api/v1/notifiers/nil/mute?duration=1h",
+            "/api/v1/channels/nil/mute?duration=1h",
+        ] {
+            let (status, _) = post(app.clone(), uri, "s3cret").await;
+            assert_eq!(status, StatusCode::NOT_FOUND, "{}", uri);
+        }
+    }
+
+    #[test]
+    fn test_mute_until() {
+        let q = MuteQuery {
+            duration: "30m".to_string(),
+        };
+        let until = mute_until(&q).unwrap();
+        let d = until.duration_since(SystemTime::now()).unwrap();
+        assert!(d.as_secs() > 1790 && d.as_secs() <= 1800);
+        let q = MuteQuery {
+            duration: "soon".to_string(),
+        };
+        assert_eq!(mute_until(&q).unwrap_err().0, StatusCode::BAD_REQUEST);
+        let q = MuteQuery {
+            duration: format!("{}s", u64::MAX),
+        };
+        assert_eq!(mute_until(&q).unwrap_err().0, StatusCode::BAD_REQUEST);
+    }
+}
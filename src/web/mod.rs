@@ -0,0 +1,8 @@
+mod server;
+pub use server::*;
+mod api;
+pub use api::*;
+mod control;
+pub use control::*;
+mod access;
+pub use access::*;
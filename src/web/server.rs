@@ -0,0 +1,318 @@
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+
+use anyhow::{Context, Result};
+use axum::{
+    extract::State,
+    http::header,
+    middleware,
+    response::{Html, IntoResponse},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::Serialize;
+use tokio::sync::RwLock;
+
+use crate::{
+    conf::HTTPServer, escape_html, global, logger::LogWriter, manager, metric, sla_percent,
+    ProbeResult, Prober, Snapshot, DEFAULT_PROBE_INTERVAL,
+};
+
+/// The shared state of the handlers.
+pub struct WebState {
+    pub probers: Vec<Arc<RwLock<dyn Prober>>>,
+    /// The auto-refresh interval of the dashboard
+    pub refresh: Duration,
+    /// The bearer token of the control API
+    pub token: String,
+}
+
+impl WebState {
+    /// The state of the probers, the refresh defaults to the minimum probe interval.
+    pub async fn new(refresh: Duration, probers: Vec<Arc<RwLock<dyn Prober>>>) -> Self {
+        let refresh = if refresh.is_zero() {
+            let mut min = None;
+            for p in &probers {
+                let interval = *p.read().await.interval();
+                min = Some(min.map_or(interval, |m: Duration| m.min(interval)));
+            }
+            min.unwrap_or(DEFAULT_PROBE_INTERVAL)
+        } else {
+            refresh
+        };
+        Self {
+            probers,
+            refresh,
+            token: String::new(),
+        }
+    }
+
+    /// The latest results, the probers being probed are not waited for.
+    pub async fn results(&self) -> Vec<ProbeResult> {
+        let mut results = Vec::with_capacity(self.probers.len());
+        for p in &self.probers {
+            results.push(Snapshot::of(p).await.result);
+        }
+        results
+    }
+}
+
+/// The status of a prober on the dashboard.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProberStatus {
+    pub name: String,
+    pub kind: String,
+    pub endpoint: String,
+    pub status: String,
+    pub message: String,
+    /// The round trip time in milliseconds
+    pub rtt: u64,
+    /// The uptime percentage
+    pub uptime: f64,
+    /// The time of the last check in RFC 3339
+    pub last_check: String,
+    pub paused: bool,
+}
+
+impl From<&ProbeResult> for ProberStatus {
+    fn from(r: &ProbeResult) -> Self {
+        Self {
+            name: r.name.clone(),
+            kind: r.kind.clone(),
+            endpoint: r.endpoint.clone(),
+            status: r.status.to_string().to_owned(),
+            message: r.message.clone(),
+            rtt: r.round_trip_time.as_millis() as u64,
+            uptime: (sla_percent(&r.stat) * 100.0).round() / 100.0,
+            last_check: chrono::DateTime::<chrono::Utc>::from(r.start_time).to_rfc3339(),
+            paused: manager::is_prober_paused(&r.kind, &r.name),
+        }
+    }
+}
+
+/// Renders the dashboard, it reloads itself every `refresh`.
+fn dashboard(results: &[ProbeResult], refresh: Duration) -> String {
+    let e = escape_html;
+    let mut rows = String::new();
+    for r in results {
+        let paused = if manager::is_prober_paused(&r.kind, &r.name) {
+            " (⏸️ paused)"
+        } else {
+            ""
+        };
+        rows.push_str(&format!(
+            "<tr class=\"{}\"><td>{} {}{}</td><td>{}</td><td>{}</td><td>{}</td>\
+            <td>{}ms</td><td>{:.2}%</td><td>{}</td></tr>\n",
+            r.status,
+            r.status.emoji(),
+            r.status,
+            paused,
+            e(&r.name),
+            e(&r.endpoint),
+            e(&r.message),
+            r.round_trip_time.as_millis(),
+            sla_percent(&r.stat),
+            global::format_time(r.start_time)
+        ));
+    }
+    let mut muted = String::new();
+    for (kind, mutes) in [
+        ("Channel", manager::muted_channels()),
+        ("Notifier", manager::muted_notifiers()),
+    ] {
+        for (name, until) in mutes {
+            muted.push_str(&format!(
+                "<li>🔇 {} {} is muted until {}</li>\n",
+                kind,
+                e(&name),
+                global::format_time(until)
+            ));
+        }
+    }
+    if !muted.is_empty() {
+        muted = format!("<ul class=\"muted\">\n{}</ul>\n", muted);
+    }
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta http-equiv="refresh" content="{refresh}">
+<title>EaseProbe Status</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em; }}
+table {{ border-collapse: collapse; width: 100%; }}
+th, td {{ border: 1px solid #ddd; padding: 6px 10px; text-align: left; }}
+th {{ background: #f4f4f4; }}
+tr.down td {{ background: #fdecea; }}
+tr.bad td, tr.unknown td {{ background: #fff4e5; }}
+footer {{ margin-top: 1em; color: #888; }}
+</style>
+</head>
+<body>
+<h1>EaseProbe Status</h1>
+<table>
+<tr><th>Status</th><th>Name</th><th>Endpoint</th><th>Message</th><th>RTT</th><th>Uptime</th><th>Last Check</th></tr>
+{rows}</table>
+{muted}<footer>{footer} - refreshed every {refresh}s at {time}</footer>
+</body>
+</html>
+"#,
+        refresh = refresh.as_secs().max(1),
+        rows = rows,
+        muted = muted,
+        footer = e(&global::footer_string()),
+        time = global::format_time(std::time::SystemTime::now()),
+    )
+}
+
+async fn index(State(state): State<Arc<WebState>>) -> impl IntoResponse {
+    let results = state.results().await;
+    (
+        [(header::CACHE_CONTROL, "no-cache")],
+        Html(dashboard(&results, state.refresh)),
+    )
+}
+
+async fn metrics() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        metric::render(),
+    )
+}
+
+async fn status(State(state): State<Arc<WebState>>) -> Json<Vec<ProberStatus>> {
+    let results = state.results().await;
+    Json(results.iter().map(ProberStatus::from).collect())
+}
+
+/// The routes of the status server.
+pub fn router(state: Arc<WebState>) -> Router {
+    let control = Router::new()
+        .route("/api/v1/probers/{kind}/{name}/pause", post(super::pause))
+        .route("/api/v1/probers/{kind}/{name}/resume", post(super::resume))
+        .route("/api/v1/probers/{kind}/{name}/probe", post(super::trigger))
+        .route("/api/v1/notifiers/{name}/mute", post(super::mute_notifier))
+        .route(
+            "/api/v1/notifiers/{name}/unmute",
+            post(super::unmute_notifier),
+        )
+        .route("/api/v1/channels/{name}/mute", post(super::mute_channel))
+        .route(
+            "/api/v1/channels/{name}/unmute",
+            post(super::unmute_channel),
+        )
+        .route_layer(middleware::from_fn_with_state(state.clone(), super::auth));
+    Router::new()
+        .merge(control)
+        .route("/", get(index))
+        .route("/api/v1/status", get(status))
+        .route("/api/v1/sla", get(super::sla))
+        .route("/api/v1/probers/{kind}/{name}", get(super::prober))
+        .route("/metrics", get(metrics))
+        .with_state(state)
+}
+
+/// Serves the status of the probers until the process exits.
+pub async fn serve(conf: &HTTPServer, probers: Vec<Arc<RwLock<dyn Prober>>>) -> Result<()> {
+    let ip = if conf.ip.is_empty() {
+        "0.0.0.0"
+    } else {
+        &conf.ip
+    };
+    let addr: SocketAddr = format!("{}:{}", ip, conf.port)
+        .parse()
+        .or_else(|_| format!("[{}]:{}", ip, conf.port).parse())
+        .with_context(|| format!("invalid http server address {}:{}", ip, conf.port))?;
+    let mut state = WebState::new(conf.refresh, probers).await;
+    state.token = conf.token.clone();
+    let state = Arc::new(state);
+    let access = Arc::new(LogWriter::new(&conf.log)?);
+    let app = router(state).layer(middleware::from_fn_with_state(access, super::access_log));
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("failed to listen on {}", addr))?;
+    log::info!("The http server is listening on {}", addr);
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use tower::ServiceExt;
+
+    use crate::{channel::new_dummy_prober, Status};
+
+    use super::*;
+
+    async fn get(app: Router, uri: &str) -> (String, String) {
+        let resp = app
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert!(resp.status().is_success());
+        let content_type = resp.headers()[header::CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (content_type, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    async fn state() -> Arc<WebState> {
+        let prober: Arc<RwLock<dyn Prober>> = Arc::new(new_dummy_prober("http", "", "web", vec![]));
+        {
+            let mut p = prober.write().await;
+            let r = p.result();
+            r.name = "<web>".to_string();
+            r.kind = "http".to_string();
+            r.endpoint = "https://example.com".to_string();
+            r.status = Status::Down;
+            r.message = "HTTP Status Code is 500".to_string();
+            r.round_trip_time = Duration::from_millis(42);
+            r.stat.uptime = Duration::from_secs(90);
+            r.stat.downtime = Duration::from_secs(10);
+        }
+        Arc::new(WebState::new(Duration::ZERO, vec![prober]).await)
+    }
+
+    #[tokio::test]
+    async fn test_dashboard() {
+        let state = state().await;
+        // the interval of the dummy prober
+        assert_eq!(state.refresh, Duration::from_secs(5));
+        let (content_type, html) = get(router(state), "/").await;
+        assert!(content_type.starts_with("text/html"));
+        assert!(html.contains(r#"<meta http-equiv="refresh" content="5">"#));
+        assert!(html.contains(
+            "<tr class=\"down\"><td>❌ down</td><td>&lt;web&gt;</td><td>https://example.com</td>\
+            <td>HTTP Status Code is 500</td><td>42ms</td><td>90.00%</td>"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_status() {
+        let (content_type, json) = get(router(state().await), "/api/v1/status").await;
+        assert_eq!(content_type, "application/json");
+        let status: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(status[0]["name"], "<web>");
+        assert_eq!(status[0]["status"], "down");
+        assert_eq!(status[0]["rtt"], 42);
+        assert_eq!(status[0]["uptime"], 90.0);
+
+        let (content_type, metrics) = get(router(state().await), "/metrics").await;
+        assert_eq!(content_type, "text/plain; version=0.0.4");
+        assert!(metrics.contains("# TYPE easeprobe_probe_total counter"));
+
+        let state = WebState::new(Duration::from_secs(3), vec![]).await;
+        assert_eq!(state.refresh, Duration::from_secs(3));
+        let state = WebState::new(Duration::ZERO, vec![]).await;
+        assert_eq!(state.refresh, DEFAULT_PROBE_INTERVAL);
+    }
+}